@@ -0,0 +1,72 @@
+//! Фаззинг комбинирования долей: `cargo +nightly fuzz run combine`.
+//! Каждые 12 байт входа -- доля (номер u64 и значение u32, little-endian);
+//! ни одна операция над произвольным набором долей не должна паниковать.
+//! Те же доли с числом блоков от 0 до 3 подаются схемам с произвольным порогом
+//! и взвешенным участникам. Доли не несут отпечатка, поэтому схема принимает доли версии 1,
+//! иначе голосование и восстановление не доходили бы до разбора значений.
+//! Тот же вход как текст разбирается читателями манифеста и блок-дизайна GAP.
+#![no_main]
+
+use hadamard_sss::design::from_gap;
+use hadamard_sss::general::GeneralizedScheme;
+use hadamard_sss::manifest::ShareSetManifest;
+use hadamard_sss::scheme_traits::SchemeExt;
+use hadamard_sss::{ChunkedPart, HadamardSSS, Part, ParticipantRows, SharingScheme};
+use libfuzzer_sys::fuzz_target;
+use ndarray::Array2;
+use std::sync::OnceLock;
+
+fn scheme() -> &'static HadamardSSS {
+    static SCHEME: OnceLock<HadamardSSS> = OnceLock::new();
+    SCHEME.get_or_init(|| {
+        let mtx = Array2::from_shape_fn((8, 8), |(i, j)| if (i & j).count_ones() % 2 == 0 { 1 } else { -1 });
+        let mut hsss = HadamardSSS::from(&mtx).expect("Sylvester matrix is a Hadamard matrix");
+        hsss.set_accept_legacy(true);
+        hsss
+    })
+}
+
+fn generalized() -> &'static [GeneralizedScheme; 2] {
+    static SCHEMES: OnceLock<[GeneralizedScheme; 2]> = OnceLock::new();
+    SCHEMES.get_or_init(|| {
+        [
+            GeneralizedScheme::new(6, 10).expect("6-of-10 is a valid threshold"),
+            GeneralizedScheme::new(3, 10).expect("3-of-10 is a valid threshold"),
+        ]
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = ShareSetManifest::from_json(text);
+        let _ = from_gap(text);
+    }
+
+    let parts: Vec<Part> = data
+        .chunks_exact(12)
+        .map(|chunk| {
+            let number = u64::from_le_bytes(chunk[..8].try_into().unwrap());
+            let value = u32::from_le_bytes(chunk[8..].try_into().unwrap());
+            Part::from(number as usize, value)
+        })
+        .collect();
+    let hsss = scheme();
+    let _ = hsss.reconstruct(parts.clone());
+    let _ = hsss.reconstruct_strict(parts.clone());
+    let _ = hsss.reconstruct_robust(parts.clone());
+    let _ = hsss.validate(parts.clone());
+    let _ = hsss.validate_design(parts.clone());
+    let _ = hsss.complete_shares(parts.clone(), &[0]);
+
+    let chunked: Vec<ChunkedPart> = parts
+        .iter()
+        .map(|part| ChunkedPart::from(part.number(), vec![part.data(); (part.data() % 4) as usize]))
+        .collect();
+    let mapping = ParticipantRows::weighted(&[3, 1, 1, 1, 1]).expect("weights are positive");
+    let _ = hsss.reconstruct_weighted(chunked.clone(), &mapping);
+    let _ = hsss.validate_weighted(chunked.clone(), &mapping);
+    for scheme in generalized() {
+        let _ = scheme.reconstruct(chunked.clone());
+        let _ = scheme.validate(chunked.clone());
+    }
+});
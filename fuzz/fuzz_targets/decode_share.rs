@@ -0,0 +1,16 @@
+//! Фаззинг декодера долей: `cargo +nightly fuzz run decode_share`.
+#![no_main]
+
+use hadamard_sss::decode::decode_share;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((_, part)) = decode_share(data) {
+        // успешно декодированная доля с отпечатком кодируется обратно в двоичный формат без потерь
+        if part.fingerprint().is_some() {
+            let bytes = part.to_bytes().expect("decoded part has a wire encoding");
+            let (_, again) = decode_share(&bytes).expect("wire encoding of a decoded part is valid");
+            assert_eq!((again.number(), again.data(), again.fingerprint()), (part.number(), part.data(), part.fingerprint()));
+        }
+    }
+});
@@ -0,0 +1,311 @@
+//! Инструменты анализа свойств схемы.
+use crate::entropy::RngBackend;
+use crate::scheme_impl::Part;
+use crate::validation::ShareVerdict;
+use crate::{HadamardSSS, SharingScheme};
+use rand::seq::index::sample;
+use rand::Rng;
+
+/// Перебор всех k-элементных подмножеств множества {0, ..., n - 1} в лексикографическом порядке.
+pub(crate) struct Combinations {
+    /// Текущее подмножество.
+    current: Vec<usize>,
+    /// Мощность исходного множества.
+    n: usize,
+    /// Признак окончания перебора.
+    done: bool,
+}
+
+impl Combinations {
+    /// Начало перебора k-элементных подмножеств n-элементного множества.
+    pub(crate) fn new(n: usize, k: usize) -> Self {
+        Combinations { current: (0..k).collect(), n, done: k > n }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        if self.done {
+            return None;
+        }
+        let res = self.current.clone();
+        let k = self.current.len();
+        match (0..k).rev().find(|&i| self.current[i] < self.n - k + i) {
+            Some(i) => {
+                self.current[i] += 1;
+                for j in i + 1..k {
+                    self.current[j] = self.current[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(res)
+    }
+}
+
+/// Число сочетаний из n по k (с насыщением при переполнении).
+pub(crate) fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k.min(n - k)).fold(1usize, |acc, i| acc.saturating_mul(n - i) / (i + 1))
+}
+
+/// Результат эмпирической проверки секретности для коалиций размера threshold - 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecrecyReport {
+    /// Пороговое значение проверенной схемы.
+    threshold: usize,
+    /// Проверенные коалиции и маски битов секрета, которые каждая из них определила.
+    determined: Vec<(Vec<usize>, u32)>,
+    /// Источник случайности, которым заполнялись биты-заполнители.
+    rng_backend: RngBackend,
+}
+
+/// Реализация методов отчёта о секретности.
+impl SecrecyReport {
+    /// Возвращение значения поля threshold.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Возвращение значения поля rng_backend.
+    pub fn rng_backend(&self) -> RngBackend {
+        self.rng_backend
+    }
+
+    /// Проверенные коалиции с масками определённых ими битов секрета.
+    pub fn determined(&self) -> &[(Vec<usize>, u32)] {
+        &self.determined
+    }
+
+    /// Ни одна проверенная коалиция не определила ни одного бита секрета.
+    pub fn is_perfect(&self) -> bool {
+        self.determined.iter().all(|(_, mask)| *mask == 0)
+    }
+
+    /// Коалиции, определившие секрет целиком.
+    pub fn recovering_coalitions(&self) -> Vec<&[usize]> {
+        self.determined
+            .iter()
+            .filter(|(_, mask)| *mask == u32::MAX)
+            .map(|(coalition, _)| coalition.as_slice())
+            .collect()
+    }
+
+    /// Наибольшее число битов секрета, определённое одной коалицией.
+    pub fn max_determined_bits(&self) -> u32 {
+        self.determined.iter().map(|(_, mask)| mask.count_ones()).max().unwrap_or(0)
+    }
+}
+
+/// Эмпирическая проверка того, какие биты секрета определяют коалиции из threshold - 1 участников.
+///
+/// Разделяется samples случайных секретов. Бит считается определённым коалицией, если
+/// в каждом разделении он совпал с соответствующим битом доли хотя бы одного её участника.
+/// Случайный бит-заполнитель проходит такую проверку с вероятностью 2^-samples, поэтому
+/// samples стоит брать не меньше 32. Если коалиций больше max_coalitions, проверяется
+/// случайная выборка из max_coalitions коалиций.
+///
+/// Противник здесь знает матрицу: коалиция всегда узнаёт биты, закреплённые за столбцами,
+/// покрытыми её строками. Поэтому для матриц Адамара отчёт, как правило, не является
+/// совершенным, а секретность схемы опирается на то, что матрица хранится у дилера.
+/// Функция позволяет увидеть, сколько битов раскрывается при её компрометации,
+/// и сравнить между собой нестандартные матрицы.
+pub fn subthreshold_secrecy(scheme: &HadamardSSS, samples: usize, max_coalitions: usize) -> SecrecyReport {
+    let n = scheme.hss.mtx_len();
+    let mut rng = crate::entropy::rng();
+    let mut agree = vec![u32::MAX; n];
+    for _ in 0..samples {
+        let secret: u32 = rng.gen();
+        let parts = scheme.share(secret).expect("sharing a u32 secret does not fail");
+        for part in parts {
+            agree[part.number()] &= !(part.data() ^ secret);
+        }
+    }
+
+    let k = scheme.threshold.saturating_sub(1).min(n);
+    let coalitions: Vec<Vec<usize>> = if binomial(n, k) <= max_coalitions {
+        Combinations::new(n, k).collect()
+    } else {
+        (0..max_coalitions)
+            .map(|_| {
+                let mut coalition = sample(&mut rng, n, k).into_vec();
+                coalition.sort_unstable();
+                coalition
+            })
+            .collect()
+    };
+    let determined = coalitions
+        .into_iter()
+        .map(|coalition| {
+            let mask = coalition.iter().fold(0, |mask, &i| mask | agree[i]);
+            (coalition, mask)
+        })
+        .collect();
+    SecrecyReport { threshold: scheme.threshold, determined, rng_backend: crate::entropy::backend() }
+}
+
+/// Группа долей, которые, по-видимому, искажены в сговоре.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollusionCluster {
+    /// Номера долей группы по возрастанию.
+    members: Vec<usize>,
+    /// Число наборов, в которых не менее двух долей группы признаны подозрительными вместе.
+    support: usize,
+}
+
+/// Реализация методов группы сговора.
+impl CollusionCluster {
+    /// Возвращение значения поля members.
+    pub fn members(&self) -> &[usize] {
+        &self.members
+    }
+
+    /// Возвращение значения поля support.
+    pub fn support(&self) -> usize {
+        self.support
+    }
+}
+
+/// Поиск групп сговора по наборам долей.
+///
+/// В каждом наборе подозрительные доли определяются [HadamardSSS::validate_design]. Две
+/// подозрительные доли считаются действующими заодно, если они согласованы между собой
+/// на общих битах и противоречат хотя бы одной общей честной доле. Пары, которые так вели
+/// себя не менее чем в min_support наборах и ни разу не противоречили друг другу,
+/// объединяются в группы. Случайно совпавшие независимые искажения редко повторяются
+/// от набора к набору, поэтому при нескольких наборах min_support стоит брать больше 1.
+pub fn collusion_clusters(scheme: &HadamardSSS, sets: &[Vec<Part>], min_support: usize) -> Vec<CollusionCluster> {
+    let n = scheme.hss.mtx_len();
+    let masks = scheme.masks();
+    let mut together = vec![vec![0usize; n]; n];
+    let mut split = vec![vec![false; n]; n];
+    let mut flagged_sets: Vec<Vec<usize>> = Vec::new();
+    for parts in sets {
+        let liars: Vec<usize> = scheme
+            .validate_design(parts.clone())
+            .verdicts()
+            .iter()
+            .filter(|verdict| !verdict.is_foreign())
+            .map(ShareVerdict::number)
+            .collect();
+        let data = |number: usize| parts.iter().find(|part| part.number() == number).map(Part::data);
+        let contradicts = |a: usize, b: usize| match (data(a), data(b)) {
+            (Some(x), Some(y)) => (x ^ y) & masks[a] & masks[b] != 0,
+            _ => false,
+        };
+        let honest: Vec<usize> = parts
+            .iter()
+            .map(Part::number)
+            .filter(|&number| number < n && !liars.contains(&number))
+            .collect();
+        for (i, &a) in liars.iter().enumerate() {
+            for &b in &liars[i + 1..] {
+                if contradicts(a, b) {
+                    split[a][b] = true;
+                } else if honest.iter().any(|&c| contradicts(a, c) && contradicts(b, c)) {
+                    together[a][b] += 1;
+                }
+            }
+        }
+        flagged_sets.push(liars);
+    }
+
+    // объединение пар в группы (система непересекающихся множеств)
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn root(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+    for a in 0..n {
+        for b in a + 1..n {
+            if together[a][b] >= min_support.max(1) && !split[a][b] {
+                let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+                parent[ra] = rb;
+            }
+        }
+    }
+    let mut groups: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for number in 0..n {
+        let r = root(&mut parent, number);
+        groups[r].push(number);
+    }
+    let mut clusters: Vec<CollusionCluster> = groups
+        .into_iter()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let support = flagged_sets
+                .iter()
+                .filter(|liars| liars.iter().filter(|number| members.contains(number)).count() > 1)
+                .count();
+            CollusionCluster { members, support }
+        })
+        .collect();
+    clusters.sort_by_key(|cluster| cluster.members[0]);
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_combinations() {
+        let all: Vec<Vec<usize>> = Combinations::new(4, 2).collect();
+        assert_eq!(all, vec![vec![0, 1], vec![0, 2], vec![0, 3], vec![1, 2], vec![1, 3], vec![2, 3]]);
+        assert_eq!(Combinations::new(7, 4).count(), binomial(7, 4));
+        assert_eq!(Combinations::new(3, 0).count(), 1);
+        assert_eq!(Combinations::new(2, 3).count(), 0);
+    }
+
+    #[test]
+    fn test_subthreshold_secrecy_matches_coverage() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let report = subthreshold_secrecy(&hsss, 64, 1000);
+        assert_eq!(report.threshold(), 5);
+        assert_eq!(report.rng_backend(), crate::entropy::backend());
+        assert_eq!(report.determined().len(), 35);
+        for (coalition, mask) in report.determined() {
+            let coverage = coalition.iter().fold(0, |m, &i| m | hsss.hss.mask(i));
+            assert_eq!(*mask, coverage);
+        }
+        assert!(!report.is_perfect());
+        // 4 строки не покрывают столбец, только если это в точности его нулевые позиции
+        assert_eq!(report.recovering_coalitions().len(), 28);
+
+        let sampled = subthreshold_secrecy(&hsss, 64, 10);
+        assert_eq!(sampled.determined().len(), 10);
+    }
+
+    #[test]
+    fn test_collusion_clusters() {
+        let hsss = HadamardSSS::from(&crate::fixtures::matrix()).unwrap();
+        let sets: Vec<Vec<Part>> = [1, 2, 3]
+            .iter()
+            .map(|&secret| {
+                let mut parts = hsss.share(secret).unwrap();
+                for i in [0, 3] {
+                    parts[i] = parts[i].blinded(hsss.hss.mask(i));
+                }
+                parts
+            })
+            .collect();
+        let clusters = collusion_clusters(&hsss, &sets, 3);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].members(), [0, 3]);
+        assert_eq!(clusters[0].support(), 3);
+        assert!(collusion_clusters(&hsss, &sets[0..2], 3).is_empty());
+
+        // одиночное искажение группы не образует
+        let mut parts = hsss.share(4).unwrap();
+        parts[5] = parts[5].blinded(hsss.hss.mask(5));
+        assert!(collusion_clusters(&hsss, &[parts], 1).is_empty());
+    }
+}
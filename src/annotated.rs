@@ -0,0 +1,311 @@
+//! Построчный текстовый формат для хранения долей и манифестов в системе контроля версий.
+//!
+//! Каждая строка -- пара `ключ: значение`, за которой через ` | ` следует контрольная сумма
+//! строки (первые 4 байта SHA-256 от `ключ: значение` в шестнадцатеричной записи). Строки,
+//! начинающиеся с `#`, и пустые строки -- комментарии. Изменение одной доли меняет одну
+//! строку, поэтому файлы читаемо сравниваются, а случайная порча строки при слиянии или
+//! ручной правке обнаруживается при чтении.
+//!
+//! ```text
+//! # hadamard_sss annotated v1
+//! format: shares | 712c4dec
+//! custodian_group: ops | e89b83cb
+//! share: 0 deadbeef 1a2b3c4d | 8059aca3
+//! ```
+//!
+//! Строка доли -- номер, значение и отпечаток схемы; у доли без отпечатка третьего поля нет.
+use crate::manifest::{ManifestEntry, ShareSetManifest};
+use crate::scheme_impl::Part;
+use sha2::{Digest, Sha256};
+#[cfg(feature = "encryption")]
+use crate::encrypted::EncryptedShare;
+
+/// Строка-заголовок документа.
+const HEADER: &str = "# hadamard_sss annotated v1";
+
+/// Контрольная сумма строки.
+fn checksum(key: &str, value: &str) -> String {
+    let digest = Sha256::new().chain_update(key).chain_update(": ").chain_update(value).finalize();
+    hex(&digest[..4])
+}
+
+/// Запись байтов шестнадцатеричной строкой.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Чтение байтов из шестнадцатеричной строки.
+fn unhex(s: &str) -> Result<Vec<u8>, &'static str> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return Err("invalid hex");
+    }
+    (0..s.len() / 2).map(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).map_err(|_| "invalid hex")).collect()
+}
+
+/// Чтение массива байтов фиксированной длины из шестнадцатеричной строки.
+fn unhex_array<const N: usize>(s: &str) -> Result<[u8; N], &'static str> {
+    unhex(s)?.try_into().map_err(|_| "wrong hex length")
+}
+
+/// Документ из упорядоченных пар ключ-значение.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Document {
+    /// Поля документа в порядке следования.
+    fields: Vec<(String, String)>,
+}
+
+/// Реализация методов документа.
+impl Document {
+    /// Пустой документ.
+    pub fn new() -> Self {
+        Document::default()
+    }
+
+    /// Добавление поля. Ключ состоит из строчных латинских букв, цифр и `_`,
+    /// значение не содержит переводов строк.
+    pub fn push(&mut self, key: &str, value: impl Into<String>) -> Result<(), &'static str> {
+        let value = value.into();
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_') {
+            return Err("invalid key");
+        }
+        if value.contains(['\n', '\r']) || value.contains(" | ") || value.trim() != value {
+            return Err("value cannot be stored on one line");
+        }
+        self.fields.push((key.to_string(), value));
+        Ok(())
+    }
+
+    /// Возвращение полей документа.
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// Значение первого поля с данным ключом.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Значения всех полей с данным ключом.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.fields.iter().filter(move |(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// Запись документа.
+    pub fn to_text(&self) -> String {
+        let mut res = format!("{}\n", HEADER);
+        for (key, value) in &self.fields {
+            res.push_str(&format!("{}: {} | {}\n", key, value, checksum(key, value)));
+        }
+        res
+    }
+
+    /// Чтение документа. Возвращается ошибка, если строка не имеет вида
+    /// `ключ: значение | сумма` или сумма не сходится.
+    pub fn parse(text: &str) -> Result<Self, &'static str> {
+        let mut document = Document::new();
+        for line in text.lines().map(str::trim_end).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let (content, sum) = line.rsplit_once(" | ").ok_or("line has no checksum")?;
+            let (key, value) = content.split_once(": ").ok_or("line is not a key: value pair")?;
+            if checksum(key, value) != sum {
+                return Err("line checksum mismatch");
+            }
+            document.push(key, value)?;
+        }
+        Ok(document)
+    }
+
+    /// Значение обязательного поля.
+    fn require(&self, key: &str) -> Result<&str, &'static str> {
+        self.get(key).ok_or("missing field")
+    }
+
+    /// Проверка вида документа.
+    fn expect_format(&self, format: &str) -> Result<(), &'static str> {
+        if self.require("format")? != format {
+            return Err("unexpected document format");
+        }
+        Ok(())
+    }
+}
+
+/// Документ с долями и дополнительными полями metadata.
+pub fn shares_to_text(parts: &[Part], metadata: &[(&str, &str)]) -> Result<String, &'static str> {
+    let mut document = Document::new();
+    document.push("format", "shares")?;
+    for &(key, value) in metadata {
+        document.push(key, value)?;
+    }
+    for part in parts {
+        let value = match part.fingerprint() {
+            Some(fingerprint) => format!("{} {:08x} {:08x}", part.number(), part.data(), fingerprint),
+            None => format!("{} {:08x}", part.number(), part.data()),
+        };
+        document.push("share", value)?;
+    }
+    Ok(document.to_text())
+}
+
+/// Чтение долей, записанных [shares_to_text]; возвращается и сам документ с метаданными.
+pub fn shares_from_text(text: &str) -> Result<(Vec<Part>, Document), &'static str> {
+    let document = Document::parse(text)?;
+    document.expect_format("shares")?;
+    let parts = document
+        .get_all("share")
+        .map(|value| {
+            let fields: Vec<&str> = value.split(' ').collect();
+            let (number, data, fingerprint) = match fields[..] {
+                [number, data] => (number, data, None),
+                [number, data, fingerprint] => (number, data, Some(fingerprint)),
+                _ => return Err("malformed share line"),
+            };
+            let number = number.parse().map_err(|_| "malformed share number")?;
+            let part = Part::from(number, u32::from_be_bytes(unhex_array(data)?));
+            Ok(match fingerprint {
+                Some(fingerprint) => part.with_fingerprint(u32::from_be_bytes(unhex_array(fingerprint)?)),
+                None => part,
+            })
+        })
+        .collect::<Result<_, &'static str>>()?;
+    Ok((parts, document))
+}
+
+/// Документ с зашифрованными долями и дополнительными полями metadata.
+#[cfg(feature = "encryption")]
+pub fn encrypted_shares_to_text(shares: &[EncryptedShare], metadata: &[(&str, &str)]) -> Result<String, &'static str> {
+    let mut document = Document::new();
+    document.push("format", "encrypted_shares")?;
+    for &(key, value) in metadata {
+        document.push(key, value)?;
+    }
+    for share in shares {
+        document.push("share", format!("{} {} {}", share.number(), hex(&share.nonce()), hex(share.ciphertext())))?;
+    }
+    Ok(document.to_text())
+}
+
+/// Чтение зашифрованных долей, записанных [encrypted_shares_to_text].
+#[cfg(feature = "encryption")]
+pub fn encrypted_shares_from_text(text: &str) -> Result<(Vec<EncryptedShare>, Document), &'static str> {
+    let document = Document::parse(text)?;
+    document.expect_format("encrypted_shares")?;
+    let shares = document
+        .get_all("share")
+        .map(|value| match value.split(' ').collect::<Vec<_>>()[..] {
+            [number, nonce, ciphertext] => Ok(EncryptedShare::from(
+                number.parse().map_err(|_| "malformed share number")?,
+                unhex_array(nonce)?,
+                unhex(ciphertext)?,
+            )),
+            _ => Err("malformed share line"),
+        })
+        .collect::<Result<_, &'static str>>()?;
+    Ok((shares, document))
+}
+
+/// Документ с манифестом: по строке на каждое поле и на каждую запись о доле.
+pub fn manifest_to_text(manifest: &ShareSetManifest) -> Result<String, &'static str> {
+    let mut document = Document::new();
+    document.push("format", "manifest")?;
+    document.push("order", manifest.order().to_string())?;
+    document.push("threshold", manifest.threshold().to_string())?;
+    document.push("created_at", manifest.created_at().to_string())?;
+    document.push("nonce", hex(&manifest.nonce()))?;
+    if let Some(salt) = manifest.assignment_salt() {
+        document.push("assignment_salt", hex(&salt))?;
+    }
+    for entry in manifest.entries() {
+        document.push("share", format!("{} {} {}", entry.number(), hex(&entry.digest()), entry.custodian()))?;
+    }
+    Ok(document.to_text())
+}
+
+/// Чтение манифеста, записанного [manifest_to_text].
+pub fn manifest_from_text(text: &str) -> Result<ShareSetManifest, &'static str> {
+    let document = Document::parse(text)?;
+    document.expect_format("manifest")?;
+    let number = |key: &str| -> Result<u64, &'static str> { document.require(key)?.parse().map_err(|_| "malformed number") };
+    let entries = document
+        .get_all("share")
+        .map(|value| {
+            let mut fields = value.splitn(3, ' ');
+            let (number, digest, custodian) = (fields.next(), fields.next(), fields.next());
+            let (Some(number), Some(digest), Some(custodian)) = (number, digest, custodian) else {
+                return Err("malformed share line");
+            };
+            Ok(ManifestEntry::assemble(number.parse().map_err(|_| "malformed share number")?, custodian.to_string(), unhex_array(digest)?))
+        })
+        .collect::<Result<_, &'static str>>()?;
+    Ok(ShareSetManifest::assemble(
+        number("order")? as usize,
+        number("threshold")? as usize,
+        number("created_at")?,
+        unhex_array(document.require("nonce")?)?,
+        entries,
+        document.get("assignment_salt").map(unhex_array).transpose()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::{HadamardSSS, SharingScheme};
+
+    #[test]
+    fn test_shares_roundtrip() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let text = shares_to_text(&parts, &[("custodian_group", "ops east")]).unwrap();
+        assert_eq!(text.lines().count(), 1 + 2 + 7);
+        // комментарии и пустые строки при чтении пропускаются
+        let annotated = text.replace("share: 3", "# rotated 2026-10-01\n\nshare: 3");
+        let (read, document) = shares_from_text(&annotated).unwrap();
+        assert_eq!(document.get("custodian_group"), Some("ops east"));
+        assert!(read.iter().zip(&parts).all(|(a, b)| (a.number(), a.data(), a.fingerprint()) == (b.number(), b.data(), b.fingerprint())));
+        assert_eq!(hsss.reconstruct(read).unwrap(), 314159265);
+        assert!(shares_to_text(&parts, &[("Bad Key", "x")]).is_err());
+        assert!(shares_to_text(&parts, &[("note", "two\nlines")]).is_err());
+    }
+
+    #[test]
+    fn test_corruption_detected() {
+        let text = shares_to_text(&[Part::from(0, 0xDEADBEEF)], &[("custodian_group", "ops")]).unwrap();
+        assert_eq!(text, "# hadamard_sss annotated v1\nformat: shares | 712c4dec\ncustodian_group: ops | e89b83cb\nshare: 0 deadbeef | 9986b659\n");
+        let stamped = shares_to_text(&[Part::from(0, 0xDEADBEEF).with_fingerprint(0x1a2b3c4d)], &[]).unwrap();
+        assert!(stamped.ends_with("share: 0 deadbeef 1a2b3c4d | 8059aca3\n"));
+        assert_eq!(shares_from_text(&stamped).unwrap().0[0].fingerprint(), Some(0x1a2b3c4d));
+        let corrupt = text.replace("deadbeef", "deadbeee");
+        assert_eq!(shares_from_text(&corrupt).err(), Some("line checksum mismatch"));
+        let unsummed = text.replace(" | ", " ");
+        assert_eq!(shares_from_text(&unsummed).err(), Some("line has no checksum"));
+        let manifest_text = text.replace("format: shares", "format: manifest");
+        assert!(shares_from_text(&manifest_text).is_err());
+    }
+
+    #[test]
+    fn test_manifest_roundtrip() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(7).unwrap();
+        let custodians = ["alice", "bob", "carol", "dave", "eve", "frank", "grace hopper"];
+        let manifest = hsss.manifest(&parts, &custodians, &[7; 32]).unwrap();
+        let text = manifest_to_text(&manifest).unwrap();
+        assert_eq!(manifest_from_text(&text).unwrap(), manifest);
+
+        let assignment = hsss.assign_rows(&["a", "b", "c", "d", "e", "f", "g"], [3; 16]).unwrap();
+        let assigned = hsss.assigned_manifest(&parts, &assignment, &[7; 32]).unwrap();
+        assert_eq!(manifest_from_text(&manifest_to_text(&assigned).unwrap()).unwrap(), assigned);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_roundtrip() {
+        use crate::keys::ChaChaKey;
+        let key = ChaChaKey::from([9; 32]);
+        let sealed: Vec<EncryptedShare> = (0..3).map(|i| EncryptedShare::seal(&Part::from(i, 100 + i as u32), &key).unwrap()).collect();
+        let text = encrypted_shares_to_text(&sealed, &[("key_id", "backup-2026")]).unwrap();
+        let (read, document) = encrypted_shares_from_text(&text).unwrap();
+        assert_eq!(read, sealed);
+        assert_eq!(document.get("key_id"), Some("backup-2026"));
+        assert_eq!(read[2].open(&key).unwrap().data(), 102);
+    }
+}
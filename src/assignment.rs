@@ -0,0 +1,161 @@
+//! Детерминированное распределение строк матрицы между хранителями.
+//!
+//! Строка хранителя выбирается рандеву-хэшированием: хранители обрабатываются в порядке
+//! SHA-256(соль, идентификатор), и каждый получает свободную строку с наибольшим значением
+//! SHA-256(соль, идентификатор, строка). Результат зависит только от соли и множества
+//! хранителей, но не от порядка их перечисления, и две строки одному хранителю не достаются.
+//! Соль записывается в манифест, поэтому распределение можно перепроверить функцией
+//! [verify_assignment].
+use crate::manifest::{ManifestKey, ShareSetManifest};
+use crate::scheme_impl::Part;
+use crate::HadamardSSS;
+use sha2::{Digest, Sha256};
+
+/// Метка хэширования.
+const LABEL: &[u8] = b"hadamard_sss assignment";
+
+/// Хэш идентификатора хранителя и, если задана, строки.
+fn score(salt: &[u8; 16], custodian: &str, row: Option<usize>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(LABEL);
+    hasher.update(salt);
+    hasher.update((custodian.len() as u64).to_le_bytes());
+    hasher.update(custodian.as_bytes());
+    if let Some(row) = row {
+        hasher.update((row as u64).to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Распределение строк между хранителями.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowAssignment {
+    /// Соль распределения.
+    salt: [u8; 16],
+    /// Пары (хранитель, строка) по возрастанию строк.
+    rows: Vec<(String, usize)>,
+}
+
+/// Реализация методов распределения строк.
+impl RowAssignment {
+    /// Распределение rows строк между хранителями. Возвращается ошибка, если хранителей
+    /// больше, чем строк, или идентификаторы повторяются.
+    pub fn new(custodians: &[&str], rows: usize, salt: [u8; 16]) -> Result<Self, &'static str> {
+        if custodians.len() > rows {
+            return Err("more custodians than rows");
+        }
+        let mut order: Vec<(&str, [u8; 32])> = custodians.iter().map(|&c| (c, score(&salt, c, None))).collect();
+        order.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+        if order.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err("duplicate custodian identifier");
+        }
+        let mut free = vec![true; rows];
+        let mut res: Vec<(String, usize)> = order
+            .iter()
+            .map(|&(custodian, _)| {
+                let row = (0..rows)
+                    .filter(|&row| free[row])
+                    .max_by_key(|&row| score(&salt, custodian, Some(row)))
+                    .expect("there are at least as many rows as custodians");
+                free[row] = false;
+                (custodian.to_string(), row)
+            })
+            .collect();
+        res.sort_by_key(|&(_, row)| row);
+        Ok(RowAssignment { salt, rows: res })
+    }
+
+    /// Возвращение значения поля salt.
+    pub fn salt(&self) -> [u8; 16] {
+        self.salt
+    }
+
+    /// Пары (хранитель, строка) по возрастанию строк.
+    pub fn rows(&self) -> &[(String, usize)] {
+        &self.rows
+    }
+
+    /// Строка данного хранителя.
+    pub fn row(&self, custodian: &str) -> Option<usize> {
+        self.rows.iter().find(|(c, _)| c == custodian).map(|&(_, row)| row)
+    }
+}
+
+/// Составление манифеста по распределению.
+impl HadamardSSS {
+    /// Распределение строк этой схемы между хранителями.
+    pub fn assign_rows(&self, custodians: &[&str], salt: [u8; 16]) -> Result<RowAssignment, &'static str> {
+        RowAssignment::new(custodians, self.hss.mtx_len(), salt)
+    }
+
+    /// Манифест для долей, розданных по распределению: каждый хранитель получает долю своей строки.
+    /// parts -- все доли схемы, как их возвращает [crate::SharingScheme::share].
+    pub fn assigned_manifest(&self, parts: &[Part], assignment: &RowAssignment, key: &ManifestKey) -> Result<ShareSetManifest, &'static str> {
+        let issued = assignment
+            .rows
+            .iter()
+            .map(|(_, row)| parts.iter().find(|part| part.number() == *row).copied().ok_or("no part for an assigned row"))
+            .collect::<Result<Vec<Part>, _>>()?;
+        let custodians: Vec<&str> = assignment.rows.iter().map(|(c, _)| c.as_str()).collect();
+        let mut manifest = self.manifest(&issued, &custodians, key)?;
+        manifest.assignment_salt = Some(assignment.salt);
+        Ok(manifest)
+    }
+}
+
+/// Проверка, что строки в манифесте распределены по соли манифеста.
+/// Для манифестов без соли возвращается false.
+pub fn verify_assignment(manifest: &ShareSetManifest) -> bool {
+    let Some(salt) = manifest.assignment_salt() else {
+        return false;
+    };
+    let custodians: Vec<&str> = manifest.entries().iter().map(|entry| entry.custodian()).collect();
+    let rows = manifest.order().saturating_sub(1);
+    RowAssignment::new(&custodians, rows, salt).is_ok_and(|assignment| {
+        manifest.entries().iter().all(|entry| assignment.row(entry.custodian()) == Some(entry.number()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::manifest::verify_manifest;
+    use crate::SharingScheme;
+
+    #[test]
+    fn test_assignment_is_stable() {
+        let salt = [9u8; 16];
+        let a = RowAssignment::new(&["alice", "bob", "carol", "dave"], 7, salt).unwrap();
+        let b = RowAssignment::new(&["dave", "carol", "bob", "alice"], 7, salt).unwrap();
+        assert_eq!(a, b);
+        let mut rows: Vec<usize> = a.rows().iter().map(|&(_, row)| row).collect();
+        rows.dedup();
+        assert_eq!(rows.len(), 4);
+        assert!(rows.iter().all(|&row| row < 7));
+        assert_ne!(a, RowAssignment::new(&["alice", "bob", "carol", "dave"], 7, [8u8; 16]).unwrap());
+        assert_eq!(RowAssignment::new(&["a", "b", "a"], 7, salt), Err("duplicate custodian identifier"));
+        assert_eq!(RowAssignment::new(&["a", "b", "c"], 2, salt), Err("more custodians than rows"));
+    }
+
+    #[test]
+    fn test_assigned_manifest() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let key = [1u8; 32];
+        let custodians = ["alice", "bob", "carol", "dave", "erin", "frank", "grace"];
+        let assignment = hsss.assign_rows(&custodians, [3u8; 16]).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let manifest = hsss.assigned_manifest(&parts, &assignment, &key).unwrap();
+        let opened: Vec<_> = parts.iter().map(|&part| (part, manifest.opening(part.number(), &key))).collect();
+        assert!(verify_manifest(&opened, &manifest).is_empty());
+        let restored = ShareSetManifest::from_json(&manifest.to_json()).unwrap();
+        assert_eq!(restored.assignment_salt(), Some([3u8; 16]));
+        assert!(verify_assignment(&restored));
+        for entry in restored.entries() {
+            assert_eq!(assignment.row(entry.custodian()), Some(entry.number()));
+        }
+
+        let manual = hsss.manifest(&parts, &custodians, &key).unwrap();
+        assert!(!verify_assignment(&manual));
+    }
+}
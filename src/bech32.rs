@@ -0,0 +1,141 @@
+//! Текстовое кодирование долей с контрольной суммой (bech32m).
+//!
+//! Доля в формате [crate::wire] записывается по BIP-350: префикс [HRP], разделитель `1`,
+//! тринадцать байт в алфавите bech32 и шесть контрольных символов. Строки с девятью байтами
+//! формата версии 1 тоже читаются. Контрольная сумма --
+//! BCH-код, который обнаруживает любые ошибки не более чем в четырёх символах, поэтому
+//! опечатка при переписывании доли от руки выявляется при декодировании, а не портит
+//! восстановление. Строка пишется строчными буквами; при чтении допускаются и заглавные,
+//! но не смесь регистров. Строки читаются любой реализацией bech32m.
+use crate::scheme_impl::Part;
+use crate::wire::{LEGACY_WIRE_LEN, WIRE_LEN};
+
+/// Человекочитаемый префикс строки.
+pub const HRP: &str = "hss";
+
+/// Алфавит bech32.
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+/// Константа контрольной суммы bech32m.
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+/// Число контрольных символов.
+const CHECKSUM_LEN: usize = 6;
+/// Число символов данных: 104 бита по 5 бит.
+const DATA_LEN: usize = (WIRE_LEN * 8).div_ceil(5);
+/// Число символов данных формата версии 1: 72 бита по 5 бит.
+const LEGACY_DATA_LEN: usize = (LEGACY_WIRE_LEN * 8).div_ceil(5);
+
+/// Остаток от деления на образующий многочлен BCH-кода.
+fn polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+    values.into_iter().fold(1, |chk, value| {
+        let top = chk >> 25;
+        let chk = ((chk & 0x1ff_ffff) << 5) ^ u32::from(value);
+        (0..5).filter(|i| top >> i & 1 == 1).fold(chk, |chk, i| chk ^ GEN[i])
+    })
+}
+
+/// Префикс в виде, который входит в контрольную сумму.
+fn hrp_expand(hrp: &str) -> impl Iterator<Item = u8> + '_ {
+    hrp.bytes().map(|c| c >> 5).chain([0]).chain(hrp.bytes().map(|c| c & 31))
+}
+
+/// Контрольные символы для префикса и данных.
+fn checksum(hrp: &str, data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let residue = polymod(hrp_expand(hrp).chain(data.iter().copied()).chain([0; CHECKSUM_LEN])) ^ BECH32M_CONST;
+    std::array::from_fn(|i| (residue >> (5 * (CHECKSUM_LEN - 1 - i)) & 31) as u8)
+}
+
+/// Кодирование доли. Возвращается ошибка, если доля не записывается в [crate::wire].
+/// # Пример
+/// ```
+/// use hadamard_sss::{bech32, Part};
+///
+/// let text = bech32::encode(&Part::from(3, 0xDEADBEEF).with_fingerprint(7)).unwrap();
+/// assert!(text.starts_with("hss1"));
+/// assert_eq!(bech32::decode(&text).unwrap().data(), 0xDEADBEEF);
+/// ```
+pub fn encode(part: &Part) -> Result<String, &'static str> {
+    let bytes = part.to_bytes()?;
+    let bits = bytes.iter().fold(0u128, |acc, &byte| (acc << 8) | u128::from(byte)) << (DATA_LEN * 5 - WIRE_LEN * 8);
+    let data: Vec<u8> = (0..DATA_LEN).rev().map(|i| (bits >> (5 * i) & 31) as u8).collect();
+    let symbols: String = data.iter().copied().chain(checksum(HRP, &data)).map(|v| char::from(CHARSET[v as usize])).collect();
+    Ok(format!("{HRP}1{symbols}"))
+}
+
+/// Декодирование доли, записанной [encode].
+pub fn decode(text: &str) -> Result<Part, &'static str> {
+    if text.chars().any(|c| c.is_ascii_lowercase()) && text.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err("mixed case in bech32 string");
+    }
+    let text = text.to_ascii_lowercase();
+    let (hrp, rest) = text.rsplit_once('1').ok_or("missing bech32 separator")?;
+    if hrp != HRP {
+        return Err("wrong bech32 prefix");
+    }
+    let values = rest
+        .bytes()
+        .map(|c| CHARSET.iter().position(|&a| a == c).map(|v| v as u8).ok_or("invalid character"))
+        .collect::<Result<Vec<u8>, _>>()?;
+    let (data_len, len) = match values.len().checked_sub(CHECKSUM_LEN) {
+        Some(DATA_LEN) => (DATA_LEN, WIRE_LEN),
+        Some(LEGACY_DATA_LEN) => (LEGACY_DATA_LEN, LEGACY_WIRE_LEN),
+        _ => return Err("wrong number of characters"),
+    };
+    if polymod(hrp_expand(hrp).chain(values.iter().copied())) != BECH32M_CONST {
+        return Err("bech32 checksum mismatch");
+    }
+    let bits = values[..data_len].iter().fold(0u128, |acc, &v| (acc << 5) | u128::from(v));
+    let padding = data_len * 5 - len * 8;
+    if bits & ((1 << padding) - 1) != 0 {
+        return Err("invalid padding bits");
+    }
+    let bytes: Vec<u8> = (0..len).rev().map(|i| (bits >> (padding + 8 * i)) as u8).collect();
+    Part::from_bytes(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        for (number, data) in [(0, 0), (6, 314159265), (u32::MAX as usize, u32::MAX)] {
+            let text = encode(&Part::from(number, data).with_fingerprint(data ^ 1)).unwrap();
+            assert_eq!(text.len(), HRP.len() + 1 + DATA_LEN + CHECKSUM_LEN);
+            let part = decode(&text).unwrap();
+            assert_eq!((part.number(), part.data(), part.fingerprint()), (number, data, Some(data ^ 1)));
+            assert_eq!(decode(&text.to_uppercase()).unwrap().data(), data);
+        }
+        // строка формата версии 1 читается как доля без отпечатка
+        let legacy = decode("hss1qyqqqqqxm6kmamcd38h63").unwrap();
+        assert_eq!((legacy.number(), legacy.data(), legacy.fingerprint()), (6, 0xDEADBEEF, None));
+        // контрольный пример BIP-350
+        let values: Vec<u8> = b"lqfn3a".iter().map(|&c| CHARSET.iter().position(|&a| a == c).unwrap() as u8).collect();
+        assert_eq!(checksum("a", &[]).to_vec(), values);
+    }
+
+    #[test]
+    fn test_typos_detected() {
+        let text = encode(&Part::from(4, 0xC0FFEE).with_fingerprint(0xF00D)).unwrap();
+        let start = HRP.len() + 1;
+        for position in start..text.len() {
+            for &symbol in CHARSET.iter().filter(|&&a| a != text.as_bytes()[position]) {
+                let mut bytes = text.clone().into_bytes();
+                bytes[position] = symbol;
+                assert_eq!(decode(std::str::from_utf8(&bytes).unwrap()).err(), Some("bech32 checksum mismatch"));
+            }
+        }
+        for position in start..text.len() - 1 {
+            let mut bytes = text.clone().into_bytes();
+            if bytes[position] != bytes[position + 1] {
+                bytes.swap(position, position + 1);
+                assert!(decode(std::str::from_utf8(&bytes).unwrap()).is_err());
+            }
+        }
+        assert_eq!(decode(&text.replacen('h', "H", 1)).err(), Some("mixed case in bech32 string"));
+        assert_eq!(decode(&text.replacen("hss", "abc", 1)).err(), Some("wrong bech32 prefix"));
+        assert_eq!(decode(&text[..text.len() - 1]).err(), Some("wrong number of characters"));
+        assert_eq!(decode("hss").err(), Some("missing bech32 separator"));
+        assert_eq!(decode(&format!("{}b", &text[..text.len() - 1])).err(), Some("invalid character"));
+    }
+}
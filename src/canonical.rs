@@ -0,0 +1,116 @@
+//! Каноническое представление долей и манифестов для хэширования и подписи.
+//!
+//! Одни и те же данные всегда дают одни и те же байты, независимо от того, какой
+//! компонент их кодирует. Целые записываются как u64 в big-endian, поля -- в порядке
+//! их имён, строки -- длиной (u64) и байтами UTF-8, необязательные поля -- байтом
+//! присутствия (0 или 1) и значением. Перед данными идёт метка домена, так что
+//! представления разных типов не совпадают.
+//!
+//! ```text
+//! Part:     "hadamard_sss part v1"     data number
+//! Manifest: "hadamard_sss manifest v1" assignment_salt created_at entries nonce order threshold
+//! Entry:    custodian digest number
+//! ```
+//!
+//! Записи манифеста упорядочиваются по номеру доли, затем по хранителю и дайджесту.
+use crate::manifest::{ManifestEntry, ShareSetManifest};
+use crate::scheme_impl::Part;
+
+/// Метка домена долей.
+const PART_DOMAIN: &[u8] = b"hadamard_sss part v1";
+/// Метка домена манифестов.
+const MANIFEST_DOMAIN: &[u8] = b"hadamard_sss manifest v1";
+
+/// Запись целого.
+fn push_uint(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Запись строки байтов с длиной.
+fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    push_uint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+/// Запись записи манифеста.
+fn push_entry(out: &mut Vec<u8>, entry: &ManifestEntry) {
+    push_bytes(out, entry.custodian().as_bytes());
+    out.extend_from_slice(&entry.digest());
+    push_uint(out, entry.number() as u64);
+}
+
+/// Каноническое представление доли.
+impl Part {
+    /// Канонические байты доли.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(PART_DOMAIN.len() + 16);
+        out.extend_from_slice(PART_DOMAIN);
+        push_uint(&mut out, u64::from(self.data()));
+        push_uint(&mut out, self.number() as u64);
+        out
+    }
+}
+
+/// Каноническое представление манифеста.
+impl ShareSetManifest {
+    /// Канонические байты манифеста.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut out = MANIFEST_DOMAIN.to_vec();
+        match self.assignment_salt() {
+            Some(salt) => {
+                out.push(1);
+                out.extend_from_slice(&salt);
+            }
+            None => out.push(0),
+        }
+        push_uint(&mut out, self.created_at());
+        let mut entries: Vec<&ManifestEntry> = self.entries().iter().collect();
+        entries.sort_by(|a, b| (a.number(), a.custodian(), a.digest()).cmp(&(b.number(), b.custodian(), b.digest())));
+        push_uint(&mut out, entries.len() as u64);
+        for entry in entries {
+            push_entry(&mut out, entry);
+        }
+        out.extend_from_slice(&self.nonce());
+        push_uint(&mut out, self.order() as u64);
+        push_uint(&mut out, self.threshold() as u64);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::{HadamardSSS, SharingScheme};
+
+    #[test]
+    fn test_part_bytes() {
+        let bytes = Part::from(3, 0x01020304).canonical_bytes();
+        assert_eq!(&bytes[..PART_DOMAIN.len()], PART_DOMAIN);
+        assert_eq!(&bytes[PART_DOMAIN.len()..], [0, 0, 0, 0, 1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 3]);
+        assert_ne!(bytes, Part::from(4, 0x01020304).canonical_bytes());
+    }
+
+    #[test]
+    fn test_manifest_bytes_are_stable() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let custodians = ["a", "b", "c", "d", "e", "f", "g"];
+        let manifest = hsss.manifest(&parts, &custodians, &[7; 32]).unwrap();
+        let bytes = manifest.canonical_bytes();
+        assert_eq!(ShareSetManifest::from_json(&manifest.to_json()).unwrap().canonical_bytes(), bytes);
+
+        // порядок записей в JSON не влияет на представление
+        let json = manifest.to_json();
+        let (head, tail) = json.split_at(json.find("[").unwrap() + 1);
+        let mut items: Vec<&str> = tail.trim_end_matches("]}").split("},").map(|item| item.trim_end_matches('}')).collect();
+        items.reverse();
+        let reordered = format!("{}{}}}]}}", head, items.join("},"));
+        let reordered = ShareSetManifest::from_json(&reordered).unwrap();
+        assert_eq!(reordered.entries()[0].number(), 6);
+        assert_eq!(reordered.canonical_bytes(), bytes);
+
+        let other = hsss.manifest(&parts, &custodians, &[8; 32]).unwrap();
+        assert_ne!(other.canonical_bytes(), bytes);
+    }
+}
@@ -0,0 +1,72 @@
+//! Перевыпуск утраченных долей.
+//!
+//! Биты доли, известные её владельцу, однозначно определяются секретом, а биты-заполнители
+//! других долей никак не ограничивают, поэтому новая доля для той же строки с новыми
+//! заполнителями согласована со всеми уцелевшими долями. Если доли выдавались через
+//! [HadamardSSS::share_seeded], точную копию даёт повторный вызов с тем же зерном.
+use crate::scheme_impl::Part;
+use crate::validation::ValidationOutcome;
+use crate::{HadamardSSS, SharingScheme};
+use rand::RngCore;
+
+/// Перевыпуск долей.
+impl HadamardSSS {
+    /// Новые доли для строк missing по известному секрету.
+    pub fn complete_shares_from_secret(&self, secret: u32, missing: &[usize]) -> Result<Vec<Part>, &'static str> {
+        let n = self.hss.mtx_len();
+        if missing.iter().any(|&row| row >= n) {
+            return Err("scheme mismatch: part number exceeds the order of the scheme");
+        }
+        let mut rng = crate::entropy::rng();
+        Ok(missing
+            .iter()
+            .map(|&row| {
+                let mask = self.hss.mask(row);
+                Part::from(row, (secret & mask) | (rng.next_u32() & !mask)).with_fingerprint(self.fingerprint())
+            })
+            .collect())
+    }
+
+    /// Новые доли для строк missing по набору уцелевших долей, достаточному для восстановления.
+    /// Набор с подозрительными долями отклоняется, чтобы искажение не перешло в новые доли.
+    pub fn complete_shares(&self, parts: Vec<Part>, missing: &[usize]) -> Result<Vec<Part>, &'static str> {
+        if let ValidationOutcome::Suspicious(_) = self.validate(parts.clone()) {
+            return Err("suspicious shares detected");
+        }
+        let secret = self.reconstruct(parts)?;
+        self.complete_shares_from_secret(secret, missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_completed_shares_are_consistent() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let replaced = hsss.complete_shares(parts[2..7].to_vec(), &[0, 1]).unwrap();
+        assert_eq!(replaced.iter().map(Part::number).collect::<Vec<_>>(), [0, 1]);
+        assert!(replaced.iter().all(|part| part.fingerprint() == Some(hsss.fingerprint())));
+        let mut renewed = replaced.clone();
+        renewed.extend_from_slice(&parts[2..7]);
+        assert!(hsss.validate(renewed).is_clean());
+        assert_eq!(hsss.reconstruct(vec![replaced[0], replaced[1], parts[2], parts[3], parts[4]]).unwrap(), 314159265);
+        for (new, old) in replaced.iter().zip(&parts) {
+            let mask = hsss.hss.mask(new.number());
+            assert_eq!(new.data() & mask, old.data() & mask);
+        }
+        assert!(hsss.complete_shares_from_secret(1, &[7]).is_err());
+    }
+
+    #[test]
+    fn test_suspicious_set_rejected() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut parts = hsss.share(5).unwrap();
+        parts[3] = parts[3].blinded(u32::MAX);
+        assert_eq!(hsss.complete_shares(parts[1..7].to_vec(), &[0]).err(), Some("suspicious shares detected"));
+        assert_eq!(hsss.complete_shares(parts[0..3].to_vec(), &[6]).err(), Some("less than threshold parties"));
+    }
+}
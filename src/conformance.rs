@@ -0,0 +1,207 @@
+//! Проверка соответствия схем разделения для CI пользователей.
+//!
+//! Доступно с функцией `test-utils`. [run_scheme_conformance] прогоняет схему через
+//! круговые проверки, проверку порога и обнаружения искажений и возвращает [Report]
+//! со списком проверок, чтобы собственные матрицы и реализации [SharingScheme]
+//! можно было проверить теми же тестами, что и схему Адамара.
+use crate::scheme_impl::Part;
+use crate::validation::ValidationOutcome;
+use crate::SharingScheme;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Параметры проверки соответствия.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConformanceConfig {
+    /// Пороговое значение проверяемой схемы.
+    pub threshold: usize,
+    /// Число случайных секретов.
+    pub rounds: usize,
+    /// Зерно генератора секретов и наборов долей, чтобы отчёт воспроизводился.
+    pub seed: u64,
+}
+
+/// Реализация методов параметров проверки.
+impl ConformanceConfig {
+    /// Параметры для схемы с данным порогом: 16 случайных секретов, зерно 0.
+    pub fn new(threshold: usize) -> Self {
+        ConformanceConfig { threshold, rounds: 16, seed: 0 }
+    }
+}
+
+/// Итог одной проверки.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Check {
+    /// Название проверки.
+    pub name: &'static str,
+    /// Первая найденная ошибка или `None`, если проверка пройдена.
+    pub failure: Option<&'static str>,
+}
+
+/// Отчёт о проверке соответствия.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// Проверки в порядке выполнения.
+    checks: Vec<Check>,
+}
+
+/// Реализация методов отчёта.
+impl Report {
+    /// Возвращение проверок отчёта.
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+
+    /// Все проверки пройдены.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.failure.is_none())
+    }
+
+    /// Непройденные проверки.
+    pub fn failures(&self) -> Vec<&Check> {
+        self.checks.iter().filter(|check| check.failure.is_some()).collect()
+    }
+}
+
+/// Прогон схемы через проверки соответствия:
+/// - "shares" -- доли имеют различные номера и их число не меньше порога;
+/// - "round trip" -- секрет восстанавливается по всем долям и по случайным наборам из порога долей,
+///   включая крайние значения 0 и `u32::MAX`;
+/// - "below threshold" -- набор из порога без одной доли отклоняется;
+/// - "clean validation" -- неискажённые доли не признаются подозрительными;
+/// - "tamper detection" -- доля с инвертированным значением признаётся подозрительной.
+pub fn run_scheme_conformance<S>(scheme: &S, cfg: ConformanceConfig) -> Report
+where
+    S: SharingScheme<SecretType = u32, PartType = Part>,
+{
+    let mut rng = ChaCha20Rng::seed_from_u64(cfg.seed);
+    let mut secrets = vec![0, u32::MAX];
+    secrets.extend((0..cfg.rounds).map(|_| rng.gen::<u32>()));
+    let mut shared: Vec<(u32, Vec<Part>)> = Vec::new();
+    let mut failure = None;
+    for &secret in &secrets {
+        match scheme.share(secret) {
+            Ok(parts) => shared.push((secret, parts)),
+            Err(_) => failure = failure.or(Some("share failed")),
+        }
+    }
+    let checks = vec![
+        Check { name: "shares", failure: failure.or_else(|| check_shares(&shared, cfg.threshold)) },
+        Check { name: "round trip", failure: check_round_trip(scheme, &shared, cfg.threshold, &mut rng) },
+        Check { name: "below threshold", failure: check_below_threshold(scheme, &shared, cfg.threshold, &mut rng) },
+        Check { name: "clean validation", failure: check_clean_validation(scheme, &shared) },
+        Check { name: "tamper detection", failure: check_tamper_detection(scheme, &shared, &mut rng) },
+    ];
+    Report { checks }
+}
+
+/// Номера долей различны, долей не меньше порога.
+fn check_shares(shared: &[(u32, Vec<Part>)], threshold: usize) -> Option<&'static str> {
+    for (_, parts) in shared {
+        let mut numbers: Vec<usize> = parts.iter().map(Part::number).collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        if numbers.len() != parts.len() {
+            return Some("duplicate share numbers");
+        }
+        if parts.len() < threshold.max(1) {
+            return Some("fewer shares than the threshold");
+        }
+    }
+    None
+}
+
+/// Восстановление по всем долям и по случайным наборам из порога долей.
+fn check_round_trip<S>(scheme: &S, shared: &[(u32, Vec<Part>)], threshold: usize, rng: &mut ChaCha20Rng) -> Option<&'static str>
+where
+    S: SharingScheme<SecretType = u32, PartType = Part>,
+{
+    for (secret, parts) in shared {
+        if scheme.reconstruct(parts.clone()).ok() != Some(*secret) {
+            return Some("secret not recovered from all shares");
+        }
+        let mut quorum = parts.clone();
+        quorum.shuffle(rng);
+        quorum.truncate(threshold);
+        if scheme.reconstruct(quorum).ok() != Some(*secret) {
+            return Some("secret not recovered from a threshold of shares");
+        }
+    }
+    None
+}
+
+/// Отклонение набора из порога без одной доли.
+fn check_below_threshold<S>(scheme: &S, shared: &[(u32, Vec<Part>)], threshold: usize, rng: &mut ChaCha20Rng) -> Option<&'static str>
+where
+    S: SharingScheme<SecretType = u32, PartType = Part>,
+{
+    for (_, parts) in shared {
+        let mut subset = parts.clone();
+        subset.shuffle(rng);
+        subset.truncate(threshold.saturating_sub(1));
+        if scheme.reconstruct(subset).is_ok() {
+            return Some("secret reconstructed below the threshold");
+        }
+    }
+    None
+}
+
+/// Неискажённые доли не подозрительны.
+fn check_clean_validation<S>(scheme: &S, shared: &[(u32, Vec<Part>)]) -> Option<&'static str>
+where
+    S: SharingScheme<SecretType = u32, PartType = Part>,
+{
+    shared
+        .iter()
+        .any(|(_, parts)| matches!(scheme.validate(parts.clone()), ValidationOutcome::Suspicious(_)))
+        .then_some("untampered shares reported as suspicious")
+}
+
+/// Инвертированная доля признаётся подозрительной.
+fn check_tamper_detection<S>(scheme: &S, shared: &[(u32, Vec<Part>)], rng: &mut ChaCha20Rng) -> Option<&'static str>
+where
+    S: SharingScheme<SecretType = u32, PartType = Part>,
+{
+    for (_, parts) in shared.iter().filter(|(_, parts)| !parts.is_empty()) {
+        let index = rng.gen_range(0..parts.len());
+        let mut tampered = parts.clone();
+        tampered[index] = parts[index].blinded(u32::MAX);
+        if !scheme.validate(tampered).suspicious().contains(&parts[index].number()) {
+            return Some("tampered share not detected");
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::testing::MockScheme;
+    use crate::HadamardSSS;
+
+    #[test]
+    fn test_conforming_schemes() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let report = run_scheme_conformance(&hsss, ConformanceConfig::new(5));
+        assert!(report.passed());
+        assert_eq!(report.checks().len(), 5);
+        assert!(run_scheme_conformance(&MockScheme::new(5, 3), ConformanceConfig { threshold: 3, rounds: 4, seed: 997 }).passed());
+    }
+
+    #[test]
+    fn test_failures_reported() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let report = run_scheme_conformance(&hsss, ConformanceConfig::new(4));
+        assert_eq!(report.failures().iter().map(|check| check.name).collect::<Vec<_>>(), ["round trip"]);
+
+        let mock = MockScheme::new(5, 3).with_suspicious(vec![0]);
+        let report = run_scheme_conformance(&mock, ConformanceConfig::new(4));
+        let failures: Vec<(&str, Option<&str>)> = report.failures().iter().map(|check| (check.name, check.failure)).collect();
+        assert_eq!(failures, [("below threshold", Some("secret reconstructed below the threshold")), ("clean validation", Some("untampered shares reported as suspicious"))]);
+
+        mock.fail_next_share("device unavailable");
+        assert_eq!(run_scheme_conformance(&mock, ConformanceConfig::new(3)).checks()[0].failure, Some("share failed"));
+    }
+}
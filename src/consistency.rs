@@ -0,0 +1,170 @@
+//! Проверка долей по свойствам блок-схемы.
+//!
+//! Строки матрицы инцидентности попарно пересекаются ровно по n - 1 столбцам, поэтому любые
+//! две честные доли обязаны совпадать на битах этих столбцов. Доли, нарушающие это, образуют
+//! граф противоречий; наименьшее множество долей, покрывающее все его рёбра, -- самое
+//! экономное объяснение набора, в котором лжецов меньше всего.
+//!
+//! Поразрядное голосование [crate::SharingScheme::validate] рассматривает биты по отдельности:
+//! если сговорившиеся доли в большинстве по общему с честной долей биту, под подозрение
+//! попадает честная доля. Проверка по графу противоречий учитывает все пары сразу и в таком
+//! случае оправдывает честную долю. Если наименьших объяснений несколько, итог --
+//! [ValidationOutcome::Inconclusive].
+//!
+//! Противоречия пар и голоса по битам вычисляются без ветвлений по значениям долей.
+//! Перебор покрытий зависит только от графа противоречий, то есть от уже принятого решения.
+use crate::scheme_impl::Part;
+use crate::validation::{BitVote, ShareVerdict, Tally, ValidationOutcome};
+use subtle::ConstantTimeEq;
+use crate::HadamardSSS;
+
+/// Наибольшее число лжецов, которое ищет проверка; перебор экспоненциален по этому числу.
+pub const MAX_LIARS: usize = 16;
+
+/// Поиск всех наименьших вершинных покрытий графа противоречий размера не больше [MAX_LIARS].
+struct Covers<'a> {
+    /// Рёбра графа противоречий.
+    edges: &'a [(usize, usize)],
+    /// Размер лучшего найденного покрытия.
+    best: usize,
+    /// Найденные покрытия размера best.
+    found: Vec<Vec<usize>>,
+}
+
+impl Covers<'_> {
+    /// Ветвление по первому непокрытому ребру: в покрытие входит один из его концов.
+    fn search(&mut self, cover: &mut Vec<usize>) {
+        if cover.len() > self.best {
+            return;
+        }
+        match self.edges.iter().find(|(a, b)| !cover.contains(a) && !cover.contains(b)) {
+            None => {
+                if cover.len() < self.best {
+                    self.best = cover.len();
+                    self.found.clear();
+                }
+                let mut set = cover.clone();
+                set.sort_unstable();
+                if !self.found.contains(&set) {
+                    self.found.push(set);
+                }
+            }
+            Some(&(a, b)) => {
+                for vertex in [a, b] {
+                    cover.push(vertex);
+                    self.search(cover);
+                    cover.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Проверка по графу противоречий.
+impl HadamardSSS {
+    /// Проверка набора долей по попарным пересечениям строк блок-схемы. Подозрительными
+    /// считаются доли наименьшего множества, без которого оставшиеся доли попарно согласованы;
+    /// доли чужих схем (см. [crate::SharingScheme::validate]) подозрительны всегда. Если таких множеств несколько
+    /// или лжецов больше [MAX_LIARS], итог -- [ValidationOutcome::Inconclusive].
+    pub fn validate_design(&self, parts: Vec<Part>) -> ValidationOutcome {
+        self.check_canaries(&parts);
+        let (foreign, known): (Vec<Part>, Vec<Part>) = parts.into_iter().partition(|part| self.hss.is_foreign(part));
+        let masks: Vec<u32> = known.iter().map(|part| self.hss.mask(part.number())).collect();
+        let mut tally = Tally::default();
+        let mut pairs = Vec::new();
+        for a in 0..known.len() {
+            tally.add(masks[a], known[a].data());
+            for b in a + 1..known.len() {
+                let common = masks[a] & masks[b];
+                pairs.push((a, b, !((known[a].data() ^ known[b].data()) & common).ct_eq(&0)));
+            }
+        }
+        let checked = tally.checked();
+        let edges: Vec<(usize, usize)> = pairs
+            .into_iter()
+            .filter(|&(_, _, conflict)| bool::from(conflict))
+            .map(|(a, b, _)| (a, b))
+            .collect();
+
+        let mut covers = Covers { edges: &edges, best: MAX_LIARS, found: Vec::new() };
+        covers.search(&mut Vec::new());
+        let liars = match covers.found.as_slice() {
+            [liars] => liars.clone(),
+            _ => return ValidationOutcome::Inconclusive,
+        };
+        if liars.is_empty() && foreign.is_empty() {
+            return if checked { ValidationOutcome::Clean } else { ValidationOutcome::Inconclusive };
+        }
+
+        let mut verdicts: Vec<ShareVerdict> = liars
+            .iter()
+            .map(|&liar| {
+                let disputed = edges
+                    .iter()
+                    .filter_map(|&(a, b)| if a == liar { Some(b) } else if b == liar { Some(a) } else { None })
+                    .filter(|other| !liars.contains(other))
+                    .fold(0, |mask, other| mask | (masks[liar] & masks[other] & (known[liar].data() ^ known[other].data())));
+                let votes = (0..u32::BITS)
+                    .filter(|&bit| disputed >> bit & 1 == 1)
+                    .map(|bit| {
+                        let (ones, zeros) = tally.votes(bit);
+                        let (agree, disagree) = if known[liar].data() >> bit & 1 == 1 { (ones, zeros) } else { (zeros, ones) };
+                        BitVote { bit, agree, disagree }
+                    })
+                    .collect();
+                ShareVerdict::conflicting(known[liar].number(), votes)
+            })
+            .collect();
+        verdicts.sort_by_key(ShareVerdict::number);
+        verdicts.dedup_by_key(|verdict| verdict.number());
+        let mut foreign: Vec<usize> = foreign.iter().map(|part| part.number()).collect();
+        foreign.sort_unstable();
+        foreign.dedup();
+        verdicts.extend(foreign.into_iter().map(ShareVerdict::foreign));
+        ValidationOutcome::Suspicious(verdicts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::SharingScheme;
+
+    #[test]
+    fn test_honest_and_single_tampering() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut parts = hsss.share(314159265).unwrap();
+        assert_eq!(hsss.validate_design(parts.clone()), ValidationOutcome::Clean);
+        assert_eq!(hsss.validate_design(parts[0..1].to_vec()), ValidationOutcome::Inconclusive);
+        parts[4] = parts[4].blinded(hsss.hss.mask(4));
+        parts.push(Part::from(8, 0));
+        let outcome = hsss.validate_design(parts.clone());
+        assert_eq!(outcome.suspicious(), [4, 8]);
+        assert_eq!(outcome.suspicious(), hsss.validate(parts).suspicious());
+    }
+
+    #[test]
+    fn test_coordinated_tampering() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut parts = hsss.share(314159265).unwrap();
+        // доли 0 и 1 сговорились: обе инвертируют все известные им биты, так что на общем
+        // столбце они в большинстве против третьей знающей его доли
+        parts[0] = parts[0].blinded(hsss.hss.mask(0));
+        parts[1] = parts[1].blinded(hsss.hss.mask(1));
+        let common = hsss.hss.mask(0) & hsss.hss.mask(1);
+        let witness = (2..7).find(|&i| hsss.hss.mask(i) & common == common).unwrap();
+        assert!(hsss.validate(parts.clone()).suspicious().contains(&witness));
+        let outcome = hsss.validate_design(parts);
+        assert_eq!(outcome.suspicious(), [0, 1]);
+        assert!(outcome.verdicts().iter().all(|verdict| !verdict.votes().is_empty()));
+    }
+
+    #[test]
+    fn test_ambiguous_pair() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut parts = hsss.share(7).unwrap();
+        parts[1] = parts[1].blinded(hsss.hss.mask(1));
+        assert_eq!(hsss.validate_design(parts[0..2].to_vec()), ValidationOutcome::Inconclusive);
+    }
+}
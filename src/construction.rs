@@ -0,0 +1,387 @@
+//! Построения матриц Адамара порядков, недоступных конструкции Сильвестра,
+//! и их произведение Кронекера.
+//!
+//! Порядок матрицы определяет число долей и порог схемы, поэтому чем больше доступно порядков,
+//! тем точнее схему можно подобрать под число участников. Построенные матрицы не проверяются
+//! на ортогональность строк (проверка стоит O(n^3)): корректность построений покрыта тестами.
+use crate::hadamard_matrix::HadamardMatrix;
+use ndarray::Array2;
+
+/// Наибольший порядок построенной матрицы, как у [HadamardMatrix::sylvester].
+pub const MAX_CONSTRUCTED_ORDER: usize = 1 << HadamardMatrix::MAX_SYLVESTER_POWER;
+
+/// Известные четвёрки Уильямсона для нечётных n от 3 до 29: первые половины
+/// a_0..a_{(n-1)/2} первых строк симметричных циркулянтов A, B, C, D ('+' -- 1, '-' -- -1).
+const WILLIAMSON: &[(usize, [&str; 4])] = &[
+    (3, ["+-", "+-", "+-", "++"]),
+    (5, ["++-", "+-+", "+--", "+--"]),
+    (7, ["+-+-", "+--+", "++--", "+---"]),
+    (9, ["++-+-", "+-++-", "++---", "++-++"]),
+    (11, ["+-+--+", "+-+++-", "++++--", "+--+--"]),
+    (13, ["+-++--+", "+-+--++", "+++-+--", "+-----+"]),
+    (15, ["++-+-+--", "+--+++--", "+++-++--", "++-++-++"]),
+    (17, ["+-++-+--+", "+---+--++", "+++---+--", "+-+-----+"]),
+    (19, ["++--+-+--+", "+-+---++-+", "+++---+---", "++++-+--++"]),
+    (21, ["++--+-+-++-", "+--++++---+", "++++-+---+-", "++-+++++-+-"]),
+    (23, ["++---+-+-+++", "+-++-+++--+-", "+++--+---+--", "+++-+--++++-"]),
+    (25, ["+++---+++--+-", "++---+-+-++-+", "+++-+------+-", "+-++---+---+-"]),
+    (27, ["+-++---+-+++--", "+-+--++--++-+-", "+++-+-----+-+-", "+-------++--++"]),
+    (29, ["+-+-+--+-++-++-", "+-+----++++--+-", "+++++--++---+-+", "+---+++-++++++-"]),
+];
+
+/// Разложение q = p^m, если q -- степень простого числа.
+fn prime_power(q: usize) -> Option<(usize, usize)> {
+    let p = (2..=q).find(|&d| q.is_multiple_of(d))?;
+    let (mut rest, mut m) = (q, 0);
+    while rest.is_multiple_of(p) {
+        rest /= p;
+        m += 1;
+    }
+    (rest == 1).then_some((p, m))
+}
+
+/// Матрица Адамара данного порядка, построенная одним из построений модуля:
+/// Сильвестра, Пэли I, Пэли II, Уильямсона или произведением Кронекера уже построенных
+/// матриц меньших порядков. `None`, если ни одно построение порядка не достигает.
+pub(crate) fn construct(order: usize) -> Option<HadamardMatrix> {
+    if order == 0 || order > MAX_CONSTRUCTED_ORDER {
+        return None;
+    }
+    if order.is_power_of_two() {
+        return HadamardMatrix::sylvester(order.trailing_zeros()).ok();
+    }
+    if !order.is_multiple_of(4) {
+        return None;
+    }
+    if prime_power(order - 1).is_some() {
+        return HadamardMatrix::paley1(order - 1).ok();
+    }
+    if (order / 2 - 1) % 4 == 1 && prime_power(order / 2 - 1).is_some() {
+        return HadamardMatrix::paley2(order / 2 - 1).ok();
+    }
+    if let Ok(h_mtx) = HadamardMatrix::williamson(order / 4) {
+        return Some(h_mtx);
+    }
+    (2..order)
+        .filter(|&a| order.is_multiple_of(a) && a * a <= order)
+        .find_map(|a| Some(construct(a)?.kronecker(&construct(order / a)?)))
+}
+
+/// Конечное поле GF(p^m). Элементы -- числа 0..p^m, цифры которых по основанию p
+/// являются коэффициентами многочлена по модулю неприводимого многочлена modulus.
+struct Field {
+    /// Характеристика поля.
+    p: usize,
+    /// Степень расширения.
+    m: usize,
+    /// Унитарный неприводимый многочлен степени m, коэффициенты от младшего.
+    modulus: Vec<usize>,
+}
+
+/// Арифметика конечного поля.
+impl Field {
+    /// Поле из q элементов; ошибка, если q не степень простого числа.
+    fn new(q: usize) -> Result<Self, &'static str> {
+        let (p, m) = prime_power(q).ok_or("q is not a prime power")?;
+        let mut field = Field { p, m, modulus: Vec::new() };
+        field.modulus = (0..q)
+            .map(|low| {
+                let mut poly = field.digits(low, m);
+                poly.push(1);
+                poly
+            })
+            .find(|poly| field.is_irreducible(poly))
+            .expect("an irreducible polynomial of every degree exists");
+        Ok(field)
+    }
+
+    /// Число элементов поля.
+    fn order(&self) -> usize {
+        self.p.pow(self.m as u32)
+    }
+
+    /// Первые len цифр числа x по основанию p.
+    fn digits(&self, mut x: usize, len: usize) -> Vec<usize> {
+        (0..len)
+            .map(|_| {
+                let digit = x % self.p;
+                x /= self.p;
+                digit
+            })
+            .collect()
+    }
+
+    /// Число по цифрам.
+    fn number(&self, digits: &[usize]) -> usize {
+        digits.iter().rev().fold(0, |acc, &digit| acc * self.p + digit)
+    }
+
+    /// Остаток от деления многочлена a на унитарный многочлен f.
+    fn rem(&self, a: &[usize], f: &[usize]) -> Vec<usize> {
+        let mut r = a.to_vec();
+        while r.len() >= f.len() {
+            let lead = r[r.len() - 1];
+            let shift = r.len() - f.len();
+            for (i, &c) in f.iter().enumerate() {
+                r[shift + i] = (r[shift + i] + self.p - lead * c % self.p) % self.p;
+            }
+            r.pop();
+        }
+        r
+    }
+
+    /// Неприводимость унитарного многочлена: нет унитарных делителей степени до половины его степени.
+    fn is_irreducible(&self, f: &[usize]) -> bool {
+        let degree = f.len() - 1;
+        (1..=degree / 2).all(|d| {
+            (0..self.p.pow(d as u32)).all(|low| {
+                let mut divisor = self.digits(low, d);
+                divisor.push(1);
+                self.rem(f, &divisor).iter().any(|&c| c != 0)
+            })
+        })
+    }
+
+    /// Разность элементов.
+    fn sub(&self, a: usize, b: usize) -> usize {
+        let (a, b) = (self.digits(a, self.m), self.digits(b, self.m));
+        self.number(&a.iter().zip(&b).map(|(&x, &y)| (x + self.p - y) % self.p).collect::<Vec<_>>())
+    }
+
+    /// Произведение элементов.
+    fn mul(&self, a: usize, b: usize) -> usize {
+        let (a, b) = (self.digits(a, self.m), self.digits(b, self.m));
+        let mut product = vec![0; 2 * self.m];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                product[i + j] = (product[i + j] + x * y) % self.p;
+            }
+        }
+        self.number(&self.rem(&product, &self.modulus))
+    }
+
+    /// Квадратичный характер: 0 для нуля, 1 для ненулевых квадратов, -1 для остальных.
+    fn characters(&self) -> Vec<i8> {
+        let mut chi = vec![-1; self.order()];
+        chi[0] = 0;
+        for x in 1..self.order() {
+            chi[self.mul(x, x)] = 1;
+        }
+        chi
+    }
+
+    /// Матрица Якобшталя: Q[i][j] = chi(a_i - a_j).
+    fn jacobsthal(&self) -> Array2<i8> {
+        let chi = self.characters();
+        let q = self.order();
+        Array2::from_shape_fn((q, q), |(i, j)| chi[self.sub(i, j)])
+    }
+}
+
+/// Первая строка симметричного циркулянта порядка n по её первой половине.
+fn symmetric_row(half: &str, n: usize) -> Vec<i8> {
+    let half: Vec<i8> = half.chars().map(|c| if c == '+' { 1 } else { -1 }).collect();
+    (0..n).map(|i| half[i.min(n - i)]).collect()
+}
+
+/// Построения матриц Адамара.
+impl HadamardMatrix {
+    /// Матрица Пэли типа I порядка q + 1 для степени простого числа q ≡ 3 (mod 4):
+    /// H = I + S, где S = [[0, 1], [-1, Q]] и Q -- матрица Якобшталя поля GF(q).
+    /// Матрица кососимметрическая. Возвращается ошибка, если q не степень простого числа,
+    /// q не сравнимо с 3 по модулю 4 или порядок больше [MAX_CONSTRUCTED_ORDER].
+    pub fn paley1(q: usize) -> Result<HadamardMatrix, &'static str> {
+        if q % 4 != 3 {
+            return Err("Paley I construction requires q ≡ 3 (mod 4)");
+        }
+        if q + 1 > MAX_CONSTRUCTED_ORDER {
+            return Err("constructed matrix order is too large");
+        }
+        let jacobsthal = Field::new(q)?.jacobsthal();
+        Ok(HadamardMatrix::wrap(Array2::from_shape_fn((q + 1, q + 1), |(i, j)| match (i, j) {
+            _ if i == j => 1,
+            (0, _) => 1,
+            (_, 0) => -1,
+            _ => jacobsthal[[i - 1, j - 1]],
+        })))
+    }
+
+    /// Матрица Пэли типа II порядка 2(q + 1) для степени простого числа q ≡ 1 (mod 4):
+    /// каждый элемент c симметричной конференц-матрицы C = [[0, 1], [1, Q]] заменяется
+    /// блоком c * [[1, 1], [1, -1]], а нули диагонали -- блоком [[1, -1], [-1, -1]].
+    /// Возвращается ошибка, если q не степень простого числа, q не сравнимо с 1 по модулю 4
+    /// или порядок больше [MAX_CONSTRUCTED_ORDER].
+    pub fn paley2(q: usize) -> Result<HadamardMatrix, &'static str> {
+        if q % 4 != 1 {
+            return Err("Paley II construction requires q ≡ 1 (mod 4)");
+        }
+        if 2 * (q + 1) > MAX_CONSTRUCTED_ORDER {
+            return Err("constructed matrix order is too large");
+        }
+        let jacobsthal = Field::new(q)?.jacobsthal();
+        let conference = |i: usize, j: usize| match (i, j) {
+            _ if i == j => 0,
+            (0, _) | (_, 0) => 1,
+            _ => jacobsthal[[i - 1, j - 1]],
+        };
+        let n = 2 * (q + 1);
+        Ok(HadamardMatrix::wrap(Array2::from_shape_fn((n, n), |(i, j)| {
+            let (r, c) = (i % 2, j % 2);
+            match conference(i / 2, j / 2) {
+                0 if r == 0 && c == 0 => 1,
+                0 => -1,
+                entry if r == 1 && c == 1 => -entry,
+                entry => entry,
+            }
+        })))
+    }
+
+    /// Произведение Кронекера: матрица порядка mn, в которой каждый элемент h этой матрицы
+    /// заменён блоком h * other. Произведение матриц Адамара -- матрица Адамара.
+    pub fn kronecker(&self, other: &HadamardMatrix) -> HadamardMatrix {
+        let (a, b) = (self.view(), other.view());
+        let m = other.order();
+        HadamardMatrix::wrap(Array2::from_shape_fn((self.order() * m, self.order() * m), |(i, j)| {
+            a[[i / m, j / m]] * b[[i % m, j % m]]
+        }))
+    }
+
+    /// Матрица Уильямсона порядка 4n по четвёрке из таблицы известных четвёрок:
+    /// n -- нечётное число от 3 до 29 (порядки 12, 20, ..., 116, в том числе 92).
+    /// Для других n возвращается ошибка.
+    pub fn williamson(n: usize) -> Result<HadamardMatrix, &'static str> {
+        let (_, halves) = WILLIAMSON.iter().find(|(size, _)| *size == n).ok_or("no known Williamson quadruple for that n")?;
+        let rows: Vec<Vec<i8>> = halves.iter().map(|half| symmetric_row(half, n)).collect();
+        HadamardMatrix::williamson_blocks([&rows[0], &rows[1], &rows[2], &rows[3]])
+    }
+
+    /// Матрица Уильямсона порядка 4n по первым строкам симметричных циркулянтов A, B, C, D
+    /// порядка n, для которых A^2 + B^2 + C^2 + D^2 = 4nI:
+    /// H = [[A, B, C, D], [-B, A, -D, C], [-C, D, A, -B], [-D, -C, B, A]].
+    /// Условие на блоки проверяется за O(n^2); при его нарушении возвращается ошибка.
+    pub fn williamson_blocks(rows: [&[i8]; 4]) -> Result<HadamardMatrix, &'static str> {
+        let n = rows[0].len();
+        if n == 0 || 4 * n > MAX_CONSTRUCTED_ORDER {
+            return Err("constructed matrix order is too large");
+        }
+        for row in rows {
+            if row.len() != n || row.iter().any(|&x| x != 1 && x != -1) || (1..n).any(|i| row[i] != row[n - i]) {
+                return Err("Williamson blocks must be symmetric ±1 rows of equal length");
+            }
+        }
+        // сумма периодических автокорреляций блоков при ненулевом сдвиге должна быть нулевой
+        for shift in 1..n {
+            let sum: i32 = rows.iter().flat_map(|row| (0..n).map(move |i| i32::from(row[i] * row[(i + shift) % n]))).sum();
+            if sum != 0 {
+                return Err("Williamson blocks do not satisfy A^2 + B^2 + C^2 + D^2 = 4nI");
+            }
+        }
+        // номер блока и его знак в массиве Уильямсона
+        const ARRAY: [[(usize, i8); 4]; 4] = [
+            [(0, 1), (1, 1), (2, 1), (3, 1)],
+            [(1, -1), (0, 1), (3, -1), (2, 1)],
+            [(2, -1), (3, 1), (0, 1), (1, -1)],
+            [(3, -1), (2, -1), (1, 1), (0, 1)],
+        ];
+        Ok(HadamardMatrix::wrap(Array2::from_shape_fn((4 * n, 4 * n), |(i, j)| {
+            let (block, sign) = ARRAY[i / n][j / n];
+            sign * rows[block][(j % n + n - i % n) % n]
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HadamardSSS, SharingScheme};
+
+    /// Проверка H * H.T = nI.
+    fn is_hadamard(h_mtx: &HadamardMatrix) -> bool {
+        HadamardMatrix::from_i8(&h_mtx.view().to_owned()).is_ok()
+    }
+
+    #[test]
+    fn test_field() {
+        let field = Field::new(9).unwrap();
+        assert_eq!((field.p, field.m), (3, 2));
+        let squares = field.characters().iter().filter(|&&chi| chi == 1).count();
+        assert_eq!(squares, 4);
+        for a in 1..9 {
+            assert_eq!((1..9).filter(|&b| field.mul(a, b) == 1).count(), 1);
+        }
+        assert!(Field::new(12).is_err());
+        assert!(Field::new(1).is_err());
+    }
+
+    #[test]
+    fn test_paley1() {
+        for q in [3, 7, 11, 19, 23, 27, 43] {
+            let h_mtx = HadamardMatrix::paley1(q).unwrap();
+            assert_eq!(h_mtx.order(), q + 1);
+            assert!(is_hadamard(&h_mtx), "q = {q}");
+            assert!(h_mtx.is_skew());
+        }
+        assert!(HadamardMatrix::paley1(5).is_err());
+        assert!(HadamardMatrix::paley1(15).is_err());
+        assert!(HadamardMatrix::paley1(4099).is_err());
+
+        let hsss = HadamardSSS::from_matrix(&HadamardMatrix::paley1(19).unwrap()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        assert_eq!(parts.len(), 19);
+        assert_eq!(hsss.reconstruct(parts[8..19].to_vec()).unwrap(), 314159265);
+    }
+
+    #[test]
+    fn test_paley2() {
+        for q in [5, 9, 13, 17, 25] {
+            let h_mtx = HadamardMatrix::paley2(q).unwrap();
+            assert_eq!(h_mtx.order(), 2 * (q + 1));
+            assert!(is_hadamard(&h_mtx), "q = {q}");
+        }
+        assert!(HadamardMatrix::paley2(7).is_err());
+        assert!(HadamardMatrix::paley2(21).is_err());
+        assert!(HadamardMatrix::paley2(2053).is_err());
+
+        let hsss = HadamardSSS::from_matrix(&HadamardMatrix::paley2(13).unwrap()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        assert_eq!(parts.len(), 27);
+        assert_eq!(hsss.reconstruct(parts[0..15].to_vec()).unwrap(), 314159265);
+        assert!(hsss.reconstruct(parts[0..14].to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_williamson() {
+        for &(n, _) in WILLIAMSON {
+            let h_mtx = HadamardMatrix::williamson(n).unwrap();
+            assert_eq!(h_mtx.order(), 4 * n);
+            assert!(is_hadamard(&h_mtx), "n = {n}");
+        }
+        assert!(HadamardMatrix::williamson(35).is_err());
+        let row: &[i8] = &[1, -1, -1];
+        assert!(HadamardMatrix::williamson_blocks([row, row, row, &[1, 1, 1]]).is_ok());
+        assert!(HadamardMatrix::williamson_blocks([row, row, row, row]).is_err());
+        assert!(HadamardMatrix::williamson_blocks([row, row, row, &[1, 1, -1]]).is_err());
+
+        let hsss = HadamardSSS::from_matrix(&HadamardMatrix::williamson(23).unwrap()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        assert_eq!(parts.len(), 91);
+        assert_eq!(hsss.reconstruct(parts[0..47].to_vec()).unwrap(), 314159265);
+    }
+
+    #[test]
+    fn test_kronecker() {
+        let h4 = HadamardMatrix::sylvester(2).unwrap();
+        let h12 = HadamardMatrix::paley1(11).unwrap();
+        let h48 = h4.kronecker(&h12);
+        assert_eq!(h48.order(), 48);
+        assert!(is_hadamard(&h48));
+        assert_eq!(h48.view()[[13, 25]], h4.view()[[1, 2]] * h12.view()[[1, 1]]);
+        assert_eq!(HadamardMatrix::sylvester(1).unwrap().kronecker(&HadamardMatrix::sylvester(2).unwrap()).view(),
+                   HadamardMatrix::sylvester(3).unwrap().view());
+
+        let hsss = HadamardSSS::from(&h48.to_i32()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        assert_eq!(parts.len(), 47);
+        assert_eq!(hsss.reconstruct(parts[0..25].to_vec()).unwrap(), 314159265);
+    }
+}
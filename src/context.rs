@@ -0,0 +1,81 @@
+//! Многократное разделение без выделения памяти.
+//!
+//! [ShareContext] хранит маски известных битов всех строк схемы и буферы для долей
+//! и битов-заполнителей. После первого вызова [ShareContext::share] буферы переиспользуются,
+//! так что сервис, разделяющий тысячи секретов в секунду, не выделяет память на каждый секрет.
+use crate::scheme_impl::Part;
+use crate::HadamardSSS;
+use rand::Rng;
+
+/// Переиспользуемые буферы для разделения и восстановления секретов одной схемой.
+pub struct ShareContext<'a> {
+    /// Схема, которой разделяются секреты.
+    scheme: &'a HadamardSSS,
+    /// Маски известных битов всех строк.
+    masks: Vec<u32>,
+    /// Доли последнего разделения.
+    parts: Vec<Part>,
+    /// Биты-заполнители последнего разделения.
+    padding: Vec<u32>,
+}
+
+/// Реализация методов контекста разделения.
+impl<'a> ShareContext<'a> {
+    /// Разделение секрета. Доли лежат в буфере контекста до следующего вызова.
+    pub fn share(&mut self, secret: u32) -> &[Part] {
+        crate::entropy::rng().fill(&mut self.padding[..]);
+        let fingerprint = self.scheme.fingerprint();
+        self.parts.clear();
+        self.parts.extend(self.masks.iter().zip(&self.padding).enumerate().map(|(i, (&mask, &padding))| {
+            Part::from(i, (secret & mask) | (padding & !mask)).with_fingerprint(fingerprint)
+        }));
+        &self.parts
+    }
+
+    /// Восстановление секрета по заимствованным долям; проверки те же, что
+    /// в [crate::SharingScheme::reconstruct].
+    pub fn reconstruct(&self, parts: &[Part]) -> Result<u32, &'static str> {
+        self.scheme.check_canaries(parts);
+        if parts.len() < self.scheme.threshold {
+            return Err("less than threshold parties");
+        }
+        self.scheme.hss.check_parts(parts)?;
+        Ok(parts.iter().fold(0, |res, part| res | (part.data() & self.masks[part.number()])))
+    }
+}
+
+/// Создание контекста разделения.
+impl HadamardSSS {
+    /// Контекст с буферами под доли этой схемы.
+    pub fn share_context(&self) -> ShareContext<'_> {
+        let n = self.hss.mtx_len();
+        ShareContext { scheme: self, masks: self.masks(), parts: Vec::with_capacity(n), padding: vec![0; n] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::SharingScheme;
+
+    #[test]
+    fn test_context_matches_scheme() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut context = hsss.share_context();
+        for secret in [0, 1, 314159265, u32::MAX] {
+            let parts = context.share(secret).to_vec();
+            assert_eq!(parts.len(), 7);
+            let padding: Vec<u32> = parts.iter().map(|part| part.data()).collect();
+            let expected = hsss.hss.share_padded(secret, |i| padding[i]);
+            assert!(parts.iter().zip(&expected).all(|(a, b)| a.data() == b.data() && a.number() == b.number()));
+            assert!(parts.iter().all(|part| part.fingerprint() == Some(hsss.fingerprint())));
+            assert_eq!(context.reconstruct(&parts[1..6]).unwrap(), secret);
+            assert_eq!(hsss.reconstruct(parts[2..7].to_vec()).unwrap(), secret);
+        }
+        let parts = context.share(7).to_vec();
+        assert_eq!(context.reconstruct(&parts[0..4]), Err("less than threshold parties"));
+        let foreign = [parts[0], parts[1], parts[2], parts[3], Part::from(7, 0)];
+        assert!(context.reconstruct(&foreign).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Смена порога без восстановления секрета.
+//!
+//! Доля схемы содержит известные владельцу биты секрета в открытом виде, поэтому долю новой
+//! схемы (другой матрицы и порога) можно собрать из битов старых долей, ни разу не собирая
+//! секрет целиком. Дилер, которому нужны только матрицы, публикует [ConversionPlan]: для
+//! каждой строки новой схемы и каждого известного ей бита -- от каких старых долей этот бит
+//! приходит. Владельцы старых долей локально вычисляют [ConversionMessage] и передают их
+//! владельцам новых долей, которые собирают доли через [ConversionPlan::combine].
+//! Каждый участник узнаёт при этом только биты своей новой доли.
+//!
+//! При избыточности плана больше 1 каждый бит приходит от нескольких старых долей,
+//! и искажённое сообщение обнаруживается по расхождению. Старые доли после смены порога
+//! нужно уничтожить: вместе с новыми они по-прежнему позволяют восстановить секрет.
+use crate::scheme_impl::Part;
+use crate::HadamardSSS;
+use rand::RngCore;
+
+/// Маршрут битов от старой доли к новой.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Route {
+    /// Номер старой доли.
+    pub from: usize,
+    /// Номер новой доли.
+    pub to: usize,
+    /// Передаваемые биты секрета.
+    pub bits: u32,
+}
+
+/// Сообщение владельца старой доли владельцу новой доли.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConversionMessage {
+    /// Номер старой доли.
+    pub from: usize,
+    /// Номер новой доли.
+    pub to: usize,
+    /// Передаваемые биты секрета.
+    pub bits: u32,
+    /// Значения этих битов.
+    pub values: u32,
+}
+
+/// Опубликованный дилером план смены порога.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionPlan {
+    /// Маски известных битов строк новой схемы.
+    masks: Vec<u32>,
+    /// Маршруты битов.
+    routes: Vec<Route>,
+    /// Сколько старых долей передают каждый бит.
+    redundancy: usize,
+    /// Отпечаток новой схемы, которым помечаются собранные доли.
+    fingerprint: u32,
+}
+
+/// Реализация методов плана.
+impl ConversionPlan {
+    /// Возвращение маршрутов плана.
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /// Возвращение значения поля redundancy.
+    pub fn redundancy(&self) -> usize {
+        self.redundancy
+    }
+
+    /// Сообщения, которые владелец старой доли отправляет по плану.
+    pub fn messages(&self, part: &Part) -> Vec<ConversionMessage> {
+        self.routes
+            .iter()
+            .filter(|route| route.from == part.number())
+            .map(|route| ConversionMessage { from: route.from, to: route.to, bits: route.bits, values: part.data() & route.bits })
+            .collect()
+    }
+
+    /// Сборка новой доли с номером number из полученных сообщений. Сообщения для других
+    /// долей пропускаются. Возвращается ошибка, если сообщение не предусмотрено планом,
+    /// какого-то сообщения не хватает или сообщения расходятся в значении бита.
+    pub fn combine(&self, number: usize, messages: &[ConversionMessage]) -> Result<Part, &'static str> {
+        let mask = *self.masks.get(number).ok_or("scheme mismatch: part number exceeds the order of the scheme")?;
+        let planned: Vec<&Route> = self.routes.iter().filter(|route| route.to == number).collect();
+        let received: Vec<&ConversionMessage> = messages.iter().filter(|message| message.to == number).collect();
+        let (mut known, mut ones, mut zeros) = (0u32, 0u32, 0u32);
+        for route in &planned {
+            let mut matching = received.iter().filter(|message| message.from == route.from);
+            let message = matching.next().ok_or("missing conversion message")?;
+            if message.bits != route.bits || matching.next().is_some() {
+                return Err("unexpected conversion message");
+            }
+            known |= message.bits;
+            ones |= message.values & message.bits;
+            zeros |= !message.values & message.bits;
+        }
+        if received.len() != planned.len() {
+            return Err("unexpected conversion message");
+        }
+        if known != mask {
+            return Err("missing conversion message");
+        }
+        if ones & zeros != 0 {
+            return Err("conversion messages disagree");
+        }
+        Ok(Part::from(number, ones | (crate::entropy::rng().next_u32() & !mask)).with_fingerprint(self.fingerprint))
+    }
+}
+
+/// Составление плана смены порога.
+impl HadamardSSS {
+    /// План перехода от этой схемы к схеме target, если в переходе участвуют старые доли
+    /// с номерами available. Каждый бит каждой новой доли передают redundancy старых долей,
+    /// знающих его; нагрузка распределяется между ними поровну. Возвращается ошибка, если
+    /// какой-то бит известен меньше чем redundancy участвующим долям.
+    pub fn conversion_plan(&self, target: &HadamardSSS, available: &[usize], redundancy: usize) -> Result<ConversionPlan, &'static str> {
+        if redundancy == 0 {
+            return Err("redundancy must be positive");
+        }
+        let n = self.hss.mtx_len();
+        let mut available: Vec<usize> = available.to_vec();
+        available.sort_unstable();
+        available.dedup();
+        if available.iter().any(|&number| number >= n) {
+            return Err("scheme mismatch: part number exceeds the order of the scheme");
+        }
+        let old_masks = self.masks();
+        let masks = target.masks();
+        let mut load = vec![0usize; n];
+        let mut routes: Vec<Route> = Vec::new();
+        for (to, &mask) in masks.iter().enumerate() {
+            for bit in (0..u32::BITS).filter(|&bit| mask >> bit & 1 == 1) {
+                let mut sources: Vec<usize> = available.iter().copied().filter(|&from| old_masks[from] >> bit & 1 == 1).collect();
+                if sources.len() < redundancy {
+                    return Err("available shares do not cover the bits of the new shares");
+                }
+                sources.sort_by_key(|&from| (load[from], from));
+                for &from in &sources[..redundancy] {
+                    load[from] += 1;
+                    match routes.iter_mut().find(|route| route.from == from && route.to == to) {
+                        Some(route) => route.bits |= 1 << bit,
+                        None => routes.push(Route { from, to, bits: 1 << bit }),
+                    }
+                }
+            }
+        }
+        routes.sort_by_key(|route| (route.to, route.from));
+        Ok(ConversionPlan { masks, routes, redundancy, fingerprint: target.fingerprint() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equivalence::classes;
+    use crate::fixtures::matrix;
+    use crate::SharingScheme;
+
+    /// Смена порога: все сообщения всех старых долей и сборка всех новых.
+    fn convert(plan: &ConversionPlan, parts: &[Part], rows: usize) -> Result<Vec<Part>, &'static str> {
+        let messages: Vec<ConversionMessage> = parts.iter().flat_map(|part| plan.messages(part)).collect();
+        (0..rows).map(|number| plan.combine(number, &messages)).collect()
+    }
+
+    #[test]
+    fn test_threshold_change() {
+        let old = HadamardSSS::from(&matrix()).unwrap();
+        let new = HadamardSSS::from_matrix(&classes(16).unwrap().next().unwrap()).unwrap();
+        let parts = old.share(314159265).unwrap();
+
+        let plan = old.conversion_plan(&new, &[0, 1, 2, 3, 4, 5, 6], 2).unwrap();
+        assert!(plan.routes().iter().all(|route| route.bits.count_ones() > 0));
+        let converted = convert(&plan, &parts, 15).unwrap();
+        assert!(converted.iter().all(|part| part.fingerprint() == Some(new.fingerprint())));
+        assert_eq!(new.reconstruct(converted[0..9].to_vec()).unwrap(), 314159265);
+        assert!(new.reconstruct(converted[0..8].to_vec()).is_err());
+        assert!(new.validate(converted.clone()).is_clean());
+
+        // и обратно, к порогу 5, от части новых долей
+        let back = new.conversion_plan(&old, &(0..12).collect::<Vec<_>>(), 1).unwrap();
+        let restored = convert(&back, &converted, 7).unwrap();
+        assert_eq!(old.reconstruct(restored[2..7].to_vec()).unwrap(), 314159265);
+    }
+
+    #[test]
+    fn test_verification() {
+        let old = HadamardSSS::from(&matrix()).unwrap();
+        let new = HadamardSSS::from_matrix(&classes(16).unwrap().next().unwrap()).unwrap();
+        let parts = old.share(7).unwrap();
+        assert!(old.conversion_plan(&new, &[0, 1, 2, 3, 4, 5, 6], 4).is_err());
+        assert!(old.conversion_plan(&new, &[0, 1], 1).is_err());
+        assert!(old.conversion_plan(&new, &[0, 7], 1).is_err());
+
+        let plan = old.conversion_plan(&new, &[0, 1, 2, 3, 4, 5, 6], 2).unwrap();
+        let mut messages: Vec<ConversionMessage> = parts.iter().flat_map(|part| plan.messages(part)).collect();
+        let index = messages.iter().position(|message| message.to == 0).unwrap();
+        messages[index].values ^= messages[index].bits;
+        assert_eq!(plan.combine(0, &messages).err(), Some("conversion messages disagree"));
+        messages.remove(index);
+        assert_eq!(plan.combine(0, &messages).err(), Some("missing conversion message"));
+        let mut forged = messages[0];
+        forged.bits = u32::MAX;
+        assert_eq!(plan.combine(forged.to, &[forged]).err(), Some("unexpected conversion message"));
+        assert!(plan.combine(15, &messages).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Строгое декодирование долей из недоверенных байтов.
+//!
+//! Комбинирующий сервис получает доли от участников, то есть от потенциального противника.
+//! [decode_share] сначала ограничивает длину входа [MAX_ENCODED_LEN], затем определяет формат ([crate::wire]
+//! или текст [crate::bech32], [crate::voice]) и передаёт вход соответствующему строгому декодеру.
+//! Ни на каком входе декодирование не паникует и не выделяет памяти больше, чем
+//! пропорционально ограниченной длине; это проверяется фаззингом (`fuzz/`, цель
+//! `decode_share`) и тестами ниже.
+use crate::scheme_impl::Part;
+use crate::HadamardSSS;
+
+/// Наибольшая длина закодированной доли в байтах; любой поддерживаемый формат
+/// укладывается в неё с запасом.
+pub const MAX_ENCODED_LEN: usize = 64;
+
+/// Формат закодированной доли.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShareFormat {
+    /// Двоичный формат, см. [crate::wire].
+    Wire,
+    /// Текст с контрольной суммой bech32m, см. [crate::bech32].
+    Bech32,
+    /// Текст для чтения вслух, см. [crate::voice].
+    Voice,
+}
+
+/// Определение формата. Вход из печатных символов ASCII считается текстом, двоичный
+/// формат начинается с непечатного байта версии. Текст с префиксом [crate::bech32::HRP] и разделителем считается bech32m.
+pub fn detect(bytes: &[u8]) -> Result<ShareFormat, &'static str> {
+    if bytes.iter().all(|byte| byte.is_ascii_graphic() || byte.is_ascii_whitespace()) && !bytes.is_empty() {
+        let prefix = crate::bech32::HRP.len() + 1;
+        if bytes.len() > prefix && bytes[..prefix].eq_ignore_ascii_case(format!("{}1", crate::bech32::HRP).as_bytes()) {
+            return Ok(ShareFormat::Bech32);
+        }
+        return Ok(ShareFormat::Voice);
+    }
+    match bytes.first() {
+        None => Err("empty share encoding"),
+        Some(&crate::wire::WIRE_VERSION | &crate::wire::LEGACY_WIRE_VERSION) => Ok(ShareFormat::Wire),
+        Some(_) => Err("unknown share encoding"),
+    }
+}
+
+/// Декодирование доли в любом поддерживаемом формате.
+pub fn decode_share(bytes: &[u8]) -> Result<(ShareFormat, Part), &'static str> {
+    if bytes.len() > MAX_ENCODED_LEN {
+        return Err("share encoding is too long");
+    }
+    let format = detect(bytes)?;
+    let part = match format {
+        ShareFormat::Wire => Part::from_bytes(bytes)?,
+        ShareFormat::Bech32 => crate::bech32::decode(std::str::from_utf8(bytes).map_err(|_| "bech32 encoding must be ASCII")?)?,
+        ShareFormat::Voice => crate::voice::decode(std::str::from_utf8(bytes).map_err(|_| "voice encoding must be ASCII")?)?,
+    };
+    Ok((format, part))
+}
+
+/// Декодирование долей для схемы.
+impl HadamardSSS {
+    /// Декодирование доли с проверкой, что её номер есть в схеме, а отпечаток совпадает
+    /// с отпечатком схемы.
+    pub fn decode_share(&self, bytes: &[u8]) -> Result<Part, &'static str> {
+        let (_, part) = decode_share(bytes)?;
+        self.hss.check_parts(&[part])?;
+        Ok(part)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    /// Корректные кодировки доли во всех форматах.
+    fn encodings(part: &Part) -> Vec<Vec<u8>> {
+        vec![part.to_bytes().unwrap().to_vec(), crate::bech32::encode(part).unwrap().into_bytes(), crate::voice::encode(part).unwrap().into_bytes()]
+    }
+
+    #[test]
+    fn test_formats_detected() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        for bytes in encodings(&Part::from(6, 0xDEADBEEF).with_fingerprint(hsss.fingerprint())) {
+            assert!(bytes.len() <= MAX_ENCODED_LEN);
+            assert_eq!(hsss.decode_share(&bytes).unwrap().data(), 0xDEADBEEF);
+        }
+        assert_eq!(detect(b"001XXD-BDYXWC"), Ok(ShareFormat::Voice));
+        assert_eq!(detect(crate::bech32::encode(&Part::from(3, 1).with_fingerprint(0)).unwrap().as_bytes()), Ok(ShareFormat::Bech32));
+        assert_eq!(detect(&Part::from(0, 0).with_fingerprint(0).to_bytes().unwrap()), Ok(ShareFormat::Wire));
+        assert_eq!(detect(&[1, 0, 0, 0, 6, 0, 0, 0, 0]), Ok(ShareFormat::Wire));
+        for part in [Part::from(7, 0).with_fingerprint(hsss.fingerprint()), Part::from(6, 0).with_fingerprint(!hsss.fingerprint())] {
+            for bytes in encodings(&part) {
+                assert!(decode_share(&bytes).is_ok());
+                assert!(hsss.decode_share(&bytes).is_err());
+            }
+        }
+        assert_eq!(decode_share(&[]).err(), Some("empty share encoding"));
+        assert_eq!(decode_share(&[0x01; 65]).err(), Some("share encoding is too long"));
+        assert_eq!(decode_share(&[0xFF, 0]).err(), Some("unknown share encoding"));
+    }
+
+    #[test]
+    fn test_malformed_input_never_panics() {
+        let mut rng = ChaCha20Rng::seed_from_u64(981);
+        for _ in 0..2000 {
+            let part = Part::from(rng.gen_range(0..1 << 16), rng.gen()).with_fingerprint(rng.gen());
+            for bytes in encodings(&part) {
+                for len in 0..bytes.len() {
+                    let _ = decode_share(&bytes[..len]);
+                }
+                let mut mutated = bytes.clone();
+                for _ in 0..rng.gen_range(1..4) {
+                    let index = rng.gen_range(0..mutated.len());
+                    mutated[index] ^= 1 << rng.gen_range(0..8);
+                }
+                let _ = decode_share(&mutated);
+            }
+            let noise: Vec<u8> = (0..rng.gen_range(0..=MAX_ENCODED_LEN)).map(|_| rng.gen()).collect();
+            let _ = decode_share(&noise);
+        }
+    }
+}
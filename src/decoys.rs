@@ -0,0 +1,101 @@
+//! Генерация долей-приманок.
+//!
+//! Приманка выглядит как обычная доля: номер строки, равномерно распределённое значение
+//! и отпечаток схемы, но не принадлежит ни одному разделению. Номера и значения приманок
+//! выводятся из ключа дилера и случайного значения сеанса, поэтому объединитель, знающий
+//! ключ, может пересчитать их и отбросить перед восстановлением.
+use crate::scheme_impl::Part;
+use crate::watermark::{watermark, WatermarkKey, WatermarkNonce};
+use crate::HadamardSSS;
+use rand::RngCore;
+
+/// Метка HKDF для номеров приманок.
+const NUMBER_LABEL: &[u8] = b"hadamard_sss decoy number";
+/// Метка HKDF для значений приманок.
+const DATA_LABEL: &[u8] = b"hadamard_sss decoy data";
+
+/// Описание набора приманок, необходимое объединителю для их распознавания.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decoys {
+    /// Случайное значение сеанса генерации.
+    nonce: WatermarkNonce,
+    /// Число сгенерированных приманок.
+    count: usize,
+}
+
+/// Реализация методов описания набора приманок.
+impl Decoys {
+    /// Создание экземпляра структуры по ранее сохранённым значениям.
+    pub fn from(nonce: WatermarkNonce, count: usize) -> Self {
+        Decoys { nonce, count }
+    }
+
+    /// Возвращение значения поля nonce.
+    pub fn nonce(&self) -> WatermarkNonce {
+        self.nonce
+    }
+
+    /// Возвращение значения поля count.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Генерация и отбрасывание приманок.
+impl HadamardSSS {
+    /// Вычисление i-й приманки набора.
+    fn decoy(&self, key: &WatermarkKey, decoys: &Decoys, i: usize) -> Part {
+        let n = self.hss.mtx_len() as u32;
+        let number = watermark(key, &decoys.nonce, NUMBER_LABEL, i) % n;
+        Part::from(number as usize, watermark(key, &decoys.nonce, DATA_LABEL, i)).with_fingerprint(self.fingerprint())
+    }
+
+    /// Генерация count приманок. Возвращает приманки и описание набора для объединителя.
+    pub fn generate_decoys(&self, count: usize, key: &WatermarkKey) -> (Vec<Part>, Decoys) {
+        let mut nonce = [0u8; 16];
+        crate::entropy::rng().fill_bytes(&mut nonce);
+        let decoys = Decoys { nonce, count };
+        ((0..count).map(|i| self.decoy(key, &decoys, i)).collect(), decoys)
+    }
+
+    /// Отбрасывание приманок из набора долей.
+    pub fn discard_decoys(&self, parts: Vec<Part>, key: &WatermarkKey, decoys: &Decoys) -> Vec<Part> {
+        let known: Vec<(usize, u32)> = (0..decoys.count)
+            .map(|i| self.decoy(key, decoys, i))
+            .map(|part| (part.number(), part.data()))
+            .collect();
+        parts
+            .into_iter()
+            .filter(|part| !known.contains(&(part.number(), part.data())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SharingScheme;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_decoys_discarded() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let key = [3u8; 32];
+        let parts = hsss.share(314159265).unwrap();
+        let (fakes, decoys) = hsss.generate_decoys(4, &key);
+        assert_eq!(fakes.len(), 4);
+        assert!(fakes.iter().all(|fake| fake.fingerprint() == Some(hsss.fingerprint())));
+        assert!(fakes.iter().all(|part| part.number() < 7));
+
+        let mut mixed = parts[0..5].to_vec();
+        mixed.insert(2, fakes[0]);
+        mixed.extend_from_slice(&fakes[1..]);
+        let cleaned = hsss.discard_decoys(mixed, &key, &decoys);
+        assert_eq!(cleaned.len(), 5);
+        assert_eq!(hsss.reconstruct(cleaned).unwrap(), 314159265);
+
+        let restored = Decoys::from(decoys.nonce(), decoys.count());
+        assert!(hsss.discard_decoys(fakes.clone(), &key, &restored).is_empty());
+        assert_eq!(hsss.discard_decoys(fakes, &[4u8; 32], &restored).len(), 4);
+    }
+}
@@ -0,0 +1,80 @@
+//! Вывод подключей из одного разделённого мастер-ключа.
+//!
+//! Вместо отдельной церемонии раздачи на каждый ключ разделяется один [MasterKey],
+//! а ключи для конкретных назначений выводятся из него HKDF-SHA256 с меткой назначения.
+//! Подключи с разными метками независимы: знание одного ничего не говорит о других
+//! и о мастер-ключе. Метки не секретны; их список удобно хранить вместе с долями,
+//! чтобы после восстановления получить те же ключи.
+use crate::keys::{sensitive, KeyCheckValue, MasterKey, SymmetricKey};
+use crate::scheme_impl::ChunkedPart;
+use crate::HadamardSSS;
+use hkdf::Hkdf;
+use rand::RngCore;
+use sha2::Sha256;
+
+/// Префикс метки HKDF для подключей.
+const LABEL: &[u8] = b"hadamard_sss subkey ";
+
+/// Подключ типа K для назначения label.
+pub fn derive_subkey<K: SymmetricKey>(master: &MasterKey, label: &str) -> K {
+    let mut bytes = sensitive(vec![0u8; K::LEN]);
+    Hkdf::<Sha256>::from_prk(master.as_bytes())
+        .expect("32 bytes is a valid pseudorandom key")
+        .expand_multi_info(&[LABEL, label.as_bytes()], &mut bytes)
+        .expect("key length is a valid HKDF output length");
+    K::from_slice(&bytes).expect("buffer has the key length")
+}
+
+/// Подключи типа K для всех назначений labels в том же порядке.
+pub fn derive_subkeys<K: SymmetricKey>(master: &MasterKey, labels: &[&str]) -> Vec<K> {
+    labels.iter().map(|label| derive_subkey(master, label)).collect()
+}
+
+/// Раздача мастер-ключа.
+impl HadamardSSS {
+    /// Разделение нового случайного мастер-ключа. Возвращаются доли, значение проверки
+    /// ключа и сам ключ, чтобы дилер мог сразу вывести нужные подключи и забыть мастер-ключ.
+    /// Восстанавливается мастер-ключ [HadamardSSS::reconstruct_key].
+    pub fn share_master(&self) -> Result<(Vec<ChunkedPart>, KeyCheckValue, MasterKey), &'static str> {
+        let mut bytes = sensitive([0u8; 32]);
+        crate::entropy::rng().fill_bytes(&mut *bytes);
+        let master = MasterKey::from(*bytes);
+        let (parts, kcv) = self.share_key(&master)?;
+        Ok((parts, kcv, master))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::keys::{Aes128Key, Aes256Key, ChaChaKey};
+
+    #[test]
+    fn test_subkeys() {
+        let master = MasterKey::from([5; 32]);
+        let storage: Aes256Key = derive_subkey(&master, "storage");
+        let again: Aes256Key = derive_subkey(&master, "storage");
+        assert_eq!(storage.as_bytes(), again.as_bytes());
+        let backup: Aes256Key = derive_subkey(&master, "backup");
+        assert_ne!(storage.as_bytes(), backup.as_bytes());
+        assert_ne!(storage.as_bytes(), master.as_bytes());
+        let short: Aes128Key = derive_subkey(&master, "storage");
+        assert_eq!(short.as_bytes(), &storage.as_bytes()[..16]);
+        let other: Aes256Key = derive_subkey(&MasterKey::from([6; 32]), "storage");
+        assert_ne!(storage.as_bytes(), other.as_bytes());
+    }
+
+    #[test]
+    fn test_share_master() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let labels = ["database", "transport", "signing"];
+        let (parts, kcv, master) = hsss.share_master().unwrap();
+        let issued: Vec<ChaChaKey> = derive_subkeys(&master, &labels);
+
+        let restored: MasterKey = hsss.reconstruct_key(parts[2..7].to_vec(), &kcv).unwrap();
+        let derived: Vec<ChaChaKey> = derive_subkeys(&restored, &labels);
+        assert!(issued.iter().zip(&derived).all(|(a, b)| a.as_bytes() == b.as_bytes()));
+        assert!(hsss.reconstruct_key::<MasterKey>(parts[0..4].to_vec(), &kcv).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Обмен блок-дизайнами с пакетом DESIGN системы GAP.
+//!
+//! Матрица инцидентности записывается вызовом `BlockDesign(v, blocks)`: i-й строке матрицы
+//! соответствует блок, состоящий из номеров столбцов, в которых стоит 1 (нумерация с 1,
+//! как принято в GAP). Результат можно загрузить в GAP и проверить параметры дизайна:
+//! ```text
+//! gap> LoadPackage("design");;
+//! gap> D := BlockDesign(3, [[2], [1], [3]]);;
+//! gap> AllTDesignLambdas(D);
+//! ```
+use crate::construction::MAX_CONSTRUCTED_ORDER;
+use ndarray::{Array2, ArrayView2};
+
+/// Наибольшее число точек и блоков, которое читает [from_gap].
+pub const MAX_GAP_SIZE: usize = MAX_CONSTRUCTED_ORDER;
+
+/// Запись матрицы инцидентности в формате пакета DESIGN.
+/// Блоки идут в порядке строк матрицы, то есть в порядке номеров долей.
+/// # Пример
+/// ```
+/// use hadamard_sss::{design, HadamardMatrix};
+/// use ndarray::arr2;
+///
+/// let h_mtx = HadamardMatrix::from(&arr2(&[[1, 1, 1, 1],
+///                                         [1, -1, 1, -1],
+///                                         [1, 1, -1, -1],
+///                                         [1, -1, -1, 1]])).unwrap();
+/// assert_eq!(design::to_gap(h_mtx.incidence()), "BlockDesign(3, [[2], [1], [3]]);");
+/// ```
+pub fn to_gap(incidence: ArrayView2<'_, i32>) -> String {
+    let blocks: Vec<String> = incidence
+        .rows()
+        .into_iter()
+        .map(|row| {
+            let points: Vec<String> = row
+                .iter()
+                .enumerate()
+                .filter(|&(_, &x)| x == 1)
+                .map(|(j, _)| (j + 1).to_string())
+                .collect();
+            format!("[{}]", points.join(", "))
+        })
+        .collect();
+    format!("BlockDesign({}, [{}]);", incidence.shape()[1], blocks.join(", "))
+}
+
+/// Чтение матрицы инцидентности из записи `BlockDesign(v, blocks)`, полученной
+/// [to_gap] или набранной вручную. Пробелы, переводы строк и завершающая `;` допускаются.
+/// Возвращается ошибка, если запись не разбирается, точка блока лежит вне 1..=v или число
+/// точек либо блоков больше [MAX_GAP_SIZE].
+pub fn from_gap(text: &str) -> Result<Array2<i32>, &'static str> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let body = compact
+        .trim_end_matches(';')
+        .strip_prefix("BlockDesign(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .ok_or("expected BlockDesign(v, blocks)")?;
+    let (v, blocks) = body.split_once(',').ok_or("expected BlockDesign(v, blocks)")?;
+    let v: usize = v.parse().map_err(|_| "number of points is not a number")?;
+    if v > MAX_GAP_SIZE {
+        return Err("too many points in the design");
+    }
+    let blocks = blocks
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or("blocks must be a list")?;
+
+    let mut rows: Vec<Vec<usize>> = Vec::new();
+    let mut rest = blocks;
+    while !rest.is_empty() {
+        let block = rest.strip_prefix('[').ok_or("block must be a list")?;
+        let end = block.find(']').ok_or("unterminated block")?;
+        let points = block[..end]
+            .split(',')
+            .filter(|p| !p.is_empty())
+            .map(|p| match p.parse::<usize>() {
+                Ok(point) if (1..=v).contains(&point) => Ok(point - 1),
+                _ => Err("block contains an invalid point"),
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        if rows.len() == MAX_GAP_SIZE {
+            return Err("too many blocks in the design");
+        }
+        rows.push(points);
+        rest = &block[end + 1..];
+        if !rest.is_empty() {
+            rest = rest.strip_prefix(',').ok_or("blocks must be separated by commas")?;
+        }
+    }
+
+    let mut incidence = Array2::zeros((rows.len(), v));
+    for (i, points) in rows.iter().enumerate() {
+        for &j in points {
+            incidence[[i, j]] = 1;
+        }
+    }
+    Ok(incidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equivalence::all_classes;
+    use ndarray::arr2;
+
+    #[test]
+    fn test_gap_roundtrip() {
+        for mtx in all_classes() {
+            let text = to_gap(mtx.incidence());
+            assert_eq!(from_gap(&text).unwrap(), mtx.incidence());
+        }
+    }
+
+    #[test]
+    fn test_gap_format() {
+        let text = "BlockDesign( 4,\n  [ [ 1, 2 ], [ 3 ], [ ] ] )";
+        assert_eq!(from_gap(text).unwrap(), arr2(&[[1, 1, 0, 0],
+                                                   [0, 0, 1, 0],
+                                                   [0, 0, 0, 0]]));
+        assert_eq!(to_gap(arr2(&[[1, 1, 0, 0], [0, 0, 1, 0]]).view()), "BlockDesign(4, [[1, 2], [3]]);");
+        assert!(from_gap("BlockDesign(3, [[1, 4]]);").is_err());
+        assert!(from_gap("BlockDesign(3, [[0]]);").is_err());
+        assert!(from_gap("Design(3, [[1]]);").is_err());
+        assert!(from_gap("BlockDesign(3, [[1] [2]]);").is_err());
+        assert_eq!(from_gap("BlockDesign(18446744073709551615, [[1]]);").err(), Some("too many points in the design"));
+        let blocks = vec!["[1]"; MAX_GAP_SIZE + 1].join(",");
+        assert_eq!(from_gap(&format!("BlockDesign(1, [{blocks}]);")).err(), Some("too many blocks in the design"));
+        assert!(from_gap(&format!("BlockDesign({MAX_GAP_SIZE}, [[{MAX_GAP_SIZE}]]);")).is_ok());
+    }
+}
@@ -0,0 +1,110 @@
+//! Восстановление с двумя кворумами: секрет и токен одобрения.
+//!
+//! Вместе с секретом дилер разделяет случайный 128-битный токен одобрения на отдельном
+//! экземпляре схемы (например, между сотрудниками службы безопасности, возможно с другим
+//! порогом) и публикует обязательство SHA-256 токена. [DualQuorum::reconstruct_dual]
+//! возвращает секрет, только если кворум одобрения восстановил именно этот токен
+//! и кворум операторов набрал порог своей схемы. Токен длиннее секрета, чтобы
+//! обязательство нельзя было обратить перебором и подделать доли одобрения.
+use crate::scheme_impl::{ChunkedPart, Part};
+use crate::{HadamardSSS, SharingScheme};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// Число слов u32 в токене одобрения.
+const TOKEN_WORDS: usize = 4;
+
+/// Обязательство токена одобрения.
+fn commitment(token: &[u32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"hadamard_sss approval");
+    for word in token {
+        hasher.update(word.to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Доли, выданные при разделении с двумя кворумами.
+pub struct DualShares {
+    /// Доли секрета для операторов.
+    pub secret_parts: Vec<Part>,
+    /// Доли токена одобрения.
+    pub approval_parts: Vec<ChunkedPart>,
+    /// Обязательство токена; хранится открыто вместе с параметрами схем.
+    pub commitment: [u8; 32],
+}
+
+/// Пара схем: для секрета и для токена одобрения.
+pub struct DualQuorum {
+    /// Схема операторов.
+    secret: HadamardSSS,
+    /// Схема одобряющих.
+    approval: HadamardSSS,
+}
+
+/// Реализация методов двойного кворума.
+impl DualQuorum {
+    /// Создание по схемам операторов и одобряющих.
+    pub fn new(secret: HadamardSSS, approval: HadamardSSS) -> Self {
+        DualQuorum { secret, approval }
+    }
+
+    /// Схема операторов.
+    pub fn secret_scheme(&self) -> &HadamardSSS {
+        &self.secret
+    }
+
+    /// Схема одобряющих.
+    pub fn approval_scheme(&self) -> &HadamardSSS {
+        &self.approval
+    }
+
+    /// Разделение секрета и нового случайного токена одобрения.
+    pub fn share(&self, secret: u32) -> Result<DualShares, &'static str> {
+        let mut rng = crate::entropy::rng();
+        let token: Vec<u32> = (0..TOKEN_WORDS).map(|_| rng.next_u32()).collect();
+        Ok(DualShares {
+            secret_parts: self.secret.share(secret)?,
+            approval_parts: self.approval.share_words(&token)?,
+            commitment: commitment(&token),
+        })
+    }
+
+    /// Восстановление секрета при наличии обоих кворумов.
+    pub fn reconstruct_dual(&self, secret_parts: Vec<Part>, approval_parts: Vec<ChunkedPart>, expected: &[u8; 32]) -> Result<u32, &'static str> {
+        let token = self.approval.reconstruct_words(approval_parts).map_err(|_| "approval quorum not met")?;
+        if token.len() != TOKEN_WORDS || !bool::from(commitment(&token).ct_eq(expected)) {
+            return Err("approval quorum not met");
+        }
+        self.secret.reconstruct(secret_parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equivalence::classes;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_both_quorums_required() {
+        let operators = HadamardSSS::from_matrix(&classes(16).unwrap().next().unwrap()).unwrap();
+        let security = HadamardSSS::from(&matrix()).unwrap();
+        let dual = DualQuorum::new(operators, security);
+        let shares = dual.share(314159265).unwrap();
+        assert_eq!(shares.secret_parts.len(), 15);
+        assert_eq!(shares.approval_parts.len(), 7);
+
+        let secret = |k: usize| shares.secret_parts[0..k].to_vec();
+        let approval = |k: usize| shares.approval_parts[0..k].to_vec();
+        assert_eq!(dual.reconstruct_dual(secret(9), approval(5), &shares.commitment), Ok(314159265));
+        assert_eq!(dual.reconstruct_dual(secret(9), approval(4), &shares.commitment), Err("approval quorum not met"));
+        assert_eq!(dual.reconstruct_dual(secret(8), approval(5), &shares.commitment), Err("less than threshold parties"));
+
+        let other = dual.share(314159265).unwrap();
+        assert_eq!(dual.reconstruct_dual(secret(9), other.approval_parts[0..5].to_vec(), &shares.commitment), Err("approval quorum not met"));
+        let forged: Vec<ChunkedPart> = (0..5).map(|i| ChunkedPart::from(i, vec![0; TOKEN_WORDS])).collect();
+        assert_eq!(dual.reconstruct_dual(secret(9), forged, &shares.commitment), Err("approval quorum not met"));
+    }
+}
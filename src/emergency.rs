@@ -0,0 +1,178 @@
+//! Аварийное восстановление с пониженным порогом.
+//!
+//! Для политик, где от восстановления секрета зависит безопасность людей, можно задать
+//! аварийный порог ниже обычного. Он действует только в [HadamardSSS::reconstruct_emergency]
+//! и только при зарегистрированном обработчике аудита. Каждая попытка порождает событие
+//! [EmergencyEvent], которое связано с предыдущим цепочкой SHA-256: удаление, изменение
+//! или перестановка событий в журнале обнаруживаются функцией [verify_chain].
+//!
+//! Коалиция меньше обычного порога знает не все биты секрета, поэтому аварийное
+//! восстановление удаётся, только если строки предъявленных долей покрывают все биты.
+use crate::scheme_impl::Part;
+use crate::{HadamardSSS, SharingScheme};
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Обработчик аудита, получающий события аварийного восстановления.
+pub type AuditSink = Box<dyn Fn(&EmergencyEvent) + Send + Sync>;
+
+/// Событие аварийного восстановления.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmergencyEvent {
+    /// Порядковый номер события, начиная с 0.
+    pub sequence: u64,
+    /// Время попытки в секундах от начала эпохи UNIX.
+    pub timestamp: u64,
+    /// Номера предъявленных долей.
+    pub parts: Vec<usize>,
+    /// Дайджест предыдущего события (нули для первого).
+    pub previous: [u8; 32],
+    /// Дайджест события.
+    pub digest: [u8; 32],
+}
+
+/// Вычисление дайджеста события по его полям.
+fn event_digest(sequence: u64, timestamp: u64, parts: &[usize], previous: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"hadamard_sss emergency");
+    hasher.update(previous);
+    hasher.update(sequence.to_le_bytes());
+    hasher.update(timestamp.to_le_bytes());
+    hasher.update((parts.len() as u64).to_le_bytes());
+    for &number in parts {
+        hasher.update((number as u64).to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Проверка журнала событий: номера идут подряд с нуля, каждое событие ссылается
+/// на дайджест предыдущего и его собственный дайджест соответствует полям.
+pub fn verify_chain(events: &[EmergencyEvent]) -> bool {
+    let mut previous = [0u8; 32];
+    for (sequence, event) in events.iter().enumerate() {
+        if event.sequence != sequence as u64
+            || event.previous != previous
+            || event.digest != event_digest(event.sequence, event.timestamp, &event.parts, &event.previous)
+        {
+            return false;
+        }
+        previous = event.digest;
+    }
+    true
+}
+
+/// Настройки аварийного режима схемы.
+pub(crate) struct Emergency {
+    /// Аварийный порог.
+    threshold: usize,
+    /// Обработчик аудита.
+    sink: AuditSink,
+    /// Номер и дайджест последнего события.
+    last: Mutex<(u64, [u8; 32])>,
+}
+
+/// Аварийное восстановление.
+impl HadamardSSS {
+    /// Включение аварийного режима с данным порогом, который должен быть положительным
+    /// и меньше обычного. Все попытки аварийного восстановления передаются sink.
+    pub fn set_emergency_threshold(&mut self, threshold: usize, sink: impl Fn(&EmergencyEvent) + Send + Sync + 'static) -> Result<(), &'static str> {
+        if threshold == 0 || threshold >= self.threshold {
+            return Err("emergency threshold must be positive and below the threshold");
+        }
+        self.emergency = Some(Emergency { threshold, sink: Box::new(sink), last: Mutex::new((0, [0; 32])) });
+        Ok(())
+    }
+
+    /// Восстановление секрета по аварийному порогу. Событие аудита передаётся обработчику
+    /// до восстановления, в том числе для неудачных попыток. Возвращается ошибка, если
+    /// аварийный режим не включён, долей меньше аварийного порога или они покрывают не все биты.
+    pub fn reconstruct_emergency(&self, parts: Vec<Part>) -> Result<u32, &'static str> {
+        let emergency = self.emergency.as_ref().ok_or("emergency mode is not configured")?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| "system clock is before the UNIX epoch")?
+            .as_secs();
+        let numbers: Vec<usize> = parts.iter().map(|part| part.number()).collect();
+        {
+            let mut last = emergency.last.lock().map_err(|_| "audit chain is poisoned")?;
+            let (sequence, previous) = *last;
+            let digest = event_digest(sequence, timestamp, &numbers, &previous);
+            (emergency.sink)(&EmergencyEvent { sequence, timestamp, parts: numbers, previous, digest });
+            *last = (sequence + 1, digest);
+        }
+
+        self.check_canaries(&parts);
+        if parts.len() < emergency.threshold {
+            return Err("less than emergency threshold parties");
+        }
+        let n = self.hss.mtx_len();
+        let covered = parts
+            .iter()
+            .filter(|part| part.number() < n)
+            .fold(0, |mask, part| mask | self.hss.mask(part.number()));
+        if covered != u32::MAX {
+            return Err("parts do not cover every bit of the secret");
+        }
+        self.hss.reconstruct(parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Combinations;
+    use crate::fixtures::matrix;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_emergency_reconstruction() {
+        let mut hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        assert_eq!(hsss.reconstruct_emergency(parts[0..5].to_vec()), Err("emergency mode is not configured"));
+        assert!(hsss.set_emergency_threshold(5, |_| ()).is_err());
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        hsss.set_emergency_threshold(4, move |event| sink.lock().unwrap().push(event.clone())).unwrap();
+        assert_eq!(hsss.reconstruct(parts[0..4].to_vec()), Err("less than threshold parties"));
+        assert!(log.lock().unwrap().is_empty());
+
+        let covering = Combinations::new(7, 4)
+            .find(|set| set.iter().fold(0, |m, &i| m | hsss.hss.mask(i)) == u32::MAX)
+            .unwrap();
+        let presented: Vec<Part> = covering.iter().map(|&i| parts[i]).collect();
+        assert_eq!(hsss.reconstruct_emergency(presented).unwrap(), 314159265);
+        assert_eq!(hsss.reconstruct_emergency(parts[0..3].to_vec()), Err("less than emergency threshold parties"));
+
+        let events = log.lock().unwrap().clone();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].parts, covering);
+        assert!(verify_chain(&events));
+    }
+
+    #[test]
+    fn test_chain_tampering_detected() {
+        let mut hsss = HadamardSSS::from(&matrix()).unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&log);
+        hsss.set_emergency_threshold(4, move |event| sink.lock().unwrap().push(event.clone())).unwrap();
+        let parts = hsss.share(7).unwrap();
+        for start in 0..3 {
+            let _ = hsss.reconstruct_emergency(parts[start..start + 4].to_vec());
+        }
+        let events = log.lock().unwrap().clone();
+        assert!(verify_chain(&events));
+
+        let mut edited = events.clone();
+        edited[1].parts = vec![0, 1, 2, 3];
+        assert!(!verify_chain(&edited));
+        let mut removed = events.clone();
+        removed.remove(1);
+        assert!(!verify_chain(&removed));
+        let mut rehashed = events;
+        rehashed[1].parts = vec![0, 1, 2, 3];
+        rehashed[1].digest = event_digest(1, rehashed[1].timestamp, &rehashed[1].parts, &rehashed[1].previous);
+        assert!(!verify_chain(&rehashed));
+    }
+}
@@ -0,0 +1,116 @@
+//! Шифрование хранимых долей алгоритмом ChaCha20-Poly1305.
+//!
+//! Номер доли остаётся открытым, чтобы хранилище могло индексировать доли, но входит в
+//! аутентифицированные данные: подменить номер незаметно нельзя. Одноразовое значение (nonce)
+//! генерируется случайно при каждом шифровании и хранится вместе с шифротекстом.
+//! Шифруется значение доли, а если у доли есть отпечаток схемы, то и он.
+use crate::keys::{ChaChaKey, SymmetricKey};
+use crate::scheme_impl::Part;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+
+/// Префикс аутентифицированных данных.
+const AAD_PREFIX: &[u8] = b"hadamard_sss share";
+
+/// Зашифрованная доля.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptedShare {
+    /// Номер доли.
+    number: usize,
+    /// Одноразовое значение, использованное при шифровании.
+    nonce: [u8; 12],
+    /// Шифротекст значения доли вместе с тегом аутентификации.
+    ciphertext: Vec<u8>,
+}
+
+/// Составление аутентифицированных данных для доли с данным номером.
+fn aad(number: usize) -> Vec<u8> {
+    [AAD_PREFIX, &(number as u64).to_le_bytes()].concat()
+}
+
+/// Реализация методов зашифрованной доли.
+impl EncryptedShare {
+    /// Шифрование доли на данном ключе.
+    pub fn seal(part: &Part, key: &ChaChaKey) -> Result<Self, &'static str> {
+        let mut nonce = [0u8; 12];
+        crate::entropy::rng().fill_bytes(&mut nonce);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_bytes()));
+        let mut msg = part.data().to_le_bytes().to_vec();
+        if let Some(fingerprint) = part.fingerprint() {
+            msg.extend_from_slice(&fingerprint.to_le_bytes());
+        }
+        let payload = Payload { msg: &msg, aad: &aad(part.number()) };
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| "encryption failed")?;
+        Ok(EncryptedShare { number: part.number(), nonce, ciphertext })
+    }
+
+    /// Расшифрование доли. Возвращается ошибка, если ключ неверен или данные были изменены.
+    pub fn open(&self, key: &ChaChaKey) -> Result<Part, &'static str> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key.as_bytes()));
+        let payload = Payload { msg: &self.ciphertext, aad: &aad(self.number) };
+        let data = cipher
+            .decrypt(Nonce::from_slice(&self.nonce), payload)
+            .map_err(|_| "share authentication failed")?;
+        let word = |offset: usize| u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+        match data.len() {
+            4 => Ok(Part::from(self.number, word(0))),
+            8 => Ok(Part::from(self.number, word(0)).with_fingerprint(word(4))),
+            _ => Err("wrong share length"),
+        }
+    }
+
+    /// Создание экземпляра структуры по ранее сохранённым полям.
+    pub fn from(number: usize, nonce: [u8; 12], ciphertext: Vec<u8>) -> Self {
+        EncryptedShare { number, nonce, ciphertext }
+    }
+
+    /// Возвращение значения поля number.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Возвращение значения поля nonce.
+    pub fn nonce(&self) -> [u8; 12] {
+        self.nonce
+    }
+
+    /// Возвращение значения поля ciphertext.
+    pub fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open() {
+        let key = ChaChaKey::from([5u8; 32]);
+        let part = Part::from(3, 314159265).with_fingerprint(0xF00D);
+        let sealed = EncryptedShare::seal(&part, &key).unwrap();
+        assert_eq!(sealed.number(), 3);
+        let opened = sealed.open(&key).unwrap();
+        assert_eq!((opened.number(), opened.data(), opened.fingerprint()), (3, 314159265, Some(0xF00D)));
+        let legacy = EncryptedShare::seal(&Part::from(3, 7), &key).unwrap().open(&key).unwrap();
+        assert_eq!((legacy.data(), legacy.fingerprint()), (7, None));
+        assert_ne!(EncryptedShare::seal(&part, &key).unwrap().nonce(), sealed.nonce());
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        let key = ChaChaKey::from([5u8; 32]);
+        let sealed = EncryptedShare::seal(&Part::from(3, 42), &key).unwrap();
+        assert!(sealed.open(&ChaChaKey::from([6u8; 32])).is_err());
+
+        let renumbered = EncryptedShare::from(4, sealed.nonce(), sealed.ciphertext().to_vec());
+        assert!(renumbered.open(&key).is_err());
+
+        let mut ciphertext = sealed.ciphertext().to_vec();
+        ciphertext[0] ^= 1;
+        assert!(EncryptedShare::from(3, sealed.nonce(), ciphertext).open(&key).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+//! Источник случайности схемы.
+//!
+//! По умолчанию используется `rand::thread_rng`. С функцией `getrandom` случайные байты берутся
+//! напрямую из системного источника через крейт getrandom; с функцией `rdrand` к ним
+//! дополнительно примешивается (сложением по модулю 2) вывод инструкции RDRAND, если
+//! процессор её поддерживает. Смешивание не ослабляет системный источник, даже если RDRAND
+//! не заслуживает доверия. Используемый источник сообщается функцией [backend] и отчётом
+//! [crate::analysis::SecrecyReport].
+use rand::{CryptoRng, RngCore};
+use std::fmt;
+
+/// Источник случайности.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RngBackend {
+    /// `rand::thread_rng`: ChaCha12, периодически переинициализируемый из ОС.
+    ThreadRng,
+    /// Системный источник через getrandom.
+    Getrandom,
+    /// Системный источник через getrandom со смешиванием RDRAND.
+    GetrandomRdrand,
+}
+
+impl fmt::Display for RngBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RngBackend::ThreadRng => write!(f, "thread_rng"),
+            RngBackend::Getrandom => write!(f, "getrandom"),
+            RngBackend::GetrandomRdrand => write!(f, "getrandom + rdrand"),
+        }
+    }
+}
+
+/// Источник, которым пользуется схема в данной сборке и на данном процессоре.
+pub fn backend() -> RngBackend {
+    if !cfg!(feature = "getrandom") {
+        RngBackend::ThreadRng
+    } else if rdrand_available() {
+        RngBackend::GetrandomRdrand
+    } else {
+        RngBackend::Getrandom
+    }
+}
+
+/// Поддерживает ли процессор RDRAND (и включена ли функция rdrand).
+fn rdrand_available() -> bool {
+    #[cfg(all(feature = "rdrand", target_arch = "x86_64"))]
+    {
+        std::is_x86_feature_detected!("rdrand")
+    }
+    #[cfg(not(all(feature = "rdrand", target_arch = "x86_64")))]
+    {
+        false
+    }
+}
+
+/// Одно слово RDRAND; инструкция может временно не выдавать данные, поэтому делается несколько попыток.
+#[cfg(all(feature = "rdrand", target_arch = "x86_64"))]
+#[target_feature(enable = "rdrand")]
+unsafe fn rdrand64() -> Option<u64> {
+    let mut word = 0;
+    (0..10).any(|_| std::arch::x86_64::_rdrand64_step(&mut word) == 1).then_some(word)
+}
+
+/// Примешивание вывода RDRAND к буферу.
+#[cfg(all(feature = "rdrand", target_arch = "x86_64"))]
+fn mix_rdrand(dest: &mut [u8]) -> Result<(), rand::Error> {
+    for chunk in dest.chunks_mut(8) {
+        // SAFETY: вызывается только при подтверждённой поддержке RDRAND
+        let word = unsafe { rdrand64() }.ok_or_else(|| rand::Error::new(std::io::Error::other("RDRAND did not return data")))?;
+        for (byte, extra) in chunk.iter_mut().zip(word.to_le_bytes()) {
+            *byte ^= extra;
+        }
+    }
+    Ok(())
+}
+
+/// Генератор, используемый схемой.
+pub(crate) struct SchemeRng;
+
+/// Получение генератора схемы.
+pub(crate) fn rng() -> SchemeRng {
+    SchemeRng
+}
+
+impl RngCore for SchemeRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.try_fill_bytes(dest).expect("entropy source failed")
+    }
+
+    #[cfg(not(feature = "getrandom"))]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        rand::thread_rng().try_fill_bytes(dest)
+    }
+
+    #[cfg(feature = "getrandom")]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        getrandom::getrandom(dest).map_err(rand::Error::from)?;
+        #[cfg(all(feature = "rdrand", target_arch = "x86_64"))]
+        if rdrand_available() {
+            mix_rdrand(dest)?;
+        }
+        Ok(())
+    }
+}
+
+impl CryptoRng for SchemeRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_matches_features() {
+        let backend = backend();
+        assert_eq!(backend == RngBackend::ThreadRng, !cfg!(feature = "getrandom"));
+        if !cfg!(feature = "rdrand") {
+            assert_ne!(backend, RngBackend::GetrandomRdrand);
+        }
+        let mut buf = [0u8; 64];
+        rng().fill_bytes(&mut buf);
+        // вероятность 64 нулевых байтов пренебрежимо мала
+        assert!(buf.iter().any(|&b| b != 0));
+        assert_ne!(rng().next_u64(), rng().next_u64());
+    }
+}
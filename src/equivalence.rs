@@ -0,0 +1,222 @@
+//! Классы эквивалентности матриц Адамара малых порядков.
+//!
+//! Две матрицы Адамара эквивалентны, если одна получается из другой перестановками строк
+//! и столбцов и умножением строк и столбцов на -1. Для порядков не больше [MAX_ORDER]
+//! число классов известно: по одному для порядков 1, 2, 4, 8 и 12, пять для порядка 16
+//! и три для порядка 20. Модуль хранит по нормализованному представителю каждого класса,
+//! а [EquivalenceTransform] позволяет получать из него другие матрицы того же класса.
+//! Это позволяет систематически изучать, как выбор матрицы влияет на схему.
+use crate::hadamard_matrix::HadamardMatrix;
+use ndarray::Array2;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Наибольший порядок, для которого известны представители всех классов.
+pub const MAX_ORDER: usize = 20;
+
+/// Представители классов: строки нормализованных матриц, бит j строки равен 1,
+/// если в j-м столбце стоит 1, и 0, если -1.
+const CLASSES: &[(usize, &[&[u32]])] = &[
+    (1, &[&[0x1]]),
+    (2, &[&[0x3, 0x1]]),
+    (4, &[&[0xf, 0x5, 0x3, 0x9]]),
+    (8, &[&[0xff, 0x55, 0x33, 0x99, 0x0f, 0xa5, 0xc3, 0x69]]),
+    // конструкция Пэли I, q = 11
+    (12, &[&[0xfff, 0xb89, 0x713, 0xe25, 0xc4b, 0x897, 0x12f, 0x25d, 0x4b9, 0x971, 0x2e3, 0x5c5]]),
+    // матрица Сильвестра; третий и четвёртый классы транспонированы друг к другу
+    (16, &[
+        &[0xffff, 0x5555, 0x3333, 0x9999, 0x0f0f, 0xa5a5, 0xc3c3, 0x6969,
+          0x00ff, 0xaa55, 0xcc33, 0x6699, 0xf00f, 0x5aa5, 0x3cc3, 0x9669],
+        &[0xffff, 0xe525, 0x1f45, 0x4997, 0x6e0b, 0x23e3, 0x8c73, 0x3939,
+          0x506f, 0xa8cd, 0xc359, 0x74d1, 0x06bd, 0xb217, 0xdaa1, 0x958b],
+        &[0xffff, 0x06eb, 0xe781, 0xe079, 0x8d65, 0x8b1b, 0x21b7, 0x39c9,
+          0x92d5, 0xb40f, 0x3e31, 0x6a47, 0x4c9d, 0x532d, 0xd8a3, 0x5553],
+        &[0xffff, 0xca63, 0x3b51, 0x32ab, 0x9569, 0x645b, 0x899b, 0x51c7,
+          0xa0f5, 0x2d27, 0x9617, 0x5cb1, 0xe781, 0x0ecd, 0xf80d, 0x433d],
+        &[0xffff, 0xe12b, 0x7247, 0x133d, 0x0f63, 0xd471, 0x6e19, 0x59c9,
+          0x4597, 0x24ed, 0xb781, 0xa955, 0x82db, 0xcaa5, 0x9c0f, 0x38b3],
+    ]),
+    // конструкции Пэли I (q = 19) и Пэли II (q = 9), затем третий класс
+    (20, &[
+        &[0xfffff, 0x9ea19, 0x3d433, 0x7a865, 0xf50c9, 0xea193, 0xd4327, 0xa864f, 0x50c9f, 0xa193d,
+          0x4327b, 0x864f5, 0x0c9eb, 0x193d5, 0x327a9, 0x64f51, 0xc9ea1, 0x93d43, 0x27a87, 0x4f50d],
+        &[0xfffff, 0xaaaa9, 0x0c3f5, 0xa6953, 0x30cdd, 0x9a647, 0xc307d, 0x69a17, 0x0fd0d, 0xa54a7,
+          0x33731, 0x9919b, 0xc1fc1, 0x6856b, 0xf430d, 0x529a7, 0xdcc31, 0x4669b, 0x7f0c1, 0x15a6b],
+        &[0xfffff, 0x28aeb, 0x8d2b5, 0x4ee13, 0xc36c9, 0x59467, 0x90f71, 0xe45a3, 0xcc94d, 0x2a5d5,
+          0x758d1, 0x63b25, 0xfa039, 0x9b983, 0xb6247, 0x3d709, 0x5039f, 0x0717b, 0x16cad, 0xa1c1f],
+    ]),
+];
+
+/// Итератор по представителям классов эквивалентности одного порядка.
+pub struct Classes {
+    /// Порядок матриц.
+    order: usize,
+    /// Оставшиеся представители.
+    rest: std::slice::Iter<'static, &'static [u32]>,
+}
+
+impl Iterator for Classes {
+    type Item = HadamardMatrix;
+
+    fn next(&mut self) -> Option<HadamardMatrix> {
+        let rows = self.rest.next()?;
+        let n = self.order;
+        Some(HadamardMatrix::wrap(Array2::from_shape_fn((n, n), |(i, j)| {
+            if rows[i] >> j & 1 == 1 { 1 } else { -1 }
+        })))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rest.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Classes {}
+
+/// Представители всех классов эквивалентности матриц Адамара данного порядка.
+/// Для порядков, при которых матриц Адамара не существует, итератор пуст.
+/// Для порядков больше [MAX_ORDER] возвращается ошибка.
+pub fn classes(order: usize) -> Result<Classes, &'static str> {
+    if order > MAX_ORDER {
+        return Err("no class data for that order");
+    }
+    let rest = CLASSES
+        .iter()
+        .find(|(n, _)| *n == order)
+        .map_or(&[][..], |(_, reps)| *reps)
+        .iter();
+    Ok(Classes { order, rest })
+}
+
+/// Представители всех классов эквивалентности всех порядков до [MAX_ORDER] включительно,
+/// по возрастанию порядка.
+pub fn all_classes() -> impl Iterator<Item = HadamardMatrix> {
+    (1..=MAX_ORDER).flat_map(|order| classes(order).expect("order does not exceed MAX_ORDER"))
+}
+
+/// Преобразование эквивалентности: перестановки строк и столбцов и смена их знаков.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EquivalenceTransform {
+    /// i-я строка результата берётся из строки rows[i].
+    rows: Vec<usize>,
+    /// j-й столбец результата берётся из столбца columns[j].
+    columns: Vec<usize>,
+    /// Знаки строк результата.
+    row_signs: Vec<i8>,
+    /// Знаки столбцов результата.
+    column_signs: Vec<i8>,
+}
+
+/// Реализация методов преобразования.
+impl EquivalenceTransform {
+    /// Создание преобразования. Возвращается ошибка, если rows или columns не являются
+    /// перестановками, знаки отличны от ±1 или длины не совпадают.
+    pub fn new(rows: Vec<usize>, columns: Vec<usize>, row_signs: Vec<i8>, column_signs: Vec<i8>) -> Result<Self, &'static str> {
+        let n = rows.len();
+        if columns.len() != n || row_signs.len() != n || column_signs.len() != n {
+            return Err("transform parts have different lengths");
+        }
+        if !is_permutation(&rows) || !is_permutation(&columns) {
+            return Err("rows and columns must be permutations");
+        }
+        if row_signs.iter().chain(&column_signs).any(|&s| s != 1 && s != -1) {
+            return Err("signs must be 1 or -1");
+        }
+        Ok(EquivalenceTransform { rows, columns, row_signs, column_signs })
+    }
+
+    /// Тождественное преобразование данного порядка.
+    pub fn identity(order: usize) -> Self {
+        EquivalenceTransform {
+            rows: (0..order).collect(),
+            columns: (0..order).collect(),
+            row_signs: vec![1; order],
+            column_signs: vec![1; order],
+        }
+    }
+
+    /// Случайное преобразование данного порядка.
+    pub fn random<R: Rng + ?Sized>(order: usize, rng: &mut R) -> Self {
+        let mut res = EquivalenceTransform::identity(order);
+        res.rows.shuffle(rng);
+        res.columns.shuffle(rng);
+        for s in res.row_signs.iter_mut().chain(res.column_signs.iter_mut()) {
+            if rng.gen() {
+                *s = -1;
+            }
+        }
+        res
+    }
+
+    /// Порядок матриц, к которым применимо преобразование.
+    pub fn order(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Применение преобразования к матрице. Результат эквивалентен исходной матрице
+    /// и, как правило, не нормализован. Возвращается ошибка, если порядки не совпадают.
+    pub fn apply(&self, mtx: &HadamardMatrix) -> Result<HadamardMatrix, &'static str> {
+        if mtx.order() != self.order() {
+            return Err("transform order differs from matrix order");
+        }
+        let view = mtx.view();
+        Ok(HadamardMatrix::wrap(Array2::from_shape_fn((self.order(), self.order()), |(i, j)| {
+            view[[self.rows[i], self.columns[j]]] * self.row_signs[i] * self.column_signs[j]
+        })))
+    }
+}
+
+/// Проверка, является ли список перестановкой чисел 0..len.
+fn is_permutation(values: &[usize]) -> bool {
+    let mut seen = vec![false; values.len()];
+    values.iter().all(|&v| v < seen.len() && !std::mem::replace(&mut seen[v], true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    /// Проверка H * H.T = nI.
+    fn is_hadamard(mtx: &HadamardMatrix) -> bool {
+        HadamardMatrix::from(&mtx.to_i32()).is_ok()
+    }
+
+    #[test]
+    fn test_class_counts() {
+        let counts: Vec<(usize, usize)> = (1..=MAX_ORDER)
+            .map(|order| (order, classes(order).unwrap().len()))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        assert_eq!(counts, vec![(1, 1), (2, 1), (4, 1), (8, 1), (12, 1), (16, 5), (20, 3)]);
+        assert_eq!(all_classes().count(), 13);
+        assert!(classes(24).is_err());
+    }
+
+    #[test]
+    fn test_representatives() {
+        for mtx in all_classes() {
+            assert!(is_hadamard(&mtx));
+            assert!(mtx.is_normalized());
+        }
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut rng = ChaCha20Rng::seed_from_u64(941);
+        for mtx in classes(12).unwrap().chain(classes(20).unwrap()) {
+            let transform = EquivalenceTransform::random(mtx.order(), &mut rng);
+            let res = transform.apply(&mtx).unwrap();
+            assert!(is_hadamard(&res));
+            assert_eq!(EquivalenceTransform::identity(mtx.order()).apply(&mtx).unwrap().view(), mtx.view());
+        }
+        let mtx = classes(8).unwrap().next().unwrap();
+        assert!(EquivalenceTransform::identity(4).apply(&mtx).is_err());
+        assert!(EquivalenceTransform::new(vec![0, 0], vec![0, 1], vec![1, 1], vec![1, 1]).is_err());
+        assert!(EquivalenceTransform::new(vec![1, 0], vec![0, 1], vec![1, 2], vec![1, 1]).is_err());
+        let swap = EquivalenceTransform::new(vec![1, 0], vec![0, 1], vec![1, -1], vec![1, 1]).unwrap();
+        let h2 = classes(2).unwrap().next().unwrap();
+        assert_eq!(swap.apply(&h2).unwrap().to_i32(), ndarray::arr2(&[[1, -1], [-1, -1]]));
+    }
+}
@@ -0,0 +1,141 @@
+//! Модуль предварительного расширения секрета для секретов с низкой энтропией.
+//!
+//! Каждая доля раскрывает часть битов секрета, поэтому для коротких или предсказуемых
+//! секретов (например, PIN-кодов) коалиция, не достигшая порога, может перебрать оставшиеся биты.
+//! Перед разделением секрет пропускается через обратимую перестановку (сеть Фейстеля),
+//! раундовые функции которой получены с помощью HKDF-SHA256 от случайной соли.
+//! Без соли известные коалиции биты разделённого значения не привязаны к конкретным битам секрета.
+//!
+//! Соль и проверочный тег хранятся у дилера (как и матрица Адамара) и не должны выдаваться участникам:
+//! зная их, можно перебрать секрет по тегу.
+use crate::scheme_impl::Part;
+use crate::{HadamardSSS, SharingScheme};
+use hkdf::Hkdf;
+use rand::RngCore;
+use sha2::Sha256;
+
+/// Число раундов сети Фейстеля.
+const ROUNDS: u8 = 8;
+
+/// Параметры расширения, которые необходимо сохранить для восстановления секрета.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Expansion {
+    /// Случайная соль, из которой выводятся раундовые функции.
+    salt: [u8; 16],
+    /// Проверочный тег исходного секрета.
+    tag: [u8; 16],
+}
+
+/// Реализация методов структуры параметров расширения.
+impl Expansion {
+    /// Создание экземпляра структуры по ранее сохранённым соли и тегу.
+    pub fn from(salt: [u8; 16], tag: [u8; 16]) -> Self {
+        Expansion { salt, tag }
+    }
+
+    /// Возвращение значения поля salt.
+    pub fn salt(&self) -> [u8; 16] {
+        self.salt
+    }
+
+    /// Возвращение значения поля tag.
+    pub fn tag(&self) -> [u8; 16] {
+        self.tag
+    }
+
+    /// Получение параметров расширения для данного секрета со случайной солью.
+    fn generate(secret: u32) -> Self {
+        let mut salt = [0u8; 16];
+        crate::entropy::rng().fill_bytes(&mut salt);
+        Expansion { salt, tag: Expansion::compute_tag(&salt, secret) }
+    }
+
+    /// Вычисление проверочного тега секрета.
+    fn compute_tag(salt: &[u8; 16], secret: u32) -> [u8; 16] {
+        let mut tag = [0u8; 16];
+        Hkdf::<Sha256>::new(Some(salt), &secret.to_le_bytes())
+            .expand(b"hadamard_sss expansion tag", &mut tag)
+            .expect("16 bytes is a valid HKDF output length");
+        tag
+    }
+
+    /// Раундовая функция сети Фейстеля.
+    fn round(&self, round: u8, half: u16) -> u16 {
+        let half = half.to_le_bytes();
+        let mut out = [0u8; 2];
+        Hkdf::<Sha256>::new(Some(&self.salt), b"")
+            .expand_multi_info(&[b"hadamard_sss expansion round", &[round], &half], &mut out)
+            .expect("2 bytes is a valid HKDF output length");
+        u16::from_le_bytes(out)
+    }
+
+    /// Прямое преобразование секрета перед разделением.
+    fn forward(&self, secret: u32) -> u32 {
+        let (mut left, mut right) = ((secret >> 16) as u16, secret as u16);
+        for r in 0..ROUNDS {
+            (left, right) = (right, left ^ self.round(r, right));
+        }
+        ((left as u32) << 16) | right as u32
+    }
+
+    /// Обратное преобразование восстановленного значения.
+    fn inverse(&self, value: u32) -> u32 {
+        let (mut left, mut right) = ((value >> 16) as u16, value as u16);
+        for r in (0..ROUNDS).rev() {
+            (left, right) = (right ^ self.round(r, left), left);
+        }
+        ((left as u32) << 16) | right as u32
+    }
+}
+
+/// Разделение и восстановление секрета с предварительным расширением.
+impl HadamardSSS {
+    /// Разделение секрета с предварительным расширением.
+    /// Возвращает доли и параметры расширения, которые нужно сохранить у дилера.
+    pub fn share_expanded(&self, secret: u32) -> Result<(Vec<Part>, Expansion), &'static str> {
+        let expansion = Expansion::generate(secret);
+        Ok((self.share(expansion.forward(secret))?, expansion))
+    }
+
+    /// Восстановление секрета, разделённого с помощью [HadamardSSS::share_expanded].
+    /// В случае несовпадения проверочного тега возвращается ошибка.
+    pub fn reconstruct_expanded(&self, parts: Vec<Part>, expansion: &Expansion) -> Result<u32, &'static str> {
+        let secret = expansion.inverse(self.reconstruct(parts)?);
+        if Expansion::compute_tag(&expansion.salt, secret) == expansion.tag {
+            Ok(secret)
+        } else {
+            Err("expansion tag mismatch")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_feistel_inverse() {
+        let expansion = Expansion::generate(0);
+        for secret in [0, 1, 1234, 314159265, u32::MAX] {
+            assert_eq!(expansion.inverse(expansion.forward(secret)), secret);
+        }
+        assert_ne!(expansion.forward(1234), 1234);
+    }
+
+    #[test]
+    fn test_expanded_roundtrip() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        for pin in [0, 42, 9999] {
+            let (parts, expansion) = hsss.share_expanded(pin).unwrap();
+            assert_eq!(hsss.reconstruct_expanded(parts[0..5].to_vec(), &expansion).unwrap(), pin);
+
+            let mut bad = parts[0..5].to_vec();
+            bad[0] = bad[0].blinded(u32::MAX);
+            assert_eq!(hsss.reconstruct_expanded(bad, &expansion).err(), Some("expansion tag mismatch"));
+
+            let restored = Expansion::from(expansion.salt(), expansion.tag());
+            assert_eq!(hsss.reconstruct_expanded(parts[2..7].to_vec(), &restored).unwrap(), pin);
+        }
+    }
+}
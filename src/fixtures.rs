@@ -0,0 +1,177 @@
+//! Зафиксированные входные и выходные данные эталонной реализации.
+//!
+//! Все наборы получены для матрицы Сильвестра порядка 8 ([MATRIX]) с раскладкой битов
+//! по умолчанию и детерминированным разделением [HadamardSSS::share_seeded].
+//! Альтернативные реализации и переработки кода можно проверить функцией
+//! [run_compatibility_suite], передав ей схему, построенную по [matrix].
+use crate::scheme_impl::Part;
+use crate::SharingScheme;
+#[cfg(doc)]
+use crate::HadamardSSS;
+use ndarray::Array2;
+use std::fmt;
+
+/// Матрица Сильвестра порядка 8, по которой получены все наборы.
+pub const MATRIX: [[i32; 8]; 8] = [[1, 1, 1, 1, 1, 1, 1, 1],
+                                   [1, -1, 1, -1, 1, -1, 1, -1],
+                                   [1, 1, -1, -1, 1, 1, -1, -1],
+                                   [1, -1, -1, 1, 1, -1, -1, 1],
+                                   [1, 1, 1, 1, -1, -1, -1, -1],
+                                   [1, -1, 1, -1, -1, 1, -1, 1],
+                                   [1, 1, -1, -1, -1, -1, 1, 1],
+                                   [1, -1, -1, 1, -1, 1, 1, -1]];
+
+/// Отпечаток схемы, построенной по [MATRIX] с раскладкой по умолчанию; им помечены доли наборов.
+pub const FINGERPRINT: u32 = 0x6BE1_D00A;
+
+/// Маска, которой искажается вторая доля в проверке обнаружения подделки.
+pub const TAMPER_MASK: u32 = 0x0F0F_0F0F;
+
+/// Зафиксированный набор: секрет, зерно и полученные доли.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fixture {
+    /// Разделённый секрет.
+    pub secret: u32,
+    /// Зерно детерминированного разделения.
+    pub seed: [u8; 32],
+    /// Доли в виде пар (номер, значение).
+    pub shares: [(usize, u32); 7],
+    /// Номера подозрительных долей, которые возвращает validate для первых пяти долей,
+    /// если значение второй из них сложить по модулю 2 с [TAMPER_MASK].
+    pub tampered_suspicious: &'static [usize],
+}
+
+/// Зафиксированные наборы.
+pub const FIXTURES: [Fixture; 4] = [
+    Fixture {
+        secret: 0,
+        seed: [1; 32],
+        shares: [(0, 3484160), (1, 1146101794), (2, 337920033), (3, 2283543616),
+                 (4, 3298841125), (5, 2747695104), (6, 3110264969)],
+        tampered_suspicious: &[1, 3, 4],
+    },
+    Fixture {
+        secret: 314159265,
+        seed: [2; 32],
+        shares: [(0, 137146612), (1, 1982366695), (2, 77410434), (3, 296989937),
+                 (4, 1133748229), (5, 1396653497), (6, 2325467497)],
+        tampered_suspicious: &[1, 4],
+    },
+    Fixture {
+        secret: u32::MAX,
+        seed: [3; 32],
+        shares: [(0, 2942031162), (1, 2540695227), (2, 3755196380), (3, 2145896439),
+                 (4, 3210014550), (5, 478084855), (6, 4272814900)],
+        tampered_suspicious: &[1],
+    },
+    Fixture {
+        secret: 0xDEADBEEF,
+        seed: [4; 32],
+        shares: [(0, 2617833082), (1, 3203207145), (2, 3471703935), (3, 3703549655),
+                 (4, 1596780235), (5, 1579923169), (6, 4140654500)],
+        tampered_suspicious: &[1],
+    },
+];
+
+/// Построение [MATRIX] в виде двумерного массива.
+pub fn matrix() -> Array2<i32> {
+    Array2::from_shape_fn((8, 8), |(i, j)| MATRIX[i][j])
+}
+
+/// Описание первой обнаруженной несовместимости.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompatibilityFailure {
+    /// Номер набора в [FIXTURES].
+    pub fixture: usize,
+    /// Название не пройденной проверки.
+    pub check: &'static str,
+}
+
+impl fmt::Display for CompatibilityFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fixture {}: {}", self.fixture, self.check)
+    }
+}
+
+impl std::error::Error for CompatibilityFailure {}
+
+/// Проверка схемы на совместимость с эталонными наборами.
+///
+/// Для каждого набора проверяется, что схема восстанавливает секрет по всем долям и по разным
+/// пороговым подмножествам, не находит подозрительных долей в честном наборе, отказывает
+/// при числе долей меньше порога и находит те же подозрительные доли в искажённом наборе.
+pub fn run_compatibility_suite<S>(scheme: &S) -> Result<(), CompatibilityFailure>
+where
+    S: SharingScheme<SecretType = u32, PartType = Part>,
+{
+    for (index, fixture) in FIXTURES.iter().enumerate() {
+        let fail = |check| Err(CompatibilityFailure { fixture: index, check });
+        let parts: Vec<Part> = fixture.shares.iter().map(|&(number, data)| Part::from(number, data).with_fingerprint(FINGERPRINT)).collect();
+
+        if scheme.reconstruct(parts.clone()).ok() != Some(fixture.secret) {
+            return fail("reconstruction from all shares");
+        }
+        for start in 0..3 {
+            if scheme.reconstruct(parts[start..start + 5].to_vec()).ok() != Some(fixture.secret) {
+                return fail("reconstruction from threshold shares");
+            }
+        }
+        if scheme.reconstruct(parts[0..4].to_vec()).is_ok() {
+            return fail("rejection of below-threshold shares");
+        }
+        if !scheme.validate(parts.clone()).is_clean() {
+            return fail("validation of honest shares");
+        }
+        let mut tampered = parts[0..5].to_vec();
+        tampered[1] = tampered[1].blinded(TAMPER_MASK);
+        if scheme.validate(tampered).suspicious() != fixture.tampered_suspicious {
+            return fail("detection of tampered share");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HadamardSSS, ValidationOutcome};
+
+    #[test]
+    fn test_reference_passes() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        assert_eq!(run_compatibility_suite(&hsss), Ok(()));
+    }
+
+    #[test]
+    fn test_seeded_shares_frozen() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        for fixture in &FIXTURES {
+            let parts = hsss.share_seeded(fixture.secret, fixture.seed).unwrap();
+            let shares: Vec<(usize, u32)> = parts.iter().map(|p| (p.number(), p.data())).collect();
+            assert_eq!(shares, fixture.shares);
+            assert!(parts.iter().all(|p| p.fingerprint() == Some(FINGERPRINT)));
+        }
+    }
+
+    #[test]
+    fn test_broken_scheme_fails() {
+        struct Broken(HadamardSSS);
+        impl SharingScheme for Broken {
+            type Error = &'static str;
+            type SecretType = u32;
+            type PartType = Part;
+            fn share(&self, secret: u32) -> Result<Vec<Part>, &'static str> {
+                self.0.share(secret)
+            }
+            fn reconstruct(&self, parts: Vec<Part>) -> Result<u32, &'static str> {
+                self.0.reconstruct(parts).map(|secret| secret ^ 1)
+            }
+            fn validate(&self, parts: Vec<Part>) -> ValidationOutcome {
+                self.0.validate(parts)
+            }
+        }
+        let broken = Broken(HadamardSSS::from(&matrix()).unwrap());
+        let failure = run_compatibility_suite(&broken).unwrap_err();
+        assert_eq!(failure, CompatibilityFailure { fixture: 0, check: "reconstruction from all shares" });
+    }
+}
@@ -0,0 +1,234 @@
+//! Схемы с произвольными порогом t и числом участников n.
+//!
+//! Порог схемы Адамара порядка 4m равен 2m + 1 при 4m - 1 долях, то есть примерно половине
+//! долей. [GeneralizedScheme] подбирает для пары (t, n) один из двух режимов:
+//! - [ThresholdMode::Rows]: каждый участник получает w подряд идущих строк схемы Адамара,
+//!   причём t участников держат не меньше порога строк, а t - 1 участников -- меньше;
+//!   так достижимы пороги примерно от n / 2;
+//! - [ThresholdMode::Layered]: для каждого t-подмножества участников секрет разделяется
+//!   на t слагаемых по XOR (схема Ито -- Сайто -- Нисидзеки), доля участника -- его слагаемые
+//!   по всем подмножествам, в которые он входит. Так достижим любой порог, но размер доли
+//!   растёт как C(n - 1, t - 1), поэтому число подмножеств ограничено [MAX_LAYERED_SUBSETS].
+//!
+//! Доля участника -- [ChunkedPart] с номером участника; API совпадает с [SharingScheme].
+use crate::analysis::Combinations;
+use crate::scheme_impl::{ChunkedPart, ParticipantRows};
+use crate::validation::ValidationOutcome;
+use crate::weighted::participant_outcome;
+use crate::{construction, HadamardSSS, SharingScheme};
+use rand::RngCore;
+
+/// Наибольшее число t-подмножеств участников в режиме [ThresholdMode::Layered].
+pub const MAX_LAYERED_SUBSETS: usize = 4096;
+
+/// Способ, которым схема достигает заданного порога.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThresholdMode {
+    /// Участники получают по rows_per_participant строк схемы Адамара данного порядка.
+    Rows {
+        /// Порядок матрицы Адамара.
+        order: usize,
+        /// Число строк у каждого участника.
+        rows_per_participant: usize,
+    },
+    /// Секрет разделяется по XOR между участниками каждого t-подмножества.
+    Layered,
+}
+
+/// Схема с порогом t из n участников.
+pub struct GeneralizedScheme {
+    /// Пороговое число участников.
+    threshold: usize,
+    /// Число участников.
+    participants: usize,
+    /// Схема Адамара в режиме [ThresholdMode::Rows].
+    inner: Option<HadamardSSS>,
+    /// Строки участников в режиме [ThresholdMode::Rows].
+    rows: ParticipantRows,
+    /// t-подмножества участников в режиме [ThresholdMode::Layered].
+    subsets: Vec<Vec<usize>>,
+}
+
+/// Реализация методов схемы с произвольным порогом.
+impl GeneralizedScheme {
+    /// Схема с порогом threshold из participants участников. Режим [ThresholdMode::Rows]
+    /// выбирается с матрицей наименьшего доступного порядка, если он достижим, иначе
+    /// [ThresholdMode::Layered]. Возвращается ошибка, если порог вне 1..=participants или
+    /// в режиме [ThresholdMode::Layered] подмножеств больше [MAX_LAYERED_SUBSETS].
+    pub fn new(threshold: usize, participants: usize) -> Result<Self, &'static str> {
+        if threshold == 0 || threshold > participants {
+            return Err("threshold must be between 1 and the number of participants");
+        }
+        let rows = (4..=construction::MAX_CONSTRUCTED_ORDER).step_by(4).find_map(|order| {
+            let (parts, inner_threshold) = (order - 1, order / 2 + 1);
+            let w = inner_threshold.div_ceil(threshold);
+            if (threshold - 1) * w >= inner_threshold || participants * w > parts {
+                return None;
+            }
+            HadamardSSS::of_order(order).ok().map(|inner| (inner, w))
+        });
+        if let Some((inner, w)) = rows {
+            let rows = ParticipantRows::weighted(&vec![w; participants])?;
+            return Ok(GeneralizedScheme { threshold, participants, inner: Some(inner), rows, subsets: Vec::new() });
+        }
+        let subsets: Vec<Vec<usize>> = Combinations::new(participants, threshold).take(MAX_LAYERED_SUBSETS + 1).collect();
+        if subsets.len() > MAX_LAYERED_SUBSETS {
+            return Err("too many participant subsets for the layered mode");
+        }
+        let rows = ParticipantRows::weighted(&[])?;
+        Ok(GeneralizedScheme { threshold, participants, inner: None, rows, subsets })
+    }
+
+    /// Возвращение значения поля threshold.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Возвращение значения поля participants.
+    pub fn participants(&self) -> usize {
+        self.participants
+    }
+
+    /// Выбранный режим.
+    pub fn mode(&self) -> ThresholdMode {
+        match &self.inner {
+            Some(inner) => ThresholdMode::Rows { order: inner.matrix().order(), rows_per_participant: self.rows.weight(0) },
+            None => ThresholdMode::Layered,
+        }
+    }
+
+    /// Число блоков в доле участника.
+    fn blocks(&self) -> usize {
+        match self.inner {
+            Some(_) => self.rows.weight(0),
+            None => self.subsets.iter().filter(|subset| subset.contains(&0)).count(),
+        }
+    }
+
+    /// Номера различных предъявленных участников или ошибка, если доля не из этой схемы.
+    fn check(&self, parts: &[ChunkedPart]) -> Result<Vec<usize>, &'static str> {
+        if parts.iter().any(|part| part.number() >= self.participants) {
+            return Err("scheme mismatch: participant number exceeds the number of participants");
+        }
+        if parts.iter().any(|part| part.data().len() != self.blocks()) {
+            return Err("participant share has wrong number of blocks");
+        }
+        let mut numbers: Vec<usize> = parts.iter().map(ChunkedPart::number).collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        Ok(numbers)
+    }
+}
+
+/// Реализация трейта SharingScheme в схеме с произвольным порогом.
+impl SharingScheme for GeneralizedScheme {
+    type Error = &'static str;
+    type SecretType = u32;
+    type PartType = ChunkedPart;
+
+    fn share(&self, secret: u32) -> Result<Vec<ChunkedPart>, &'static str> {
+        if let Some(inner) = &self.inner {
+            return inner.share_weighted(secret, &self.rows);
+        }
+        let mut rng = crate::entropy::rng();
+        let mut words: Vec<Vec<u32>> = vec![Vec::new(); self.participants];
+        for subset in &self.subsets {
+            let mut last = secret;
+            for &p in &subset[..subset.len() - 1] {
+                let piece = rng.next_u32();
+                last ^= piece;
+                words[p].push(piece);
+            }
+            words[subset[subset.len() - 1]].push(last);
+        }
+        Ok(words.into_iter().enumerate().map(|(p, data)| ChunkedPart::from(p, data)).collect())
+    }
+
+    fn reconstruct(&self, parts: Vec<ChunkedPart>) -> Result<u32, &'static str> {
+        let numbers = self.check(&parts)?;
+        if numbers.len() < self.threshold {
+            return Err("less than threshold parties");
+        }
+        if let Some(inner) = &self.inner {
+            return inner.reconstruct_weighted(parts, &self.rows);
+        }
+        let quorum = &numbers[..self.threshold];
+        let index = self.subsets.iter().position(|subset| subset == quorum).expect("every t-subset is enumerated");
+        Ok(quorum.iter().fold(0, |secret, &p| {
+            let part = parts.iter().find(|part| part.number() == p).expect("quorum is taken from the parts");
+            let slot = self.subsets[..index].iter().filter(|subset| subset.contains(&p)).count();
+            secret ^ part.data()[slot]
+        }))
+    }
+
+    /// В режиме [ThresholdMode::Rows] доли строк сверяются схемой Адамара, подозрительны
+    /// участники, которым принадлежат подозрительные строки. В режиме [ThresholdMode::Layered]
+    /// слагаемые не избыточны, и сверить можно только номера и число блоков.
+    fn validate(&self, parts: Vec<ChunkedPart>) -> ValidationOutcome {
+        match &self.inner {
+            Some(inner) => inner.validate_weighted(parts, &self.rows),
+            None => {
+                let foreign: Vec<usize> = parts
+                    .iter()
+                    .filter(|part| part.number() >= self.participants || part.data().len() != self.blocks())
+                    .map(ChunkedPart::number)
+                    .collect();
+                participant_outcome(&self.rows, ValidationOutcome::Inconclusive, &foreign)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modes() {
+        let scheme = GeneralizedScheme::new(5, 7).unwrap();
+        assert_eq!(scheme.mode(), ThresholdMode::Rows { order: 8, rows_per_participant: 1 });
+        assert_eq!(GeneralizedScheme::new(6, 10).unwrap().mode(), ThresholdMode::Rows { order: 32, rows_per_participant: 3 });
+        assert_eq!(GeneralizedScheme::new(3, 10).unwrap().mode(), ThresholdMode::Layered);
+        assert!(GeneralizedScheme::new(0, 3).is_err());
+        assert!(GeneralizedScheme::new(4, 3).is_err());
+        assert!(GeneralizedScheme::new(10, 30).is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for (t, n) in [(3, 10), (6, 10), (1, 4), (2, 2), (7, 9)] {
+            let scheme = GeneralizedScheme::new(t, n).unwrap();
+            let parts = scheme.share(314159265).unwrap();
+            assert_eq!(parts.len(), n);
+            for quorum in Combinations::new(n, t) {
+                let chosen: Vec<ChunkedPart> = quorum.iter().map(|&p| parts[p].clone()).collect();
+                assert_eq!(scheme.reconstruct(chosen.clone()), Ok(314159265), "{t}-of-{n}");
+                if t > 1 {
+                    assert_eq!(scheme.reconstruct(chosen[1..].to_vec()), Err("less than threshold parties"));
+                }
+            }
+        }
+        let scheme = GeneralizedScheme::new(3, 10).unwrap();
+        let parts = scheme.share(7).unwrap();
+        assert_eq!(parts[0].data().len(), 36);
+        assert_eq!(scheme.reconstruct(vec![parts[4].clone(), parts[4].clone(), parts[9].clone()]), Err("less than threshold parties"));
+        assert!(scheme.reconstruct(vec![parts[0].clone(), parts[1].clone(), ChunkedPart::from(10, vec![0; 36])]).is_err());
+    }
+
+    #[test]
+    fn test_validation() {
+        let scheme = GeneralizedScheme::new(6, 10).unwrap();
+        let mut parts = scheme.share(314159265).unwrap();
+        assert!(scheme.validate(parts.clone()).is_clean());
+        let mut data = parts[4].data().to_vec();
+        data[1] = !data[1];
+        parts[4] = ChunkedPart::from(4, data).with_fingerprint(parts[4].fingerprint().unwrap());
+        assert_eq!(scheme.validate(parts.clone()).suspicious(), [4]);
+        parts.push(ChunkedPart::from(3, vec![0]));
+        parts.push(ChunkedPart::from(99, vec![]));
+        assert_eq!(scheme.validate(parts).suspicious(), [3, 4, 99]);
+
+        let layered = GeneralizedScheme::new(3, 10).unwrap();
+        assert_eq!(layered.validate(layered.share(1).unwrap()), ValidationOutcome::Inconclusive);
+    }
+}
@@ -0,0 +1,262 @@
+//! Мнемоники набора долей: доля со сведениями о наборе, к которому она относится.
+//!
+//! Раскладка полей заимствована у SLIP-0039: идентификатор набора (15 бит), флаг
+//! расширяемости и показатель итераций (5 бит), номер группы, порог групп минус один, число
+//! групп минус один, номер участника и порог участников минус один (по 4 бита), значение
+//! доли с отпечатком схемы и три контрольных слова RS1024. Совместимым с SLIP-0039 формат не является: слова
+//! кодируют по 10 бит, но берутся из первых 1024 слов списка [crate::mnemonic], а не из
+//! списка SLIP-0039; строка настройки контрольной суммы -- [CUSTOMIZATION], а не "shamir";
+//! значение -- доля u32, а не доля Shamir над GF(256). Инструменты SLIP-0039 такие мнемоники
+//! не читают.
+//!
+//! Доли одной раздачи образуют одну группу; номер участника совпадает с номером доли,
+//! поэтому экспорт доступен для схем порядка не больше 16.
+use crate::scheme_impl::Part;
+use crate::HadamardSSS;
+use rand::Rng;
+
+/// Строка настройки контрольной суммы RS1024.
+pub const CUSTOMIZATION: &str = "hadamard_sss";
+
+/// Число бит в слове.
+const WORD_BITS: usize = 10;
+/// Число слов заголовка: идентификатор, параметры, группа и участник.
+const HEADER_WORDS: usize = 4;
+/// Число слов значения доли и отпечатка схемы: 64 бита с 6 нулевыми битами слева.
+const VALUE_WORDS: usize = 7;
+/// Число контрольных слов.
+const CHECKSUM_WORDS: usize = 3;
+/// Наибольшее значение 4-битного поля.
+const MAX_FIELD: usize = 15;
+
+/// Доля со сведениями о наборе.
+#[derive(Clone, Copy)]
+pub struct MnemonicShare {
+    /// Идентификатор набора долей.
+    identifier: u16,
+    /// Номер группы.
+    group_index: u8,
+    /// Порог групп.
+    group_threshold: u8,
+    /// Число групп.
+    group_count: u8,
+    /// Порог участников в группе.
+    member_threshold: u8,
+    /// Доля; её номер -- номер участника.
+    part: Part,
+}
+
+/// Реализация методов доли со сведениями о наборе.
+impl MnemonicShare {
+    /// Возвращение значения поля identifier.
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// Возвращение значения поля group_index.
+    pub fn group_index(&self) -> u8 {
+        self.group_index
+    }
+
+    /// Возвращение значения поля group_threshold.
+    pub fn group_threshold(&self) -> u8 {
+        self.group_threshold
+    }
+
+    /// Возвращение значения поля group_count.
+    pub fn group_count(&self) -> u8 {
+        self.group_count
+    }
+
+    /// Номер участника в группе.
+    pub fn member_index(&self) -> u8 {
+        self.part.number() as u8
+    }
+
+    /// Возвращение значения поля member_threshold.
+    pub fn member_threshold(&self) -> u8 {
+        self.member_threshold
+    }
+
+    /// Возвращение значения поля part.
+    pub fn part(&self) -> Part {
+        self.part
+    }
+}
+
+/// Остаток RS1024 с образующим многочленом SLIP-0039.
+fn polymod(values: impl IntoIterator<Item = u32>) -> u32 {
+    const GEN: [u32; 10] = [
+        0x00E0_E040, 0x01C1_C080, 0x0383_8100, 0x0707_0200, 0x0E0E_0009, 0x1C0C_2412, 0x3808_6C24, 0x3090_FC48, 0x21B1_F890, 0x03F3_F120,
+    ];
+    values.into_iter().fold(1, |chk, value| {
+        let top = chk >> 20;
+        let chk = ((chk & 0xF_FFFF) << 10) ^ value;
+        (0..10).filter(|i| top >> i & 1 == 1).fold(chk, |chk, i| chk ^ GEN[i])
+    })
+}
+
+/// Значения, которые входят в контрольную сумму перед словами.
+fn customization() -> impl Iterator<Item = u32> {
+    CUSTOMIZATION.bytes().map(u32::from)
+}
+
+/// Запись доли мнемоникой из четырнадцати слов. Возвращается ошибка, если у доли
+/// нет отпечатка схемы.
+pub fn encode(share: &MnemonicShare) -> Result<String, &'static str> {
+    let fingerprint = share.part.fingerprint().ok_or("part has no scheme fingerprint")?;
+    let fields = [share.group_index, share.group_threshold - 1, share.group_count - 1, share.member_index(), share.member_threshold - 1];
+    let header = fields.iter().fold(u64::from(share.identifier) << 5, |acc, &f| (acc << 4) | u64::from(f));
+    let value = u128::from(share.part.data()) << 32 | u128::from(fingerprint);
+    let bits = (u128::from(header) << (VALUE_WORDS * WORD_BITS)) | value;
+    let mut values: Vec<u32> = (0..HEADER_WORDS + VALUE_WORDS).rev().map(|i| (bits >> (WORD_BITS * i)) as u32 & 0x3FF).collect();
+    let residue = polymod(customization().chain(values.iter().copied()).chain([0; CHECKSUM_WORDS])) ^ 1;
+    values.extend((0..CHECKSUM_WORDS).rev().map(|i| residue >> (WORD_BITS * i) & 0x3FF));
+    let words: Vec<String> = values.into_iter().map(|v| crate::mnemonic::word(v as u16)).collect();
+    Ok(words.join(" "))
+}
+
+/// Чтение мнемоники, записанной [encode].
+pub fn decode(text: &str) -> Result<MnemonicShare, &'static str> {
+    let values = text
+        .split_whitespace()
+        .map(|w| crate::mnemonic::index(w).filter(|&i| i < 1 << WORD_BITS).map(u32::from).ok_or("unknown mnemonic word"))
+        .collect::<Result<Vec<u32>, _>>()?;
+    if values.len() != HEADER_WORDS + VALUE_WORDS + CHECKSUM_WORDS {
+        return Err("wrong number of mnemonic words");
+    }
+    if polymod(customization().chain(values.iter().copied())) != 1 {
+        return Err("mnemonic checksum mismatch");
+    }
+    let bits = values[..HEADER_WORDS + VALUE_WORDS].iter().fold(0u128, |acc, &v| (acc << WORD_BITS) | u128::from(v));
+    if bits >> 64 & 0x3F != 0 {
+        return Err("invalid padding bits");
+    }
+    let header = (bits >> (VALUE_WORDS * WORD_BITS)) as u64;
+    let field = |i: usize| (header >> (4 * (4 - i)) & 15) as u8;
+    if header >> 20 & 31 != 0 {
+        return Err("unsupported group mnemonic parameters");
+    }
+    let share = MnemonicShare {
+        identifier: (header >> 25) as u16,
+        group_index: field(0),
+        group_threshold: field(1) + 1,
+        group_count: field(2) + 1,
+        member_threshold: field(4) + 1,
+        part: Part::from(usize::from(field(3)), (bits >> 32) as u32).with_fingerprint(bits as u32),
+    };
+    if share.group_threshold > share.group_count {
+        return Err("group threshold exceeds group count");
+    }
+    Ok(share)
+}
+
+/// Экспорт и импорт мнемоник набора долей.
+impl HadamardSSS {
+    /// Запись долей одной раздачи мнемониками с общим случайным идентификатором.
+    pub fn export_group_mnemonics(&self, parts: &[Part]) -> Result<Vec<String>, &'static str> {
+        if self.hss.mtx_len() > MAX_FIELD + 1 || self.threshold > MAX_FIELD + 1 {
+            return Err("scheme is too large for group mnemonic fields");
+        }
+        self.hss.check_parts(parts)?;
+        let identifier = crate::entropy::rng().gen_range(0..1 << 15);
+        parts
+            .iter()
+            .map(|&part| {
+                encode(&MnemonicShare {
+                    identifier,
+                    group_index: 0,
+                    group_threshold: 1,
+                    group_count: 1,
+                    member_threshold: self.threshold as u8,
+                    part,
+                })
+            })
+            .collect()
+    }
+
+    /// Чтение долей из мнемоник. Все мнемоники должны принадлежать одной группе одного
+    /// набора, а порог в них -- совпадать с порогом схемы. Номер участника не может
+    /// повторяться: две мнемоники одного участника -- это ошибка переписывания или подделка.
+    pub fn import_group_mnemonics(&self, mnemonics: &[&str]) -> Result<Vec<Part>, &'static str> {
+        let shares = mnemonics.iter().map(|text| decode(text)).collect::<Result<Vec<_>, _>>()?;
+        let first = shares.first().ok_or("no mnemonics given")?;
+        for (i, share) in shares.iter().enumerate() {
+            if shares[..i].iter().any(|other| other.member_index() == share.member_index()) {
+                return Err("duplicate member index");
+            }
+            if (share.identifier, share.group_index) != (first.identifier, first.group_index) {
+                return Err("mnemonics belong to different share sets");
+            }
+            if usize::from(share.member_threshold) != self.threshold {
+                return Err("mnemonic threshold does not match the scheme");
+            }
+        }
+        let parts: Vec<Part> = shares.iter().map(MnemonicShare::part).collect();
+        self.hss.check_parts(&parts)?;
+        Ok(parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::SharingScheme;
+
+    #[test]
+    fn test_export_import() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let mnemonics = hsss.export_group_mnemonics(&parts).unwrap();
+        let share = decode(&mnemonics[3]).unwrap();
+        assert_eq!((share.group_index(), share.group_threshold(), share.group_count()), (0, 1, 1));
+        assert_eq!((share.member_index(), share.member_threshold()), (3, 5));
+        assert_eq!(share.part().data(), parts[3].data());
+        assert_eq!(share.part().fingerprint(), Some(hsss.fingerprint()));
+        assert_eq!(encode(&share).unwrap(), mnemonics[3]);
+
+        let texts: Vec<&str> = mnemonics.iter().map(String::as_str).collect();
+        let imported = hsss.import_group_mnemonics(&texts[1..6]).unwrap();
+        assert_eq!(hsss.reconstruct(imported).unwrap(), 314159265);
+
+        let other = hsss.export_group_mnemonics(&parts).unwrap();
+        if decode(&other[0]).unwrap().identifier() != share.identifier() {
+            assert_eq!(hsss.import_group_mnemonics(&[texts[1], &other[0]]).err(), Some("mnemonics belong to different share sets"));
+        }
+        assert_eq!(hsss.import_group_mnemonics(&[]).err(), Some("no mnemonics given"));
+        assert_eq!(hsss.import_group_mnemonics(&[texts[1], texts[2], texts[1]]).err(), Some("duplicate member index"));
+    }
+
+    #[test]
+    fn test_errors_detected() {
+        let share = MnemonicShare {
+            identifier: 12345,
+            group_index: 1,
+            group_threshold: 2,
+            group_count: 3,
+            member_threshold: 5,
+            part: Part::from(6, 0xC0FFEE).with_fingerprint(0xF00D),
+        };
+        let text = encode(&share).unwrap();
+        let decoded = decode(&text).unwrap();
+        assert_eq!(decoded.identifier(), 12345);
+        assert_eq!((decoded.group_index(), decoded.group_threshold(), decoded.group_count()), (1, 2, 3));
+        assert_eq!((decoded.member_index(), decoded.member_threshold(), decoded.part().data()), (6, 5, 0xC0FFEE));
+        let words: Vec<&str> = text.split(' ').collect();
+        // RS1024 обнаруживает любую замену до трёх слов
+        for position in 0..words.len() {
+            for delta in [1, 2, 513] {
+                let mut typo = words.clone();
+                let replaced = crate::mnemonic::word(crate::mnemonic::index(words[position]).unwrap() ^ delta);
+                typo[position] = &replaced;
+                assert_eq!(decode(&typo.join(" ")).err(), Some("mnemonic checksum mismatch"));
+            }
+        }
+        assert_eq!(encode(&MnemonicShare { part: Part::from(6, 0), ..share }).err(), Some("part has no scheme fingerprint"));
+        assert_eq!(decode(&words[..13].join(" ")).err(), Some("wrong number of mnemonic words"));
+        // слова за пределами первых 1024 не используются
+        let outside = crate::mnemonic::word(1024);
+        assert_eq!(decode(&text.replacen(words[0], &outside, 1)).err(), Some("unknown mnemonic word"));
+    }
+}
@@ -1,14 +1,120 @@
 //! Модуль, в котором реализована структура для работы с матрицами Адамара.
-use ndarray::{arr2, Array2};
+use ndarray::{Array2, ArrayView1, ArrayView2};
+use std::fmt;
+use std::sync::OnceLock;
 #[cfg(feature = "zeroize_memory")]
 use zeroize::Zeroize;
 
-#[cfg_attr(feature = "zeroize_memory", derive(Zeroize))]
-#[cfg_attr(feature = "zeroize_memory", zeroize(drop))]
 /// Структура, хранящая матрицу Адамара.
+#[derive(Clone)]
 pub struct HadamardMatrix {
     /// Двумерная матрица, являющаяся матрицей Адамара.
-    mtx: Array2<i32>,
+    /// Элементы равны ±1, поэтому хранятся в i8; на границе API они преобразуются в i32.
+    mtx: Array2<i8>,
+    /// Матрица инцидентности, вычисляемая при первом обращении и сбрасываемая при нормализации.
+    incidence: OnceLock<Array2<i32>>,
+}
+
+/// Сводка свойств матрицы Адамара, см. [HadamardMatrix::report].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatrixReport {
+    /// Порядок матрицы.
+    pub order: usize,
+    /// Состоят ли первая строка и первый столбец из одних 1.
+    pub normalized: bool,
+    /// Совпадает ли матрица со своей транспонированной.
+    pub symmetric: bool,
+    /// Является ли матрица кососимметрической матрицей Адамара.
+    pub skew: bool,
+    /// Одинаковы ли суммы всех строк и всех столбцов.
+    pub regular: bool,
+    /// Суммы элементов строк.
+    pub row_sums: Vec<i32>,
+    /// Суммы элементов столбцов.
+    pub column_sums: Vec<i32>,
+}
+
+impl fmt::Display for MatrixReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |set: bool, name: &str| if set { name.to_string() } else { format!("not {}", name) };
+        write!(f, "order {}, {}, {}, {}, {}", self.order,
+               flag(self.normalized, "normalized"), flag(self.symmetric, "symmetric"),
+               flag(self.skew, "skew"), flag(self.regular, "regular"))
+    }
+}
+
+/// Нарушение матрицей инцидентности условий блок-дизайна 2-(4n-1, 2n-1, n-1).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DesignError {
+    /// Матрица не квадратная или её порядок не имеет вида 4n-1.
+    Shape { rows: usize, columns: usize },
+    /// Элемент отличен от 0 и 1.
+    Entry { row: usize, column: usize, value: i32 },
+    /// Блок (строка) содержит не 2n-1 точек.
+    BlockSize { block: usize, size: i32, expected: i32 },
+    /// Пара точек (столбцов) содержится не в n-1 блоках.
+    PairCount { points: (usize, usize), count: i32, expected: i32 },
+}
+
+impl fmt::Display for DesignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DesignError::Shape { rows, columns } => write!(f, "incidence matrix {}x{} is not of order 4n-1", rows, columns),
+            DesignError::Entry { row, column, value } => write!(f, "entry ({}, {}) is {}, not 0 or 1", row, column, value),
+            DesignError::BlockSize { block, size, expected } => write!(f, "block {} has {} points instead of {}", block, size, expected),
+            DesignError::PairCount { points: (a, b), count, expected } => {
+                write!(f, "points {} and {} lie in {} blocks instead of {}", a, b, count, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DesignError {}
+
+/// Проверка, что матрица инцидентности задаёт блок-дизайн 2-(4n-1, 2n-1, n-1):
+/// каждая строка содержит 2n-1 единиц, а каждая пара столбцов -- ровно n-1 общих единиц.
+pub fn check_design(incidence: ArrayView2<'_, i32>) -> Result<(), DesignError> {
+    let (rows, columns) = incidence.dim();
+    if rows != columns || (rows + 1) % 4 != 0 {
+        return Err(DesignError::Shape { rows, columns });
+    }
+    if let Some(((row, column), &value)) = incidence.indexed_iter().find(|(_, &x)| x != 0 && x != 1) {
+        return Err(DesignError::Entry { row, column, value });
+    }
+    let n = ((rows + 1) / 4) as i32;
+    for (block, row) in incidence.rows().into_iter().enumerate() {
+        let size = row.sum();
+        if size != 2 * n - 1 {
+            return Err(DesignError::BlockSize { block, size, expected: 2 * n - 1 });
+        }
+    }
+    let pairs = incidence.t().dot(&incidence);
+    for a in 0..columns {
+        for b in a + 1..columns {
+            if pairs[[a, b]] != n - 1 {
+                return Err(DesignError::PairCount { points: (a, b), count: pairs[[a, b]], expected: n - 1 });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Затирание матрицы при удалении структуры.
+#[cfg(feature = "zeroize_memory")]
+impl Zeroize for HadamardMatrix {
+    fn zeroize(&mut self) {
+        self.mtx.iter_mut().for_each(Zeroize::zeroize);
+        if let Some(incidence) = self.incidence.get_mut() {
+            incidence.iter_mut().for_each(Zeroize::zeroize);
+        }
+    }
+}
+
+#[cfg(feature = "zeroize_memory")]
+impl Drop for HadamardMatrix {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
 /// Реализация методов структуры.
@@ -17,58 +123,111 @@ impl HadamardMatrix {
     /// В случае, если пришедшая матрица не является матрицей Адамара,
     /// возвращается соответствующая ошибка.
     /// # Пример
-    /// ```
+    /// ```ignore
     /// let h_mtx = HadamardMatrix::from(&arr2(&[[1, 1],
     ///                                         [1, -1]]).expect("Can't create Hadamard mtx."));
     /// ```
     pub fn from(mtx: &Array2<i32>) -> Result<HadamardMatrix, &'static str> {
-        if HadamardMatrix::is_hadamard(&mtx) {
-            Ok(HadamardMatrix { mtx: mtx.clone() })
+        if HadamardMatrix::is_hadamard(mtx) {
+            Ok(HadamardMatrix::wrap(mtx.mapv(|x| x as i8)))
+        } else {
+            Err("something wrong with that matrix")
+        }
+    }
+
+    /// Создание экземпляра структуры по матрице с элементами i8.
+    pub fn from_i8(mtx: &Array2<i8>) -> Result<HadamardMatrix, &'static str> {
+        if HadamardMatrix::is_hadamard_i8(mtx) {
+            Ok(HadamardMatrix::wrap(mtx.clone()))
         } else {
             Err("something wrong with that matrix")
         }
     }
 
+    /// Матрица Сильвестра порядка 2^k, построенная удвоением H -> [[H, H], [H, -H]];
+    /// её элементы равны `(-1)^popcount(i & j)`. Для k больше [HadamardMatrix::MAX_SYLVESTER_POWER]
+    /// возвращается ошибка.
+    pub fn sylvester(k: u32) -> Result<HadamardMatrix, &'static str> {
+        if k > HadamardMatrix::MAX_SYLVESTER_POWER {
+            return Err("Sylvester matrix order is too large");
+        }
+        let order = 1usize << k;
+        Ok(HadamardMatrix::wrap(Array2::from_shape_fn((order, order), |(i, j)| {
+            if (i & j).count_ones() % 2 == 0 { 1 } else { -1 }
+        })))
+    }
+
+    /// Наибольшая степень k в [HadamardMatrix::sylvester]: порядок 4096, 16 МиБ.
+    pub const MAX_SYLVESTER_POWER: u32 = 12;
+
+    /// Обёртка над уже проверенной матрицей.
+    pub(crate) fn wrap(mtx: Array2<i8>) -> Self {
+        HadamardMatrix { mtx, incidence: OnceLock::new() }
+    }
+
+    /// Порядок матрицы.
+    pub fn order(&self) -> usize {
+        self.mtx.shape()[0]
+    }
+
+    /// Матрица без копирования.
+    pub fn view(&self) -> ArrayView2<'_, i8> {
+        self.mtx.view()
+    }
+
+    /// Итератор по строкам матрицы.
+    pub fn rows(&self) -> impl Iterator<Item = ArrayView1<'_, i8>> {
+        self.mtx.rows().into_iter()
+    }
+
+    /// Копия матрицы с элементами i32.
+    pub fn to_i32(&self) -> Array2<i32> {
+        self.mtx.mapv(i32::from)
+    }
+
     /// Проверка матрицу на Адамаровость:
     /// - является ли она квадратной
     /// - является ли она непустой
     /// - состоит ли только из -1 и 1
     /// - проверка на определение H * H.T = nI
     /// # Пример.
-    /// ```
+    /// ```ignore
     /// let res = HadamardMatrix::is_hadamard(&arr2(&[[1, 2],
-    ///                                               [3, 4]]);
+    ///                                               [3, 4]]));
     /// // получим res == false
+    /// ```
     fn is_hadamard(mtx: &Array2<i32>) -> bool {
+        mtx.iter().all(|&x| x == -1 || x == 1) && HadamardMatrix::is_hadamard_i8(&mtx.mapv(|x| x as i8))
+    }
+
+    /// Та же проверка для матрицы с элементами i8.
+    /// Скалярные произведения строк считаются в i32, так как в i8 они переполняются уже при n > 127.
+    fn is_hadamard_i8(mtx: &Array2<i8>) -> bool {
         let n = mtx.shape()[0];
-        let m = mtx.shape()[1];
         if !mtx.is_square() || n < 1 {
             return false;
         }
+        if mtx.iter().any(|&x| x != -1 && x != 1) {
+            return false;
+        }
         for i in 0..n {
-            for j in 0..n {
-                if (mtx[[i, j]] != -1) && (mtx[[i, j]] != 1) {
+            for j in i..n {
+                let dot: i32 = mtx.row(i).iter().zip(mtx.row(j)).map(|(&a, &b)| i32::from(a * b)).sum();
+                if dot != if i == j { n as i32 } else { 0 } {
                     return false;
                 }
             }
         }
-        let res = n as i32 * Array2::<i32>::eye(n);
-        let mult_res = mtx.dot(&mtx.t());
-
-        if res != mult_res {
-            false
-        } else {
-            true
-        }
+        true
     }
 
     /// Нормализация матрицы Адамара, чтобы первый столбец и первая строка состояли из одних 1.
-    /// ```
+    /// ```text
     /// [[-1, -1],
     ///  [-1, 1]]
     /// ```
     /// Становится
-    /// ```
+    /// ```text
     /// [[1, 1],
     ///  [1, -1]]
     /// ```
@@ -86,20 +245,106 @@ impl HadamardMatrix {
                 }
             }
         }
+        self.incidence = OnceLock::new();
         self
     }
 
+    /// Проверка, состоят ли первая строка и первый столбец из одних 1.
+    pub fn is_normalized(&self) -> bool {
+        self.mtx.row(0).iter().chain(self.mtx.column(0)).all(|&x| x == 1)
+    }
+
+    /// Нормализованная копия матрицы; исходная матрица не изменяется.
+    pub fn normalized(&self) -> HadamardMatrix {
+        let mut res = self.clone();
+        if !res.is_normalized() {
+            res.normalize();
+        }
+        res
+    }
+
+    /// Проверка, является ли матрица кососимметрической матрицей Адамара (H + H.T = 2I):
+    /// на диагонали стоят 1, а симметричные относительно диагонали элементы противоположны.
+    /// Такие матрицы строит, например, конструкция Пэли I.
+    pub fn is_skew(&self) -> bool {
+        let n = self.order();
+        (0..n).all(|i| self.mtx[[i, i]] == 1 && (i + 1..n).all(|j| self.mtx[[i, j]] == -self.mtx[[j, i]]))
+    }
+
+    /// Проверка, совпадает ли матрица со своей транспонированной.
+    pub fn is_symmetric(&self) -> bool {
+        self.mtx == self.mtx.t()
+    }
+
+    /// Проверка, является ли матрица регулярной: суммы всех строк и всех столбцов одинаковы.
+    /// Регулярные матрицы Адамара существуют только для порядков вида 4u².
+    pub fn is_regular(&self) -> bool {
+        let rows = self.row_sums();
+        rows.iter().chain(&self.column_sums()).all(|&sum| sum == rows[0])
+    }
+
+    /// Суммы элементов строк.
+    pub fn row_sums(&self) -> Vec<i32> {
+        self.rows().map(|row| row.iter().map(|&x| i32::from(x)).sum()).collect()
+    }
+
+    /// Суммы элементов столбцов.
+    pub fn column_sums(&self) -> Vec<i32> {
+        self.mtx.columns().into_iter().map(|column| column.iter().map(|&x| i32::from(x)).sum()).collect()
+    }
+
+    /// Сводка свойств матрицы, например для записи в журнал того, по какой матрице построена схема.
+    pub fn report(&self) -> MatrixReport {
+        MatrixReport {
+            order: self.order(),
+            normalized: self.is_normalized(),
+            symmetric: self.is_symmetric(),
+            skew: self.is_skew(),
+            regular: self.is_regular(),
+            row_sums: self.row_sums(),
+            column_sums: self.column_sums(),
+        }
+    }
+
+    /// Транспонированная матрица. Если H * H.T = nI, то и H.T * H = nI,
+    /// поэтому результат тоже является матрицей Адамара.
+    pub fn transposed(&self) -> HadamardMatrix {
+        HadamardMatrix::wrap(self.mtx.t().to_owned())
+    }
+
+    /// Матрица с противоположными знаками всех элементов (тоже матрица Адамара).
+    pub fn negated(&self) -> HadamardMatrix {
+        HadamardMatrix::wrap(self.mtx.mapv(|x| -x))
+    }
+
     /// Получения матрицы инцидентности по данной матрице Адамара,
     /// которая будет соответствовать блок-дизайну 2-(4n-1, 2n-1, n-1)
     pub fn get_incidence(&self) -> Array2<i32> {
-        let n = self.mtx.shape()[0];
-        (&self.mtx.view().slice(s![1.., 1..]) + &Array2::<i32>::ones((n - 1, n - 1))) / 2
+        self.incidence().to_owned()
+    }
+
+    /// Матрица инцидентности с проверкой условий блок-дизайна ([check_design]).
+    /// Ошибка означает, что матрица не нормализована или её порядок не кратен 4,
+    /// и раздавать доли по такому дизайну нельзя.
+    pub fn checked_incidence(&self) -> Result<ArrayView2<'_, i32>, DesignError> {
+        let incidence = self.incidence();
+        check_design(incidence)?;
+        Ok(incidence)
+    }
+
+    /// Матрица инцидентности без копирования. Вычисляется один раз
+    /// и пересчитывается только после [HadamardMatrix::normalize].
+    pub fn incidence(&self) -> ArrayView2<'_, i32> {
+        self.incidence
+            .get_or_init(|| self.mtx.slice(s![1.., 1..]).mapv(|x| (i32::from(x) + 1) / 2))
+            .view()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ndarray::arr2;
 
     #[test]
     #[should_panic]
@@ -109,27 +354,40 @@ mod tests {
 
     #[test]
     fn false_mtx() {
-        assert_eq!(HadamardMatrix::is_hadamard(&arr2(&[[1, 2],
-                                                   [3, 4]]))
-                   , false);
-        assert_eq!(HadamardMatrix::is_hadamard(&arr2(&[[1, 0],
-                                                    [0, 1]]))
-                   , false);
-        assert_eq!(HadamardMatrix::is_hadamard(&arr2(&[[-1, -1],
-                                                    [1, 1]]))
-                   , false);
+        assert!(!HadamardMatrix::is_hadamard(&arr2(&[[1, 2],
+                                                     [3, 4]])));
+        assert!(!HadamardMatrix::is_hadamard(&arr2(&[[1, 0],
+                                                     [0, 1]])));
+        assert!(!HadamardMatrix::is_hadamard(&arr2(&[[-1, -1],
+                                                     [1, 1]])));
     }
 
     #[test]
     fn true_mtx() {
-        assert_eq!( HadamardMatrix::is_hadamard(&arr2(&[[1, 1],
-                                                   [1, -1]]))
-                   , true);
-        assert_eq!(HadamardMatrix::is_hadamard(&arr2(&[[1, 1, 1, 1],
+        assert!(HadamardMatrix::is_hadamard(&arr2(&[[1, 1],
+                                                    [1, -1]])));
+        assert!(HadamardMatrix::is_hadamard(&arr2(&[[1, 1, 1, 1],
                                                     [1, -1, 1, -1],
                                                     [1, 1, -1, -1],
-                                                    [1, -1, -1, 1]]))
-                   , true);
+                                                    [1, -1, -1, 1]])));
+    }
+
+    #[test]
+    fn test_i8_boundary() {
+        let mtx = arr2(&[[1, 1, 1, 1],
+                         [1, -1, 1, -1],
+                         [1, 1, -1, -1],
+                         [1, -1, -1, 1]]);
+        let h_mtx = HadamardMatrix::from(&mtx).unwrap();
+        assert_eq!(h_mtx.to_i32(), mtx);
+        let same = HadamardMatrix::from_i8(&mtx.mapv(|x| x as i8)).unwrap();
+        assert_eq!(same.mtx, h_mtx.mtx);
+        assert!(HadamardMatrix::from_i8(&arr2(&[[1, 1], [1, 1]])).is_err());
+        // 257 не усекается до 1 при переходе к i8
+        assert!(HadamardMatrix::from(&arr2(&[[257]])).is_err());
+        // порядок, при котором скалярное произведение не помещается в i8
+        let big = Array2::from_shape_fn((128, 128), |(i, j)| if (i & j).count_ones() % 2 == 0 { 1i8 } else { -1 });
+        assert!(HadamardMatrix::from_i8(&big).is_ok());
     }
 
     #[test]
@@ -149,6 +407,91 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_is_normalized() {
+        let h_mtx = HadamardMatrix::from(&arr2(&[[-1, 1],
+                                                 [1, 1]])).unwrap();
+        assert!(!h_mtx.is_normalized());
+        let normalized = h_mtx.normalized();
+        assert!(normalized.is_normalized());
+        assert!(!h_mtx.is_normalized());
+        assert_eq!(normalized.mtx, arr2(&[[1, 1],
+                                         [1, -1]]));
+    }
+
+    #[test]
+    fn test_accessors() {
+        let h_mtx = HadamardMatrix::from(&arr2(&[[1, 1, 1, 1],
+                                                 [1, -1, 1, -1],
+                                                 [1, 1, -1, -1],
+                                                 [1, -1, -1, 1]])).unwrap();
+        assert_eq!(h_mtx.order(), 4);
+        assert_eq!(h_mtx.view()[[1, 1]], -1);
+        let sums: Vec<i32> = h_mtx.rows().map(|row| row.iter().map(|&x| i32::from(x)).sum()).collect();
+        assert_eq!(sums, vec![4, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_transposed_negated() {
+        let h_mtx = HadamardMatrix::from(&arr2(&[[1, 1, 1, 1],
+                                                 [-1, 1, -1, 1],
+                                                 [1, 1, -1, -1],
+                                                 [-1, 1, 1, -1]])).unwrap();
+        let t = h_mtx.transposed();
+        assert!(HadamardMatrix::is_hadamard(&t.to_i32()));
+        assert_eq!(t.view(), h_mtx.view().t());
+        assert_eq!(t.transposed().mtx, h_mtx.mtx);
+
+        let neg = h_mtx.negated();
+        assert!(HadamardMatrix::is_hadamard(&neg.to_i32()));
+        assert_eq!(neg.view()[[0, 0]], -1);
+        assert_eq!(neg.negated().mtx, h_mtx.mtx);
+        // нормализация не зависит от общего знака
+        assert_eq!(neg.normalized().mtx, h_mtx.normalized().mtx);
+    }
+
+    #[test]
+    fn test_is_skew() {
+        let skew = HadamardMatrix::from(&arr2(&[[1, 1, 1, 1],
+                                                [-1, 1, 1, -1],
+                                                [-1, -1, 1, 1],
+                                                [-1, 1, -1, 1]])).unwrap();
+        assert!(skew.is_skew());
+        assert!(skew.transposed().is_skew());
+        assert!(!skew.negated().is_skew());
+        assert!(HadamardMatrix::from(&arr2(&[[1, 1],
+                                             [-1, 1]])).unwrap().is_skew());
+        assert!(!HadamardMatrix::from(&arr2(&[[1, 1],
+                                              [1, -1]])).unwrap().is_skew());
+    }
+
+    #[test]
+    fn test_report() {
+        let sylvester = HadamardMatrix::from(&arr2(&[[1, 1, 1, 1],
+                                                     [1, -1, 1, -1],
+                                                     [1, 1, -1, -1],
+                                                     [1, -1, -1, 1]])).unwrap();
+        let report = sylvester.report();
+        assert_eq!(report, MatrixReport {
+            order: 4,
+            normalized: true,
+            symmetric: true,
+            skew: false,
+            regular: false,
+            row_sums: vec![4, 0, 0, 0],
+            column_sums: vec![4, 0, 0, 0],
+        });
+        assert_eq!(report.to_string(), "order 4, normalized, symmetric, not skew, not regular");
+
+        let regular = HadamardMatrix::from(&arr2(&[[-1, 1, 1, 1],
+                                                   [1, -1, 1, 1],
+                                                   [1, 1, -1, 1],
+                                                   [1, 1, 1, -1]])).unwrap();
+        assert!(regular.is_regular());
+        assert_eq!(regular.report().row_sums, vec![2; 4]);
+        assert!(!regular.report().normalized);
+    }
+
     #[test]
     fn test_incidence() {
         assert_eq!(HadamardMatrix::from(&arr2(&[[1, 1],
@@ -166,4 +509,62 @@ mod tests {
                           [0, 0, 1]])
         );
     }
+
+    #[test]
+    fn test_checked_incidence() {
+        let mut h_mtx = HadamardMatrix::from(&arr2(&[[1, 1, 1, 1, 1, 1, 1, 1],
+                                                     [1, -1, 1, -1, 1, -1, 1, -1],
+                                                     [1, 1, -1, -1, 1, 1, -1, -1],
+                                                     [1, -1, -1, 1, 1, -1, -1, 1],
+                                                     [1, 1, 1, 1, -1, -1, -1, -1],
+                                                     [1, -1, 1, -1, -1, 1, -1, 1],
+                                                     [1, 1, -1, -1, -1, -1, 1, 1],
+                                                     [1, -1, -1, 1, -1, 1, 1, -1]])).unwrap();
+        assert_eq!(h_mtx.checked_incidence().unwrap(), h_mtx.incidence());
+        assert_eq!(h_mtx.negated().checked_incidence(),
+                   Err(DesignError::BlockSize { block: 0, size: 4, expected: 3 }));
+        assert_eq!(HadamardMatrix::from(&arr2(&[[1, 1], [1, -1]])).unwrap().checked_incidence(),
+                   Err(DesignError::Shape { rows: 1, columns: 1 }));
+        assert_eq!(check_design(arr2(&[[0, 2, 1], [1, 0, 0], [0, 0, 1]]).view()),
+                   Err(DesignError::Entry { row: 0, column: 1, value: 2 }));
+        // плоскость Фано, в которой блок {2, 4, 5} заменён на {2, 4, 6}
+        let blocks = [[0, 1, 2], [0, 3, 4], [0, 5, 6], [1, 3, 5], [1, 4, 6], [2, 3, 6], [2, 4, 6]];
+        let broken = Array2::from_shape_fn((7, 7), |(i, j)| blocks[i].contains(&j) as i32);
+        assert_eq!(check_design(broken.view()),
+                   Err(DesignError::PairCount { points: (2, 5), count: 0, expected: 1 }));
+        assert!(h_mtx.normalize().checked_incidence().is_ok());
+    }
+
+    #[test]
+    fn test_incidence_cache() {
+        let mut h_mtx = HadamardMatrix::from(&arr2(&[[-1, 1],
+                                                     [1, 1]])).unwrap();
+        assert_eq!(h_mtx.incidence(), arr2(&[[1]]));
+        let first = h_mtx.incidence().as_ptr();
+        assert_eq!(h_mtx.incidence().as_ptr(), first);
+        // после нормализации кэш сбрасывается
+        assert_eq!(h_mtx.normalize().incidence(), arr2(&[[0]]));
+        assert_eq!(h_mtx.get_incidence(), arr2(&[[0]]));
+    }
+
+    #[test]
+    fn test_sylvester() {
+        let h4 = HadamardMatrix::sylvester(2).unwrap();
+        assert_eq!(h4.view(), arr2(&[[1, 1, 1, 1],
+                                     [1, -1, 1, -1],
+                                     [1, 1, -1, -1],
+                                     [1, -1, -1, 1]]));
+        for k in 0..=6 {
+            let h_mtx = HadamardMatrix::sylvester(k).unwrap();
+            assert_eq!(h_mtx.order(), 1 << k);
+            assert!(HadamardMatrix::is_hadamard_i8(&h_mtx.view().to_owned()));
+            assert!(h_mtx.is_normalized());
+        }
+        assert!(HadamardMatrix::sylvester(HadamardMatrix::MAX_SYLVESTER_POWER + 1).is_err());
+
+        use crate::SharingScheme;
+        let hsss = crate::HadamardSSS::from_matrix(&HadamardMatrix::sylvester(4).unwrap()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        assert_eq!(hsss.reconstruct(parts[6..15].to_vec()).unwrap(), 314159265);
+    }
 }
@@ -0,0 +1,5 @@
+//! Вспомогательные функции для разделения ключей сторонних форматов и алгоритмов.
+#[cfg(feature = "x25519")]
+pub mod x25519;
+#[cfg(feature = "pem")]
+pub mod pem;
@@ -0,0 +1,54 @@
+//! Разделение закрытых ключей X25519.
+//!
+//! 32-байтный закрытый ключ разделяется как восемь блоков u32. При восстановлении
+//! из ключа вычисляется открытый ключ и сравнивается с ожидаемым за постоянное время.
+use crate::keys::{from_words, sensitive, to_words};
+use crate::scheme_impl::ChunkedPart;
+use crate::HadamardSSS;
+use subtle::ConstantTimeEq;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// Разделение и восстановление закрытых ключей X25519.
+impl HadamardSSS {
+    /// Разделение закрытого ключа X25519 на доли.
+    pub fn share_x25519(&self, secret: &StaticSecret) -> Result<Vec<ChunkedPart>, &'static str> {
+        self.share_words(&sensitive(to_words(secret.as_bytes())))
+    }
+
+    /// Восстановление закрытого ключа X25519 по долям.
+    /// Возвращается ошибка, если открытый ключ восстановленного ключа не совпадает с ожидаемым.
+    pub fn reconstruct_x25519(&self, parts: Vec<ChunkedPart>, expected: &PublicKey) -> Result<StaticSecret, &'static str> {
+        if parts.iter().any(|part| part.data().len() != 8) {
+            return Err("wrong key length");
+        }
+        let words = sensitive(self.reconstruct_words(parts)?);
+        let mut bytes = sensitive([0u8; 32]);
+        bytes.copy_from_slice(sensitive(from_words(&words)).get(..32).ok_or("wrong key length")?);
+        let secret = StaticSecret::from(*bytes);
+        if bool::from(PublicKey::from(&secret).as_bytes().ct_eq(expected.as_bytes())) {
+            Ok(secret)
+        } else {
+            Err("public key mismatch")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_x25519_roundtrip() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let secret = StaticSecret::from([9u8; 32]);
+        let public = PublicKey::from(&secret);
+        let parts = hsss.share_x25519(&secret).unwrap();
+        let res = hsss.reconstruct_x25519(parts[2..7].to_vec(), &public).unwrap();
+        assert_eq!(res.to_bytes(), secret.to_bytes());
+
+        let other = PublicKey::from(&StaticSecret::from([1u8; 32]));
+        assert!(hsss.reconstruct_x25519(parts[2..7].to_vec(), &other).is_err());
+        assert!(hsss.reconstruct_x25519(parts[2..5].to_vec(), &public).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+//! Типизированные обёртки для симметричных ключей.
+//!
+//! Ключ фиксированной длины разбивается на блоки u32, каждый из которых разделяется схемой.
+//! При восстановлении проверяется число блоков в долях и значение проверки ключа (KCV),
+//! сравнение выполняется за постоянное время.
+use crate::scheme_impl::ChunkedPart;
+use crate::HadamardSSS;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+#[cfg(feature = "zeroize_memory")]
+use zeroize::{Zeroize, Zeroizing};
+
+/// Значение проверки ключа: префикс SHA-256 от типа и байтов ключа.
+pub type KeyCheckValue = [u8; 8];
+
+/// Общий интерфейс симметричных ключей фиксированной длины.
+pub trait SymmetricKey: Sized {
+    /// Длина ключа в байтах.
+    const LEN: usize;
+    /// Название алгоритма, участвующее в вычислении значения проверки ключа.
+    const NAME: &'static str;
+
+    /// Создание ключа из среза байтов с проверкой длины.
+    fn from_slice(bytes: &[u8]) -> Result<Self, &'static str>;
+
+    /// Байты ключа.
+    fn as_bytes(&self) -> &[u8];
+
+    /// Вычисление значения проверки ключа.
+    fn check_value(&self) -> KeyCheckValue {
+        let digest = Sha256::new()
+            .chain_update(b"hadamard_sss kcv")
+            .chain_update(Self::NAME)
+            .chain_update(self.as_bytes())
+            .finalize();
+        let mut kcv = [0u8; 8];
+        kcv.copy_from_slice(&digest[..8]);
+        kcv
+    }
+}
+
+/// Объявление обёртки над ключом заданной длины.
+macro_rules! symmetric_key {
+    ($(#[$doc:meta])* $name:ident, $len:expr, $alg:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        #[cfg_attr(feature = "zeroize_memory", derive(Zeroize))]
+        #[cfg_attr(feature = "zeroize_memory", zeroize(drop))]
+        pub struct $name([u8; $len]);
+
+        impl $name {
+            /// Создание ключа из массива байтов.
+            pub fn from(bytes: [u8; $len]) -> Self {
+                $name(bytes)
+            }
+        }
+
+        impl SymmetricKey for $name {
+            const LEN: usize = $len;
+            const NAME: &'static str = $alg;
+
+            fn from_slice(bytes: &[u8]) -> Result<Self, &'static str> {
+                let bytes: [u8; $len] = bytes.try_into().map_err(|_| "wrong key length")?;
+                Ok($name(bytes))
+            }
+
+            fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+        }
+    };
+}
+
+symmetric_key!(
+    /// 128-битный ключ AES.
+    Aes128Key, 16, "AES-128"
+);
+symmetric_key!(
+    /// 256-битный ключ AES.
+    Aes256Key, 32, "AES-256"
+);
+symmetric_key!(
+    /// 256-битный ключ ChaCha20.
+    ChaChaKey, 32, "ChaCha20"
+);
+symmetric_key!(
+    /// 256-битный мастер-ключ, из которого выводятся подключи, см. [crate::derivation].
+    MasterKey, 32, "HKDF-SHA256 master"
+);
+
+/// Промежуточный буфер с материалом ключа: с функцией `zeroize_memory` затирается при удалении.
+#[cfg(feature = "zeroize_memory")]
+pub(crate) fn sensitive<T: Zeroize>(value: T) -> Zeroizing<T> {
+    Zeroizing::new(value)
+}
+
+/// Промежуточный буфер с материалом ключа; без функции `zeroize_memory` не затирается.
+#[cfg(not(feature = "zeroize_memory"))]
+pub(crate) fn sensitive<T>(value: T) -> Sensitive<T> {
+    Sensitive(value)
+}
+
+/// Обёртка буфера, повторяющая интерфейс `Zeroizing` без затирания.
+#[cfg(not(feature = "zeroize_memory"))]
+pub(crate) struct Sensitive<T>(T);
+
+#[cfg(not(feature = "zeroize_memory"))]
+impl<T> std::ops::Deref for Sensitive<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(not(feature = "zeroize_memory"))]
+impl<T> std::ops::DerefMut for Sensitive<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// Упаковка байтов ключа, длина которого кратна 4, в блоки u32 (little-endian).
+pub(crate) fn to_words(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().expect("key length is a multiple of 4")))
+        .collect()
+}
+
+/// Распаковка блоков u32 обратно в байты.
+pub(crate) fn from_words(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+/// Упаковка байтов произвольной длины в блоки u32: первый блок хранит длину,
+/// последний блок дополняется нулями.
+pub(crate) fn pack_bytes(bytes: &[u8]) -> Result<Vec<u32>, &'static str> {
+    let len = u32::try_from(bytes.len()).map_err(|_| "secret is too long")?;
+    let mut words = vec![len];
+    for chunk in bytes.chunks(4) {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        words.push(u32::from_le_bytes(word));
+    }
+    Ok(words)
+}
+
+/// Распаковка байтов, упакованных [pack_bytes].
+pub(crate) fn unpack_bytes(words: &[u32]) -> Result<Vec<u8>, &'static str> {
+    let (&len, data) = words.split_first().ok_or("missing length block")?;
+    let len = len as usize;
+    if len.div_ceil(4) != data.len() {
+        return Err("length block does not match number of blocks");
+    }
+    let mut bytes = from_words(data);
+    bytes.truncate(len);
+    Ok(bytes)
+}
+
+/// Разделение и восстановление симметричных ключей.
+impl HadamardSSS {
+    /// Разделение симметричного ключа на доли.
+    /// Возвращает доли и значение проверки ключа, которое нужно сохранить для восстановления.
+    pub fn share_key<K: SymmetricKey>(&self, key: &K) -> Result<(Vec<ChunkedPart>, KeyCheckValue), &'static str> {
+        Ok((self.share_words(&sensitive(to_words(key.as_bytes())))?, key.check_value()))
+    }
+
+    /// Восстановление симметричного ключа по долям.
+    /// Возвращается ошибка, если число блоков в долях не соответствует длине ключа
+    /// или значение проверки восстановленного ключа не совпадает с ожидаемым.
+    pub fn reconstruct_key<K: SymmetricKey>(&self, parts: Vec<ChunkedPart>, expected: &KeyCheckValue) -> Result<K, &'static str> {
+        if parts.iter().any(|part| part.data().len() * 4 != K::LEN) {
+            return Err("wrong key length");
+        }
+        let words = sensitive(self.reconstruct_words(parts)?);
+        let key = K::from_slice(&sensitive(from_words(&words)))?;
+        if bool::from(key.check_value().ct_eq(expected)) {
+            Ok(key)
+        } else {
+            Err("key check value mismatch")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    fn scheme() -> HadamardSSS {
+        HadamardSSS::from(&matrix()).unwrap()
+    }
+
+    #[test]
+    fn test_key_roundtrip() {
+        let hsss = scheme();
+        let key = Aes256Key::from([7u8; 32]);
+        let (parts, kcv) = hsss.share_key(&key).unwrap();
+        let res: Aes256Key = hsss.reconstruct_key(parts[1..6].to_vec(), &kcv).unwrap();
+        assert_eq!(res.as_bytes(), key.as_bytes());
+
+        let key = Aes128Key::from_slice(&[1u8; 16]).unwrap();
+        let (parts, kcv) = hsss.share_key(&key).unwrap();
+        let res: Aes128Key = hsss.reconstruct_key(parts[0..5].to_vec(), &kcv).unwrap();
+        assert_eq!(res.as_bytes(), key.as_bytes());
+    }
+
+    #[test]
+    fn test_key_checks() {
+        let hsss = scheme();
+        assert!(Aes128Key::from_slice(&[0u8; 15]).is_err());
+        assert!(ChaChaKey::from_slice(&[0u8; 16]).is_err());
+
+        let key = ChaChaKey::from([3u8; 32]);
+        let (parts, kcv) = hsss.share_key(&key).unwrap();
+        assert!(hsss.reconstruct_key::<Aes128Key>(parts[0..5].to_vec(), &kcv).is_err());
+        // тот же материал, но другой тип ключа
+        assert!(hsss.reconstruct_key::<Aes256Key>(parts[0..5].to_vec(), &kcv).is_err());
+        assert!(hsss.reconstruct_key::<ChaChaKey>(parts[0..3].to_vec(), &kcv).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+//! Непрозрачные метки долей для систем хранения.
+//!
+//! Хранилищу нужен ключ, по которому доля находится, но номер строки и имя хранителя
+//! раскрывают структуру раздачи. Метка доли -- 16 байт HKDF-SHA256 от ключа дилера,
+//! идентификатора сеанса и номера строки: без ключа метки неотличимы от случайных
+//! и не связаны между собой, а дилер по метке восстанавливает строку.
+use crate::scheme_impl::Part;
+use crate::{HadamardSSS, SharingScheme};
+use hkdf::Hkdf;
+use rand::RngCore;
+use sha2::Sha256;
+
+/// Ключ дилера, из которого выводятся метки.
+pub type LabelKey = [u8; 32];
+
+/// Идентификатор сеанса раздачи.
+pub type SessionId = [u8; 16];
+
+/// Метка доли.
+pub type ShareLabel = [u8; 16];
+
+/// Доля вместе с её меткой.
+pub type LabeledPart = (ShareLabel, Part);
+
+/// Метка HKDF для меток долей.
+const LABEL: &[u8] = b"hadamard_sss share label";
+
+/// Метка доли с данным номером строки в данном сеансе.
+pub fn share_label(key: &LabelKey, session: &SessionId, number: usize) -> ShareLabel {
+    let mut out = [0u8; 16];
+    Hkdf::<Sha256>::new(Some(session), key)
+        .expand_multi_info(&[LABEL, &(number as u64).to_le_bytes()], &mut out)
+        .expect("16 bytes is a valid HKDF output length");
+    out
+}
+
+/// Доли с метками.
+impl HadamardSSS {
+    /// Разделение секрета с метками долей в новом сеансе. Возвращаются пары (метка, доля)
+    /// и идентификатор сеанса, который дилер хранит вместе с ключом.
+    pub fn share_labeled(&self, secret: u32, key: &LabelKey) -> Result<(Vec<LabeledPart>, SessionId), &'static str> {
+        let mut session = [0u8; 16];
+        crate::entropy::rng().fill_bytes(&mut session);
+        let parts = self.share(secret)?;
+        let labeled = parts.into_iter().map(|part| (share_label(key, &session, part.number()), part)).collect();
+        Ok((labeled, session))
+    }
+
+    /// Номер строки, которой принадлежит метка, или `None`, если метка не из этого сеанса.
+    pub fn label_row(&self, label: &ShareLabel, key: &LabelKey, session: &SessionId) -> Option<usize> {
+        (0..self.hss.mtx_len()).find(|&number| share_label(key, session, number) == *label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_labels() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let key = [7u8; 32];
+        let (labeled, session) = hsss.share_labeled(314159265, &key).unwrap();
+        let mut labels: Vec<ShareLabel> = labeled.iter().map(|(label, _)| *label).collect();
+        for (label, part) in &labeled {
+            assert_eq!(hsss.label_row(label, &key, &session), Some(part.number()));
+            assert_eq!(hsss.label_row(label, &[8u8; 32], &session), None);
+        }
+        labels.sort_unstable();
+        labels.dedup();
+        assert_eq!(labels.len(), 7);
+
+        let (_, other) = hsss.share_labeled(1, &key).unwrap();
+        assert_ne!(share_label(&key, &session, 0), share_label(&key, &other, 0));
+        let parts: Vec<Part> = labeled.into_iter().map(|(_, part)| part).collect();
+        assert_eq!(hsss.reconstruct(parts).unwrap(), 314159265);
+    }
+}
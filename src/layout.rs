@@ -0,0 +1,224 @@
+//! Модуль, описывающий раскладку битов секрета по столбцам матрицы инцидентности.
+//!
+//! Каждый бит секрета закрепляется за одним столбцом матрицы инцидентности.
+//! Участник знает бит секрета, если в его строке в соответствующем столбце стоит 1.
+//! Сначала [ColumnSelection] определяет набор задействованных столбцов, затем
+//! [BitMapping] распределяет по ним биты секрета.
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+/// Стратегия выбора столбцов, по которым раскладываются биты секрета,
+/// когда порядок матрицы инцидентности больше разрядности секрета.
+///
+/// Если порядок не превосходит разрядности, используются все столбцы,
+/// и биты раскладываются по ним циклически.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub enum ColumnSelection {
+    /// Используются первые столбцы матрицы (поведение по умолчанию).
+    #[default]
+    Leading,
+    /// Используются столбцы, равномерно распределённые по всей ширине матрицы.
+    Spread,
+    /// Явно заданный список столбцов: i-й бит секрета закрепляется за i-м столбцом списка.
+    /// Список должен содержать ровно столько различных столбцов, сколько бит в секрете.
+    Explicit(Vec<usize>),
+}
+
+/// Стратегия распределения битов секрета по задействованным столбцам.
+///
+/// От неё зависит, какие именно биты секрета узнаёт каждая коалиция участников.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub enum BitMapping {
+    /// Биты раскладываются по столбцам по кругу: bit -> bit mod k (поведение по умолчанию).
+    #[default]
+    Interleaved,
+    /// Каждому столбцу достаётся непрерывный отрезок битов: bit -> bit * k / width.
+    Sequential,
+    /// Биты предварительно переставляются перестановкой, порождённой ChaCha20 по данному зерну,
+    /// после чего раскладываются по кругу.
+    Permuted(u64),
+}
+
+/// Порядок значимости битов секрета при раскладке по столбцам.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BitOrder {
+    /// Раскладка начинается с младшего бита (поведение по умолчанию).
+    #[default]
+    LsbFirst,
+    /// Раскладка начинается со старшего бита, как в комбинаторах, обрабатывающих биты
+    /// от старшего к младшему: столбец, которому при [BitOrder::LsbFirst] достался бит i,
+    /// получает бит 31 - i.
+    MsbFirst,
+}
+
+/// Порядок байтов значения доли при передаче в байтовом виде.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ByteOrder {
+    /// От младшего байта к старшему (поведение по умолчанию).
+    #[default]
+    Little,
+    /// От старшего байта к младшему.
+    Big,
+}
+
+/// Преобразование значения доли.
+impl ByteOrder {
+    /// Значение доли в байтовом виде.
+    pub fn encode(self, data: u32) -> [u8; 4] {
+        match self {
+            ByteOrder::Little => data.to_le_bytes(),
+            ByteOrder::Big => data.to_be_bytes(),
+        }
+    }
+
+    /// Значение доли по байтовому виду.
+    pub fn decode(self, bytes: [u8; 4]) -> u32 {
+        match self {
+            ByteOrder::Little => u32::from_le_bytes(bytes),
+            ByteOrder::Big => u32::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// Раскладка битов секрета по столбцам матрицы инцидентности.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Layout {
+    /// Стратегия выбора столбцов.
+    pub columns: ColumnSelection,
+    /// Стратегия распределения битов по выбранным столбцам.
+    pub mapping: BitMapping,
+    /// Порядок значимости битов.
+    pub bit_order: BitOrder,
+    /// Порядок байтов значения доли.
+    pub byte_order: ByteOrder,
+}
+
+impl Layout {
+    /// Вычисление столбца для каждого бита секрета.
+    ///
+    /// `order` -- порядок матрицы инцидентности, `width` -- разрядность секрета.
+    /// Возвращается ошибка, если конфигурация неоднозначна:
+    /// - явный список столбцов задан для матрицы, порядок которой не больше разрядности секрета
+    ///   (в этом случае используются все столбцы и выбирать нечего);
+    /// - явный список содержит неверное число столбцов, повторы или несуществующие столбцы.
+    pub fn columns(&self, order: usize, width: usize) -> Result<Vec<usize>, &'static str> {
+        let active = self.active_columns(order, width)?;
+        let k = active.len();
+        let slots: Vec<usize> = match self.mapping {
+            BitMapping::Interleaved => (0..width).map(|b| b % k).collect(),
+            BitMapping::Sequential => (0..width).map(|b| b * k / width).collect(),
+            BitMapping::Permuted(seed) => {
+                let mut perm: Vec<usize> = (0..width).collect();
+                perm.shuffle(&mut ChaCha20Rng::seed_from_u64(seed));
+                perm.iter().map(|b| b % k).collect()
+            }
+        };
+        let mut columns: Vec<usize> = slots.iter().map(|&slot| active[slot]).collect();
+        if self.bit_order == BitOrder::MsbFirst {
+            columns.reverse();
+        }
+        Ok(columns)
+    }
+
+    /// Вычисление набора задействованных столбцов согласно [ColumnSelection].
+    fn active_columns(&self, order: usize, width: usize) -> Result<Vec<usize>, &'static str> {
+        if order == 0 {
+            return Err("empty incidence matrix");
+        }
+        if order <= width {
+            return match self.columns {
+                ColumnSelection::Explicit(_) => Err("explicit columns require matrix order greater than secret width"),
+                _ => Ok((0..order).collect()),
+            };
+        }
+        match &self.columns {
+            ColumnSelection::Leading => Ok((0..width).collect()),
+            ColumnSelection::Spread => Ok((0..width).map(|b| b * order / width).collect()),
+            ColumnSelection::Explicit(cols) => {
+                if cols.len() != width {
+                    return Err("explicit columns must cover every bit of the secret");
+                }
+                let mut used = vec![false; order];
+                for &col in cols {
+                    if col >= order || used[col] {
+                        return Err("explicit columns must be distinct columns of the matrix");
+                    }
+                    used[col] = true;
+                }
+                Ok(cols.clone())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_order_cycles() {
+        let layout = Layout::default();
+        let cols = layout.columns(7, 32).unwrap();
+        assert_eq!(cols[0..9], [0, 1, 2, 3, 4, 5, 6, 0, 1]);
+        let spread = Layout { columns: ColumnSelection::Spread, ..Default::default() };
+        assert_eq!(spread.columns(7, 32).unwrap(), cols);
+        let explicit = Layout { columns: ColumnSelection::Explicit((0..32).collect()), ..Default::default() };
+        assert!(explicit.columns(7, 32).is_err());
+    }
+
+    #[test]
+    fn test_large_order() {
+        assert_eq!(Layout::default().columns(63, 32).unwrap(), (0..32).collect::<Vec<_>>());
+        let spread = Layout { columns: ColumnSelection::Spread, ..Default::default() }.columns(63, 32).unwrap();
+        assert_eq!(spread[0], 0);
+        assert_eq!(spread[31], 61);
+        assert!(spread.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_explicit_rejects_ambiguous() {
+        let explicit = |cols: Vec<usize>| {
+            Layout { columns: ColumnSelection::Explicit(cols), ..Default::default() }.columns(63, 32)
+        };
+        assert!(explicit((10..42).collect()).is_ok());
+        assert!(explicit((10..41).collect()).is_err());
+        let mut dup: Vec<usize> = (0..32).collect();
+        dup[5] = 4;
+        assert!(explicit(dup).is_err());
+        let mut out: Vec<usize> = (0..32).collect();
+        out[0] = 63;
+        assert!(explicit(out).is_err());
+    }
+
+    #[test]
+    fn test_mappings() {
+        let layout = |mapping| Layout { mapping, ..Default::default() };
+        let sequential = layout(BitMapping::Sequential).columns(7, 32).unwrap();
+        assert_eq!(sequential[0..6], [0, 0, 0, 0, 0, 1]);
+        assert_eq!(sequential[31], 6);
+        assert!(sequential.windows(2).all(|w| w[0] <= w[1]));
+
+        let permuted = layout(BitMapping::Permuted(42)).columns(7, 32).unwrap();
+        assert_eq!(permuted, layout(BitMapping::Permuted(42)).columns(7, 32).unwrap());
+        assert_ne!(permuted, layout(BitMapping::Permuted(43)).columns(7, 32).unwrap());
+        for col in 0..7 {
+            let sequential = Layout::default().columns(7, 32).unwrap();
+            let count = |cols: &Vec<usize>| cols.iter().filter(|&&c| c == col).count();
+            assert_eq!(count(&permuted), count(&sequential));
+        }
+    }
+
+    #[test]
+    fn test_bit_and_byte_order() {
+        let msb = Layout { bit_order: BitOrder::MsbFirst, ..Default::default() };
+        let mut reversed = Layout::default().columns(7, 32).unwrap();
+        reversed.reverse();
+        assert_eq!(msb.columns(7, 32).unwrap(), reversed);
+        assert_eq!(msb.columns(63, 32).unwrap()[0], 31);
+
+        assert_eq!(ByteOrder::Big.encode(0x01020304), [1, 2, 3, 4]);
+        assert_eq!(ByteOrder::default().encode(0x01020304), [4, 3, 2, 1]);
+        assert_eq!(ByteOrder::Big.decode(ByteOrder::Big.encode(314159265)), 314159265);
+    }
+}
@@ -0,0 +1,195 @@
+//! Журнал жизненного цикла долей.
+//!
+//! [ShareLedger] -- журнал только для добавления: выдача, обновление, отзыв доли и её
+//! использование при восстановлении записываются по номеру доли. Как и события
+//! [crate::emergency], записи связаны цепочкой SHA-256, так что удаление, изменение или
+//! перестановка записей обнаруживаются [ShareLedger::verify]. Для хранения журнал выгружается
+//! через [ShareLedger::entries] и загружается обратно [ShareLedger::from_entries] с проверкой цепочки.
+use crate::scheme_impl::Part;
+use sha2::{Digest, Sha256};
+
+/// Событие жизненного цикла доли.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LedgerAction {
+    /// Доля выдана хранителю.
+    Issued,
+    /// Доля заменена при обновлении.
+    Refreshed,
+    /// Доля отозвана.
+    Revoked,
+    /// Доля предъявлена для восстановления.
+    Used,
+}
+
+impl LedgerAction {
+    /// Код события в дайджесте.
+    fn code(self) -> u8 {
+        match self {
+            LedgerAction::Issued => 0,
+            LedgerAction::Refreshed => 1,
+            LedgerAction::Revoked => 2,
+            LedgerAction::Used => 3,
+        }
+    }
+}
+
+/// Запись журнала.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedgerEntry {
+    /// Порядковый номер записи, начиная с 0.
+    pub sequence: u64,
+    /// Время события в секундах от начала эпохи UNIX.
+    pub timestamp: u64,
+    /// Номер доли.
+    pub number: usize,
+    /// Событие.
+    pub action: LedgerAction,
+    /// Дайджест предыдущей записи (нули для первой).
+    pub previous: [u8; 32],
+    /// Дайджест записи.
+    pub digest: [u8; 32],
+}
+
+/// Вычисление дайджеста записи по её полям.
+fn entry_digest(sequence: u64, timestamp: u64, number: usize, action: LedgerAction, previous: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"hadamard_sss ledger");
+    hasher.update(previous);
+    hasher.update(sequence.to_le_bytes());
+    hasher.update(timestamp.to_le_bytes());
+    hasher.update((number as u64).to_le_bytes());
+    hasher.update([action.code()]);
+    hasher.finalize().into()
+}
+
+/// Журнал жизненного цикла долей.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShareLedger {
+    /// Записи журнала.
+    entries: Vec<LedgerEntry>,
+}
+
+/// Реализация методов журнала.
+impl ShareLedger {
+    /// Пустой журнал.
+    pub fn new() -> Self {
+        ShareLedger::default()
+    }
+
+    /// Загрузка сохранённых записей; возвращается ошибка, если цепочка нарушена.
+    pub fn from_entries(entries: Vec<LedgerEntry>) -> Result<Self, &'static str> {
+        let ledger = ShareLedger { entries };
+        if ledger.verify() { Ok(ledger) } else { Err("ledger chain is broken") }
+    }
+
+    /// Возвращение записей журнала.
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    /// Добавление записи. Отозванную долю можно только выдать заново.
+    pub fn append(&mut self, number: usize, action: LedgerAction, timestamp: u64) -> Result<&LedgerEntry, &'static str> {
+        if action != LedgerAction::Issued && self.is_revoked(number) {
+            return Err("share is revoked");
+        }
+        let (sequence, previous) = self.entries.last().map_or((0, [0; 32]), |last| (last.sequence + 1, last.digest));
+        let digest = entry_digest(sequence, timestamp, number, action, &previous);
+        self.entries.push(LedgerEntry { sequence, timestamp, number, action, previous, digest });
+        Ok(&self.entries[self.entries.len() - 1])
+    }
+
+    /// Запись одного события для всех долей набора, например использования при восстановлении.
+    /// Если хотя бы одна доля отозвана, журнал не меняется.
+    pub fn append_parts(&mut self, parts: &[Part], action: LedgerAction, timestamp: u64) -> Result<(), &'static str> {
+        if action != LedgerAction::Issued && parts.iter().any(|part| self.is_revoked(part.number())) {
+            return Err("share is revoked");
+        }
+        for part in parts {
+            self.append(part.number(), action, timestamp)?;
+        }
+        Ok(())
+    }
+
+    /// Записи о доле с данным номером.
+    pub fn history(&self, number: usize) -> impl Iterator<Item = &LedgerEntry> {
+        self.entries.iter().filter(move |entry| entry.number == number)
+    }
+
+    /// Последнее событие доли с данным номером.
+    pub fn status(&self, number: usize) -> Option<LedgerAction> {
+        self.history(number).last().map(|entry| entry.action)
+    }
+
+    /// Отозвана ли доля с данным номером.
+    pub fn is_revoked(&self, number: usize) -> bool {
+        self.status(number) == Some(LedgerAction::Revoked)
+    }
+
+    /// Проверка журнала: номера записей идут подряд с нуля, каждая запись ссылается
+    /// на дайджест предыдущей и её собственный дайджест соответствует полям.
+    pub fn verify(&self) -> bool {
+        let mut previous = [0u8; 32];
+        for (sequence, entry) in self.entries.iter().enumerate() {
+            if entry.sequence != sequence as u64
+                || entry.previous != previous
+                || entry.digest != entry_digest(entry.sequence, entry.timestamp, entry.number, entry.action, &entry.previous)
+            {
+                return false;
+            }
+            previous = entry.digest;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::{HadamardSSS, SharingScheme};
+
+    #[test]
+    fn test_lifecycle() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let mut ledger = ShareLedger::new();
+        ledger.append_parts(&parts, LedgerAction::Issued, 100).unwrap();
+        ledger.append_parts(&parts[0..5], LedgerAction::Used, 200).unwrap();
+        ledger.append(3, LedgerAction::Revoked, 300).unwrap();
+        assert_eq!(ledger.append(3, LedgerAction::Used, 400).err(), Some("share is revoked"));
+        assert_eq!(ledger.append_parts(&parts[2..7], LedgerAction::Used, 400), Err("share is revoked"));
+        assert_eq!(ledger.entries().len(), 13);
+        ledger.append(3, LedgerAction::Issued, 500).unwrap();
+
+        let actions: Vec<LedgerAction> = ledger.history(3).map(|entry| entry.action).collect();
+        assert_eq!(actions, [LedgerAction::Issued, LedgerAction::Used, LedgerAction::Revoked, LedgerAction::Issued]);
+        assert_eq!(ledger.status(6), Some(LedgerAction::Issued));
+        assert_eq!(ledger.status(7), None);
+        assert!(ledger.verify());
+        assert_eq!(ShareLedger::from_entries(ledger.entries().to_vec()), Ok(ledger));
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        let mut ledger = ShareLedger::new();
+        for number in 0..4 {
+            ledger.append(number, LedgerAction::Issued, 100).unwrap();
+        }
+        ledger.append(1, LedgerAction::Revoked, 200).unwrap();
+        let entries = ledger.entries().to_vec();
+
+        // отброшенный хвост цепочкой не обнаруживается: длину журнала нужно хранить отдельно
+        let mut removed = entries.clone();
+        removed.remove(4);
+        assert!(ShareLedger::from_entries(removed).is_ok());
+        let mut removed = entries.clone();
+        removed.remove(2);
+        assert!(ShareLedger::from_entries(removed).is_err());
+        let mut altered = entries.clone();
+        altered[4].action = LedgerAction::Refreshed;
+        assert!(ShareLedger::from_entries(altered).is_err());
+        let mut swapped = entries;
+        swapped.swap(0, 1);
+        assert_eq!(ShareLedger::from_entries(swapped).err(), Some("ledger chain is broken"));
+    }
+}
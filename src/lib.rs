@@ -4,40 +4,219 @@
 //! на основе матриц Адамара.
 //!
 //! Если кратко, реализованы методы трейта [scheme_traits::SharingScheme]:
-//! ```
+//! ```ignore
 //! fn share(&self, secret: Self::SecretType) -> Result<Vec<Self::PartType>, Self::Error>;
 //! fn reconstruct(&self, shares: Vec<Self::PartType>) -> Result<Self::SecretType, Self::Error>;
-//! fn validate(&self, shares: Vec<Self::PartType>) -> Vec<Self::PartType>;
+//! fn validate(&self, shares: Vec<Self::PartType>) -> ValidationOutcome;
 //! ```
+//!
+//! Основные типы собраны в модуле [prelude].
 #[macro_use]
 extern crate ndarray;
 mod scheme_impl;
 pub mod scheme_traits;
-mod hadamard_matrix;
-use hadamard_matrix::HadamardMatrix;
-use scheme_impl::{HSS, Part};
+pub mod hadamard_matrix;
+pub mod layout;
+pub mod expansion;
+pub mod keys;
+pub mod interop;
+pub mod watermark;
+pub mod decoys;
+pub mod analysis;
+pub mod equivalence;
+pub mod design;
+pub mod fixtures;
+pub mod spec;
+pub mod voice;
+pub mod manifest;
+pub mod emergency;
+pub mod validation;
+pub mod entropy;
+pub mod small;
+pub mod context;
+pub mod consistency;
+pub mod robust;
+pub mod request;
+pub mod completion;
+pub mod assignment;
+pub mod labels;
+pub mod dual;
+pub mod canonical;
+pub mod decode;
+pub mod session;
+pub mod shares;
+pub mod refresh;
+pub mod ledger;
+pub mod annotated;
+pub mod passphrase;
+pub mod derivation;
+pub mod conversion;
+pub mod secret;
+pub mod construction;
+pub mod general;
+pub mod weighted;
+pub mod registry;
+pub mod wire;
+pub mod bech32;
+pub mod mnemonic;
+pub mod group_mnemonic;
+pub mod persist;
+pub mod prelude;
+#[cfg(feature = "encryption")]
+pub mod encrypted;
+#[cfg(feature = "proofs")]
+pub mod proofs;
+#[cfg(feature = "test-utils")]
+pub mod testing;
+#[cfg(feature = "test-utils")]
+pub mod conformance;
+#[cfg(feature = "matrices")]
+pub mod matrices;
+pub use hadamard_matrix::HadamardMatrix;
+use scheme_impl::HSS;
+pub use scheme_impl::{Part, ChunkedPart, ParticipantRows};
+use layout::Layout;
+use emergency::Emergency;
 pub use crate::scheme_traits::SharingScheme;
+pub use validation::ValidationOutcome;
 use anyhow::Result;
-use ndarray::{arr2, Array2};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use ndarray::Array2;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Обработчик, вызываемый при предъявлении долей-канареек; получает номера предъявленных канареек.
+pub type CanaryHook = Box<dyn Fn(&[usize]) + Send + Sync>;
 
 /// Основная структура
 pub struct HadamardSSS {
+    /// нормализованная матрица Адамара, по которой построена схема
+    matrix: HadamardMatrix,
     /// внутренняя структура реализующая схему разделения секрета
     hss: HSS,
     /// пороговое значение для матрицы Адамара, переданной в структуру
     threshold: usize,
+    /// номера долей-канареек
+    canaries: Vec<usize>,
+    /// обработчик предъявления долей-канареек
+    canary_hook: Option<CanaryHook>,
+    /// настройки аварийного восстановления с пониженным порогом
+    emergency: Option<Emergency>,
 }
 
 /// Реализация структуры
 impl HadamardSSS {
     /// Создание экземпляра структуры по данной матрице Адамара
     pub fn from(mtx: &Array2<i32>) -> Result<Self, &'static str> {
-        let mut had = HadamardMatrix::from(&mtx).expect("Error! ");
-        let incidence_mtx = had.normalize().get_incidence();
-        Ok(HadamardSSS {
-            hss: HSS::from(&incidence_mtx),
-            threshold: HadamardSSS::get_threshold(&incidence_mtx),
-        })
+        let matrix = HadamardSSS::normalized(mtx)?;
+        let hss = HSS::from(&matrix.get_incidence())?;
+        Ok(HadamardSSS::assemble(hss, matrix))
+    }
+
+    /// Создание экземпляра структуры по уже проверенной матрице Адамара.
+    /// Матрица не обязана быть нормализованной: схема строится по её нормализованной копии.
+    /// Возвращается ошибка, если порядок матрицы не кратен 4 (то есть равен 1 или 2).
+    pub fn from_matrix(mtx: &HadamardMatrix) -> Result<Self, &'static str> {
+        let matrix = mtx.normalized();
+        let hss = HSS::from(&matrix.get_incidence())?;
+        Ok(HadamardSSS::assemble(hss, matrix))
+    }
+
+    /// Создание экземпляра структуры по матрице Адамара данного порядка, которую строят
+    /// построения модуля [construction] (Сильвестра, Пэли, Уильямсона, произведение Кронекера).
+    /// Схема имеет order - 1 долей и порог order / 2 + 1. Возвращается ошибка, если порядок
+    /// меньше 4 или ни одно построение его не достигает.
+    pub fn of_order(order: usize) -> Result<Self, &'static str> {
+        let matrix = construction::construct(order).ok_or("no Hadamard matrix construction for that order")?;
+        HadamardSSS::from_matrix(&matrix)
+    }
+
+    /// Схема наименьшего доступного порядка, дающая не меньше parties долей.
+    /// Возвращаются схема, её порог и число долей.
+    pub fn for_participants(parties: usize) -> Result<(Self, usize, usize), &'static str> {
+        HadamardSSS::smallest(|order| order > parties)
+    }
+
+    /// Схема наименьшего доступного порядка с порогом не меньше threshold.
+    /// Возвращаются схема, её порог и число долей.
+    pub fn for_threshold(threshold: usize) -> Result<(Self, usize, usize), &'static str> {
+        HadamardSSS::smallest(|order| order / 2 + 1 >= threshold)
+    }
+
+    /// Схема наименьшего порядка, доступного построениям и удовлетворяющего условию.
+    fn smallest(fits: impl Fn(usize) -> bool) -> Result<(Self, usize, usize), &'static str> {
+        let hsss = (4..=construction::MAX_CONSTRUCTED_ORDER)
+            .step_by(4)
+            .filter(|&order| fits(order))
+            .find_map(|order| HadamardSSS::of_order(order).ok())
+            .ok_or("no supported Hadamard matrix order is large enough")?;
+        let (threshold, shares) = (hsss.threshold, hsss.hss.mtx_len());
+        Ok((hsss, threshold, shares))
+    }
+
+    /// Создание экземпляра структуры по данной матрице Адамара и раскладке битов секрета
+    /// по столбцам матрицы инцидентности.
+    ///
+    /// Раскладка важна, когда порядок матрицы больше разрядности секрета:
+    /// тогда часть столбцов не используется, и [layout::ColumnSelection] определяет, какие именно.
+    /// [layout::BitMapping] определяет, какой бит секрета попадает в какой столбец.
+    /// [layout::BitOrder] и [layout::ByteOrder] согласуют порядок битов и байтов доли
+    /// с аппаратными реализациями.
+    /// Неоднозначные раскладки отклоняются с ошибкой.
+    pub fn with_layout(mtx: &Array2<i32>, layout: &Layout) -> Result<Self, &'static str> {
+        let matrix = HadamardSSS::normalized(mtx)?;
+        let hss = HSS::with_layout(&matrix.get_incidence(), layout)?;
+        Ok(HadamardSSS::assemble(hss, matrix))
+    }
+
+    /// Отпечаток схемы, которым помечаются её доли: первые 4 байта SHA-256
+    /// нормализованной матрицы инцидентности, столбцов битов секрета и порядка байтов.
+    pub fn fingerprint(&self) -> u32 {
+        self.hss.fingerprint()
+    }
+
+    /// Значение доли в байтовом виде с порядком байтов раскладки схемы.
+    pub fn data_bytes(&self, part: &Part) -> [u8; 4] {
+        self.hss.byte_order().encode(part.data())
+    }
+
+    /// Доля с данным номером по значению в байтовом виде с порядком байтов раскладки схемы.
+    pub fn part_from_bytes(&self, number: usize, bytes: [u8; 4]) -> Result<Part, &'static str> {
+        if number >= self.hss.mtx_len() {
+            return Err("scheme mismatch: part number exceeds the order of the scheme");
+        }
+        Ok(Part::from(number, self.hss.byte_order().decode(bytes)).with_fingerprint(self.fingerprint()))
+    }
+
+    /// Сборка структуры по внутренней схеме и нормализованной матрице Адамара
+    fn assemble(hss: HSS, matrix: HadamardMatrix) -> Self {
+        HadamardSSS {
+            threshold: HadamardSSS::get_threshold(&matrix.get_incidence()),
+            matrix,
+            hss,
+            canaries: Vec::new(),
+            canary_hook: None,
+            emergency: None,
+        }
+    }
+
+    /// Проверка и нормализация данной матрицы Адамара
+    fn normalized(mtx: &Array2<i32>) -> Result<HadamardMatrix, &'static str> {
+        let mut matrix = HadamardMatrix::from(mtx)?;
+        matrix.normalize();
+        Ok(matrix)
+    }
+
+    /// Разрешение принимать доли без отпечатка схемы, например прочитанные из формата версии 1.
+    /// По умолчанию такие доли считаются чужими.
+    pub fn set_accept_legacy(&mut self, accept: bool) {
+        self.hss.set_accept_legacy(accept);
+    }
+
+    /// Нормализованная матрица Адамара, по которой построена схема
+    pub fn matrix(&self) -> &HadamardMatrix {
+        &self.matrix
     }
 
     /// Возвращение порогового числа участников, необходимого для восстановления секрета
@@ -48,7 +227,88 @@ impl HadamardSSS {
 
     /// Проверка, образуют ли представленные доли валидный набор для восстановления секрета
     pub fn is_valid(&self, parts: Vec<Part>) -> bool {
-        self.hss.validate(parts).is_empty()
+        self.validate(parts).suspicious().is_empty()
+    }
+
+    /// Назначение долей с данными номерами канарейками. Если хотя бы одна из них будет
+    /// предъявлена для восстановления или проверки, вызывается hook с номерами предъявленных
+    /// канареек. Результат восстановления и проверки от этого не меняется, так что по нему
+    /// нельзя понять, какие доли являются канарейками.
+    pub fn set_canaries(&mut self, numbers: Vec<usize>, hook: impl Fn(&[usize]) + Send + Sync + 'static) {
+        self.canaries = numbers;
+        self.canary_hook = Some(Box::new(hook));
+    }
+
+    /// Вызов обработчика, если среди долей есть канарейки
+    fn check_canaries(&self, parts: &[Part]) {
+        if let Some(hook) = &self.canary_hook {
+            let presented: Vec<usize> = parts
+                .iter()
+                .map(|part| part.number())
+                .filter(|number| self.canaries.contains(number))
+                .collect();
+            if !presented.is_empty() {
+                hook(&presented);
+            }
+        }
+    }
+
+    /// Восстановление с ослеплением: возвращается secret ⊕ mask. Маска применяется к долям
+    /// до сборки, поэтому открытый секрет не появляется ни в результате, ни в памяти
+    /// полудоверенного сервиса сборки; пользователь снимает маску у себя. Если сервис не должен
+    /// видеть и сами доли, участники ослепляют их через [Part::blinded] той же маской
+    /// и передают для обычного [SharingScheme::reconstruct].
+    pub fn reconstruct_blinded(&self, parts: Vec<Part>, mask: u32) -> Result<u32, &'static str> {
+        self.reconstruct(parts.iter().map(|part| part.blinded(mask)).collect())
+    }
+
+    /// Детерминированное разделение секрета: биты-заполнители берутся из ChaCha20,
+    /// инициализированного данным зерном (по одному слову u32 на долю в порядке номеров).
+    /// Одинаковые секрет и зерно дают одинаковые доли, поэтому зерно должно храниться так же,
+    /// как секрет, и не повторяться для разных секретов.
+    pub fn share_seeded(&self, secret: u32, seed: [u8; 32]) -> Result<Vec<Part>, &'static str> {
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        Ok(self.hss.share_padded(secret, |_| rng.next_u32()))
+    }
+
+    /// Детерминированное разделение с солью: зерно ChaCha20 выводится HKDF-SHA256 из seed
+    /// и salt. Разделения одного секрета с одним зерном, но разными солями дают несвязанные
+    /// доли, так что по совпадению долей нельзя заключить о совпадении секретов.
+    /// Соль не секретна, но нужна для повторного получения тех же долей.
+    pub fn share_seeded_salted(&self, secret: u32, seed: [u8; 32], salt: [u8; 16]) -> Result<Vec<Part>, &'static str> {
+        let mut salted = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(&salt), &seed)
+            .expand(b"hadamard_sss salted seed", &mut salted)
+            .map_err(|_| "invalid HKDF output length")?;
+        self.share_seeded(secret, salted)
+    }
+
+    /// Разделение секрета, состоящего из нескольких блоков u32. Каждый блок разделяется
+    /// независимо, i-я доля содержит значения i-го участника по всем блокам.
+    pub fn share_words(&self, words: &[u32]) -> Result<Vec<ChunkedPart>, &'static str> {
+        let mut blocks: Vec<Vec<Part>> = Vec::with_capacity(words.len());
+        for &word in words {
+            blocks.push(self.share(word)?);
+        }
+        let n = self.hss.mtx_len();
+        Ok((0..n)
+            .map(|i| ChunkedPart::from(i, blocks.iter().map(|parts| parts[i].data()).collect()).with_fingerprint(self.fingerprint()))
+            .collect())
+    }
+
+    /// Восстановление секрета, разделённого с помощью [HadamardSSS::share_words].
+    /// Все доли должны содержать одинаковое число блоков.
+    pub fn reconstruct_words(&self, parts: Vec<ChunkedPart>) -> Result<Vec<u32>, &'static str> {
+        let len = parts.first().map_or(0, |part| part.data().len());
+        if parts.iter().any(|part| part.data().len() != len) {
+            return Err("parts have different number of blocks");
+        }
+        (0..len)
+            .map(|block| {
+                let blocks = parts.iter().map(|part| part.block(block).ok_or("parts have different number of blocks"));
+                self.reconstruct(blocks.collect::<Result<_, _>>()?)
+            })
+            .collect()
     }
 }
 
@@ -65,8 +325,8 @@ impl SharingScheme for HadamardSSS {
 
     /// Обёртка для share_impl::HSS::reconstruct с учётом количества пришёдших долей
     fn reconstruct(&self, parts: Vec<Self::PartType>) -> Result<Self::SecretType, &'static str> {
+        self.check_canaries(&parts);
         if parts.len() < self.threshold {
-            println!("{} is less than threshold {} parties", parts.len(), self.threshold);
             Err("less than threshold parties")
         } else {
             self.hss.reconstruct(parts)
@@ -74,7 +334,8 @@ impl SharingScheme for HadamardSSS {
     }
 
     /// Обёртка для share_impl::HSS::validate
-    fn validate(&self, parts: Vec<Part>) -> Vec<usize> {
+    fn validate(&self, parts: Vec<Part>) -> ValidationOutcome {
+        self.check_canaries(&parts);
         self.hss.validate(parts)
     }
 }
@@ -83,6 +344,7 @@ impl SharingScheme for HadamardSSS {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ndarray::arr2;
 
     #[test]
     fn test_secret_reconstruction() {
@@ -102,6 +364,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_from_unnormalized_matrix() {
+        let h_mtx = arr2(&[[-1, -1, -1, -1],
+                           [1, -1, 1, -1],
+                           [1, 1, -1, -1],
+                           [1, -1, -1, 1]]);
+        let had = HadamardMatrix::from(&h_mtx).unwrap();
+        let hsss = HadamardSSS::from_matrix(&had).unwrap();
+        assert!(!had.is_normalized());
+        assert!(hsss.matrix().is_normalized());
+        assert_eq!(hsss.matrix().order(), 4);
+        let parts = hsss.share(0xC0FFEE).unwrap();
+        assert_eq!(hsss.reconstruct(parts[0..3].to_vec()).unwrap(), 0xC0FFEE);
+        let same = HadamardSSS::from(&h_mtx).unwrap();
+        assert_eq!(same.reconstruct(parts).unwrap(), 0xC0FFEE);
+    }
+
     #[test]
     fn test_hsss_validate() {
         let h_mtx = arr2(&[[1, 1, 1, 1, 1, 1, 1, 1],
@@ -115,16 +394,208 @@ mod tests {
         let hsss = HadamardSSS::from(&h_mtx).unwrap();
         for secret in 0..100 {
             let res = hsss.share(secret).unwrap();
-            let valid = hsss.validate(res[0..5].to_vec()).is_empty();
+            let valid = hsss.validate(res[0..5].to_vec()).is_clean();
             let secret_res = hsss.reconstruct(res[0..5].to_vec()).unwrap();
             assert_eq!(valid, (secret == secret_res));
         }
         for secret in 0..100 {
             let mut res = hsss.share(secret).unwrap();
-            res[0] = Part::from(res[0].number(), res[0].data() ^ 43);
-            let valid = hsss.validate(res[0..5].to_vec()).is_empty();
+            res[0] = res[0].blinded(43);
+            let valid = hsss.validate(res[0..5].to_vec()).is_clean();
             let secret_res = hsss.reconstruct(res[0..5].to_vec()).unwrap();
             assert_eq!(valid, (secret == secret_res));
         }
     }
+
+    #[test]
+    fn test_large_order_layouts() {
+        let h_mtx = Array2::from_shape_fn((64, 64), |(i, j)| {
+            if (i & j).count_ones() % 2 == 0 { 1 } else { -1 }
+        });
+        let spread = Layout { columns: layout::ColumnSelection::Spread, ..Default::default() };
+        let permuted = Layout { mapping: layout::BitMapping::Permuted(7), ..spread.clone() };
+        for layout in [Layout::default(), spread, permuted] {
+            let hsss = HadamardSSS::with_layout(&h_mtx, &layout).unwrap();
+            assert_eq!(hsss.threshold, 33);
+            for secret in [0, 1, 314159265, u32::MAX] {
+                let res = hsss.share(secret).unwrap();
+                assert_eq!(hsss.reconstruct(res[30..63].to_vec()).unwrap(), secret);
+                assert!(hsss.validate(res[30..63].to_vec()).is_clean());
+            }
+        }
+        let explicit = Layout { columns: layout::ColumnSelection::Explicit(vec![0; 32]), ..Default::default() };
+        assert!(HadamardSSS::with_layout(&h_mtx, &explicit).is_err());
+    }
+
+    #[test]
+    fn test_mappings_roundtrip() {
+        let h_mtx = Array2::from_shape_fn((8, 8), |(i, j)| {
+            if (i & j).count_ones() % 2 == 0 { 1 } else { -1 }
+        });
+        for mapping in [layout::BitMapping::Interleaved, layout::BitMapping::Sequential, layout::BitMapping::Permuted(1)] {
+            let hsss = HadamardSSS::with_layout(&h_mtx, &Layout { mapping, ..Default::default() }).unwrap();
+            for secret in [0, 7, 314159265, u32::MAX] {
+                let res = hsss.share(secret).unwrap();
+                assert_eq!(hsss.reconstruct(res[2..7].to_vec()).unwrap(), secret);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hardware_bit_and_byte_order() {
+        let hardware = Layout { bit_order: layout::BitOrder::MsbFirst, byte_order: layout::ByteOrder::Big, ..Default::default() };
+        let msb = HadamardSSS::with_layout(&fixtures::matrix(), &hardware).unwrap();
+        let lsb = HadamardSSS::from(&fixtures::matrix()).unwrap();
+        for number in 0..7 {
+            assert_eq!(msb.hss.mask(number), lsb.hss.mask(number).reverse_bits());
+        }
+        let parts = msb.share(314159265).unwrap();
+        assert_eq!(msb.reconstruct(parts[2..7].to_vec()).unwrap(), 314159265);
+        assert_eq!(msb.data_bytes(&parts[0]), parts[0].data().to_be_bytes());
+        assert_eq!(lsb.data_bytes(&parts[0]), parts[0].data().to_le_bytes());
+        let decoded = msb.part_from_bytes(3, msb.data_bytes(&parts[3])).unwrap();
+        assert_eq!((decoded.number(), decoded.data()), (3, parts[3].data()));
+        assert!(msb.part_from_bytes(7, [0; 4]).is_err());
+    }
+
+    #[test]
+    fn test_of_order() {
+        for (order, threshold) in [(8, 5), (12, 7), (28, 15), (92, 47)] {
+            let hsss = HadamardSSS::of_order(order).unwrap();
+            assert_eq!((hsss.threshold, hsss.hss.mtx_len()), (threshold, order - 1));
+            let parts = hsss.share(314159265).unwrap();
+            assert_eq!(hsss.reconstruct(parts[0..threshold].to_vec()).unwrap(), 314159265);
+        }
+        assert!(HadamardSSS::of_order(2).is_err());
+        assert!(HadamardSSS::of_order(10).is_err());
+        assert!(HadamardSSS::of_order(156).is_err());
+    }
+
+    #[test]
+    fn test_for_participants_and_threshold() {
+        let shape = |res: Result<(HadamardSSS, usize, usize), &'static str>| res.map(|(_, threshold, shares)| (threshold, shares));
+        assert_eq!(shape(HadamardSSS::for_participants(1)), Ok((3, 3)));
+        assert_eq!(shape(HadamardSSS::for_participants(7)), Ok((5, 7)));
+        assert_eq!(shape(HadamardSSS::for_participants(8)), Ok((7, 11)));
+        assert_eq!(shape(HadamardSSS::for_participants(152)), Ok((81, 159)));
+        assert_eq!(shape(HadamardSSS::for_threshold(6)), Ok((7, 11)));
+        assert_eq!(shape(HadamardSSS::for_threshold(47)), Ok((47, 91)));
+        assert!(HadamardSSS::for_participants(5000).is_err());
+
+        let (hsss, threshold, _) = HadamardSSS::for_participants(10).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        assert_eq!(hsss.reconstruct(parts[0..threshold].to_vec()).unwrap(), 314159265);
+    }
+
+    #[test]
+    fn test_blinded_reconstruction() {
+        let hsss = HadamardSSS::from(&fixtures::matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let mask = 0xA5A5_5A5A;
+        assert_eq!(hsss.reconstruct_blinded(parts[1..6].to_vec(), mask).unwrap(), 314159265 ^ mask);
+        let blinded: Vec<Part> = parts[2..7].iter().map(|part| part.blinded(mask)).collect();
+        assert_eq!(hsss.reconstruct(blinded).unwrap() ^ mask, 314159265);
+        assert!(hsss.reconstruct_blinded(parts[0..4].to_vec(), mask).is_err());
+    }
+
+    #[test]
+    fn test_malformed_input_returns_errors() {
+        use crate::scheme_traits::SchemeExt;
+        assert!(HadamardSSS::from(&arr2(&[[1, 2], [3, 4]])).is_err());
+        assert!(HadamardSSS::from(&Array2::zeros((0, 0))).is_err());
+        assert!(HadamardSSS::from(&arr2(&[[1, 1], [1, -1]])).is_err());
+        assert!(HadamardSSS::from_matrix(&HadamardMatrix::from(&arr2(&[[1, 1], [1, -1]])).unwrap()).is_err());
+
+        let hsss = HadamardSSS::from(&fixtures::matrix()).unwrap();
+        assert_eq!(hsss.hss.mask(7), 0);
+        assert_eq!(hsss.hss.mask(usize::MAX), 0);
+        let parts = hsss.share(314159265).unwrap();
+        let hostile: Vec<Part> = (0..7).map(|i| Part::from(usize::MAX - i, u32::MAX)).collect();
+        let mut mixed = parts[0..3].to_vec();
+        mixed.extend_from_slice(&hostile[0..3]);
+        for set in [Vec::new(), hostile.clone(), mixed] {
+            assert!(hsss.reconstruct(set.clone()).is_err());
+            assert!(hsss.reconstruct_strict(set.clone()).is_err());
+            assert!(hsss.reconstruct_robust(set.clone()).is_err());
+            assert!(hsss.complete_shares(set.clone(), &[0]).is_err());
+            let _ = hsss.validate(set.clone());
+            let _ = hsss.validate_design(set.clone());
+            let _ = hsss.validate_with(set, &validation::ValidationPolicy::default());
+        }
+        assert!(hsss.complete_shares_from_secret(1, &[usize::MAX]).is_err());
+        let chunked = vec![ChunkedPart::from(0, vec![1, 2]), ChunkedPart::from(1, vec![1])];
+        assert!(hsss.reconstruct_words(chunked).is_err());
+        assert!(ChunkedPart::from(0, vec![1]).block(1).is_none());
+        let words = hsss.share_words(&[1, 2]).unwrap();
+        assert!(words.iter().all(|part| part.fingerprint() == Some(hsss.fingerprint())));
+    }
+
+    #[test]
+    fn test_salted_seeded_sharing() {
+        let hsss = HadamardSSS::from(&fixtures::matrix()).unwrap();
+        let data = |parts: Vec<Part>| parts.iter().map(|part| part.data()).collect::<Vec<u32>>();
+        let salted = data(hsss.share_seeded_salted(314159265, [7; 32], [1; 16]).unwrap());
+        assert_eq!(data(hsss.share_seeded_salted(314159265, [7; 32], [1; 16]).unwrap()), salted);
+        let other = data(hsss.share_seeded_salted(314159265, [7; 32], [2; 16]).unwrap());
+        assert_ne!(other, salted);
+        assert_ne!(data(hsss.share_seeded(314159265, [7; 32]).unwrap()), salted);
+        // совпадают только биты секрета, известные владельцу доли
+        let masks = hsss.masks();
+        assert!(salted.iter().zip(&other).zip(&masks).all(|((a, b), mask)| (a ^ b) & mask == 0));
+        assert!(salted.iter().zip(&other).zip(&masks).any(|((a, b), mask)| (a ^ b) & !mask != 0));
+        let parts = hsss.share_seeded_salted(314159265, [7; 32], [2; 16]).unwrap();
+        assert_eq!(hsss.reconstruct(parts[0..5].to_vec()).unwrap(), 314159265);
+    }
+
+    #[test]
+    fn test_fingerprint_mismatch() {
+        let hsss = HadamardSSS::from(&fixtures::matrix()).unwrap();
+        let mut swapped = fixtures::matrix();
+        for j in 0..8 {
+            swapped.swap([1, j], [2, j]);
+        }
+        let other = HadamardSSS::from(&swapped).unwrap();
+        assert_ne!(hsss.fingerprint(), other.fingerprint());
+        let msb = Layout { bit_order: layout::BitOrder::MsbFirst, ..Default::default() };
+        assert_ne!(HadamardSSS::with_layout(&fixtures::matrix(), &msb).unwrap().fingerprint(), hsss.fingerprint());
+        let big = Layout { byte_order: layout::ByteOrder::Big, ..Default::default() };
+        assert_ne!(HadamardSSS::with_layout(&fixtures::matrix(), &big).unwrap().fingerprint(), hsss.fingerprint());
+        assert_eq!(HadamardSSS::load(&hsss.save()).unwrap().fingerprint(), hsss.fingerprint());
+
+        let parts = hsss.share(314159265).unwrap();
+        assert!(parts.iter().all(|part| part.fingerprint() == Some(hsss.fingerprint())));
+        assert_eq!(other.reconstruct(parts[0..5].to_vec()).err(), Some("scheme mismatch: part fingerprint does not match the scheme"));
+        let mut mixed = other.share(314159265).unwrap()[0..5].to_vec();
+        mixed[2] = parts[2];
+        let outcome = other.validate(mixed);
+        assert_eq!(outcome.suspicious(), [2]);
+        assert!(outcome.verdicts()[0].is_foreign());
+        // доли без отпечатка принимаются только по явному разрешению
+        let bare: Vec<Part> = parts.iter().map(|part| Part::from(part.number(), part.data())).collect();
+        assert_eq!(hsss.reconstruct(bare[0..5].to_vec()).err(), Some("scheme mismatch: part has no fingerprint"));
+        assert!(hsss.validate(bare[0..5].to_vec()).verdicts()[0].is_foreign());
+        let mut legacy = HadamardSSS::from(&fixtures::matrix()).unwrap();
+        legacy.set_accept_legacy(true);
+        assert_eq!(legacy.reconstruct(bare[0..5].to_vec()).unwrap(), 314159265);
+        assert_eq!(legacy.validate(bare[0..5].to_vec()), ValidationOutcome::Clean);
+    }
+
+    #[test]
+    fn test_canaries() {
+        use std::sync::{Arc, Mutex};
+        let h_mtx = Array2::from_shape_fn((8, 8), |(i, j)| {
+            if (i & j).count_ones() % 2 == 0 { 1 } else { -1 }
+        });
+        let mut hsss = HadamardSSS::from(&h_mtx).unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        hsss.set_canaries(vec![1, 6], move |numbers| log.lock().unwrap().push(numbers.to_vec()));
+
+        let parts = hsss.share(1234).unwrap();
+        assert_eq!(hsss.reconstruct(parts[2..6].to_vec()), Err("less than threshold parties"));
+        assert!(seen.lock().unwrap().is_empty());
+        assert_eq!(hsss.reconstruct(parts[1..6].to_vec()).unwrap(), 1234);
+        assert!(hsss.validate(parts[2..7].to_vec()).is_clean());
+        assert_eq!(*seen.lock().unwrap(), vec![vec![1], vec![6]]);
+    }
 }
@@ -0,0 +1,504 @@
+//! Манифест набора долей для аудиторов.
+//!
+//! Манифест составляется при раздаче долей и перечисляет параметры схемы, время раздачи,
+//! хранителей долей и дайджесты самих долей. Позже по нему можно проверить, что
+//! предъявленные доли -- те самые, что были розданы.
+//!
+//! Доля содержит всего 32 бита, поэтому обычный хэш доли перебирается за секунды и раскрывает
+//! её. Поэтому каждая доля выдаётся хранителю вместе с раскрытием ([ManifestOpening]) --
+//! 32 случайными на вид байтами, которые дилер получает HKDF-SHA256 от своего ключа
+//! ([ManifestKey]), случайного значения манифеста и номера доли. Дайджест доли -- SHA-256 от
+//! номера, значения и раскрытия: без раскрытия он ничего не говорит о доле, а проверка
+//! [verify_manifest] по доле и её раскрытию не требует ключа дилера и доступна любому аудитору.
+use crate::scheme_impl::Part;
+use crate::HadamardSSS;
+use hkdf::Hkdf;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Ключ дилера, от которого вычисляются раскрытия долей.
+pub type ManifestKey = [u8; 32];
+
+/// Раскрытие доли: передаётся хранителю вместе с долей и предъявляется аудитору.
+pub type ManifestOpening = [u8; 32];
+
+/// Метка HKDF для раскрытий долей.
+const OPENING_LABEL: &[u8] = b"hadamard_sss manifest opening";
+
+/// Метка дайджестов долей.
+const LABEL: &[u8] = b"hadamard_sss manifest";
+
+/// Версия формата манифеста. В версии 1 дайджест вычислялся от ключа дилера.
+const VERSION: u64 = 2;
+
+/// Запись манифеста об одной доле.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Номер доли.
+    number: usize,
+    /// Метка хранителя доли.
+    custodian: String,
+    /// Дайджест доли.
+    digest: [u8; 32],
+}
+
+/// Реализация методов записи манифеста.
+impl ManifestEntry {
+    /// Сборка записи из прочитанных полей.
+    pub(crate) fn assemble(number: usize, custodian: String, digest: [u8; 32]) -> Self {
+        ManifestEntry { number, custodian, digest }
+    }
+
+    /// Возвращение значения поля number.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Возвращение значения поля custodian.
+    pub fn custodian(&self) -> &str {
+        &self.custodian
+    }
+
+    /// Возвращение значения поля digest.
+    pub fn digest(&self) -> [u8; 32] {
+        self.digest
+    }
+}
+
+/// Манифест набора долей.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareSetManifest {
+    /// Порядок матрицы Адамара схемы.
+    order: usize,
+    /// Пороговое число долей.
+    threshold: usize,
+    /// Время раздачи в секундах от начала эпохи UNIX.
+    created_at: u64,
+    /// Случайное значение манифеста.
+    nonce: [u8; 16],
+    /// Записи о долях.
+    entries: Vec<ManifestEntry>,
+    /// Соль распределения строк между хранителями, если оно выполнено [crate::assignment].
+    pub(crate) assignment_salt: Option<[u8; 16]>,
+}
+
+/// Вычисление раскрытия доли с данным номером.
+fn opening(key: &ManifestKey, nonce: &[u8; 16], number: usize) -> ManifestOpening {
+    let mut out = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(nonce), key)
+        .expand_multi_info(&[OPENING_LABEL, &(number as u64).to_le_bytes()], &mut out)
+        .expect("32 bytes is a valid HKDF output length");
+    out
+}
+
+/// Вычисление дайджеста доли по её раскрытию.
+fn digest(nonce: &[u8; 16], part: &Part, opening: &ManifestOpening) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(LABEL);
+    hasher.update(nonce);
+    hasher.update((part.number() as u64).to_le_bytes());
+    hasher.update(part.data().to_le_bytes());
+    hasher.update(opening);
+    hasher.finalize().into()
+}
+
+/// Реализация методов манифеста.
+impl ShareSetManifest {
+    /// Сборка манифеста из прочитанных полей.
+    pub(crate) fn assemble(order: usize, threshold: usize, created_at: u64, nonce: [u8; 16], entries: Vec<ManifestEntry>,
+                           assignment_salt: Option<[u8; 16]>) -> Self {
+        ShareSetManifest { order, threshold, created_at, nonce, entries, assignment_salt }
+    }
+
+    /// Возвращение значения поля order.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Возвращение значения поля threshold.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Возвращение значения поля created_at.
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    /// Возвращение значения поля nonce.
+    pub fn nonce(&self) -> [u8; 16] {
+        self.nonce
+    }
+
+    /// Возвращение записей о долях.
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    /// Возвращение значения поля assignment_salt.
+    pub fn assignment_salt(&self) -> Option<[u8; 16]> {
+        self.assignment_salt
+    }
+
+    /// Раскрытие доли с данным номером, которое дилер передаёт её хранителю вместе с долей.
+    pub fn opening(&self, number: usize, key: &ManifestKey) -> ManifestOpening {
+        opening(key, &self.nonce, number)
+    }
+
+    /// Запись манифеста в JSON.
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                format!("{{\"number\":{},\"custodian\":{},\"digest\":\"{}\"}}",
+                        entry.number, json::quote(&entry.custodian), json::hex(&entry.digest))
+            })
+            .collect();
+        let assignment = self
+            .assignment_salt
+            .map_or(String::new(), |salt| format!(",\"assignment_salt\":\"{}\"", json::hex(&salt)));
+        format!("{{\"version\":{},\"order\":{},\"threshold\":{},\"created_at\":{},\"nonce\":\"{}\"{},\"shares\":[{}]}}",
+                VERSION, self.order, self.threshold, self.created_at, json::hex(&self.nonce), assignment, entries.join(","))
+    }
+
+    /// Чтение манифеста, записанного [ShareSetManifest::to_json].
+    pub fn from_json(text: &str) -> Result<Self, &'static str> {
+        let value = json::parse(text)?;
+        if value.field("version")?.number()? != VERSION {
+            return Err("unsupported manifest version");
+        }
+        let entries = value
+            .field("shares")?
+            .array()?
+            .iter()
+            .map(|entry| {
+                Ok(ManifestEntry {
+                    number: entry.field("number")?.number()? as usize,
+                    custodian: entry.field("custodian")?.string()?.to_string(),
+                    digest: json::unhex(entry.field("digest")?.string()?)?,
+                })
+            })
+            .collect::<Result<Vec<_>, &'static str>>()?;
+        Ok(ShareSetManifest {
+            order: value.field("order")?.number()? as usize,
+            threshold: value.field("threshold")?.number()? as usize,
+            created_at: value.field("created_at")?.number()?,
+            nonce: json::unhex(value.field("nonce")?.string()?)?,
+            entries,
+            assignment_salt: match value.field("assignment_salt") {
+                Ok(salt) => Some(json::unhex(salt.string()?)?),
+                Err(_) => None,
+            },
+        })
+    }
+}
+
+/// Составление манифеста.
+impl HadamardSSS {
+    /// Составление манифеста при раздаче долей: i-я доля передаётся хранителю `custodians[i]`
+    /// вместе со своим раскрытием [ShareSetManifest::opening].
+    pub fn manifest(&self, parts: &[Part], custodians: &[&str], key: &ManifestKey) -> Result<ShareSetManifest, &'static str> {
+        if parts.len() != custodians.len() {
+            return Err("every part needs a custodian");
+        }
+        let mut nonce = [0u8; 16];
+        crate::entropy::rng().fill_bytes(&mut nonce);
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| "system clock is before the UNIX epoch")?
+            .as_secs();
+        let entries = parts
+            .iter()
+            .zip(custodians)
+            .map(|(part, custodian)| ManifestEntry {
+                number: part.number(),
+                custodian: custodian.to_string(),
+                digest: digest(&nonce, part, &opening(key, &nonce, part.number())),
+            })
+            .collect();
+        Ok(ShareSetManifest {
+            order: self.matrix.order(),
+            threshold: self.threshold,
+            created_at,
+            nonce,
+            entries,
+            assignment_salt: None,
+        })
+    }
+}
+
+/// Проверка долей по манифесту; каждая доля предъявляется вместе со своим раскрытием,
+/// ключ дилера не нужен. Возвращаются номера долей, которых нет в манифесте или дайджест
+/// которых не совпадает с записанным; пустой вектор означает успешную проверку.
+pub fn verify_manifest(shares: &[(Part, ManifestOpening)], manifest: &ShareSetManifest) -> Vec<usize> {
+    shares
+        .iter()
+        .filter(|(part, opening)| {
+            !manifest
+                .entries
+                .iter()
+                .any(|entry| entry.number == part.number() && entry.digest == digest(&manifest.nonce, part, opening))
+        })
+        .map(|(part, _)| part.number())
+        .collect()
+}
+
+/// Минимальная поддержка JSON, достаточная для формата манифеста.
+mod json {
+    /// Наибольшая вложенность массивов и объектов; манифест использует три уровня.
+    const MAX_DEPTH: usize = 4;
+
+    /// Значение JSON.
+    pub(super) enum Value {
+        Number(u64),
+        Str(String),
+        Array(Vec<Value>),
+        Object(Vec<(String, Value)>),
+    }
+
+    impl Value {
+        /// Поле объекта.
+        pub(super) fn field(&self, name: &str) -> Result<&Value, &'static str> {
+            match self {
+                Value::Object(fields) => fields.iter().find(|(key, _)| key == name).map(|(_, v)| v).ok_or("missing field"),
+                _ => Err("expected an object"),
+            }
+        }
+
+        /// Неотрицательное целое число.
+        pub(super) fn number(&self) -> Result<u64, &'static str> {
+            match self {
+                Value::Number(n) => Ok(*n),
+                _ => Err("expected a number"),
+            }
+        }
+
+        /// Строка.
+        pub(super) fn string(&self) -> Result<&str, &'static str> {
+            match self {
+                Value::Str(s) => Ok(s),
+                _ => Err("expected a string"),
+            }
+        }
+
+        /// Массив.
+        pub(super) fn array(&self) -> Result<&[Value], &'static str> {
+            match self {
+                Value::Array(items) => Ok(items),
+                _ => Err("expected an array"),
+            }
+        }
+    }
+
+    /// Строка в кавычках с экранированием.
+    pub(super) fn quote(s: &str) -> String {
+        let mut res = String::from("\"");
+        for c in s.chars() {
+            match c {
+                '"' => res.push_str("\\\""),
+                '\\' => res.push_str("\\\\"),
+                c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+                c => res.push(c),
+            }
+        }
+        res.push('"');
+        res
+    }
+
+    /// Запись байтов шестнадцатеричной строкой.
+    pub(super) fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Чтение массива байтов фиксированной длины из шестнадцатеричной строки.
+    pub(super) fn unhex<const N: usize>(s: &str) -> Result<[u8; N], &'static str> {
+        if s.len() != 2 * N || !s.is_ascii() {
+            return Err("wrong hex length");
+        }
+        let mut res = [0u8; N];
+        for (i, byte) in res.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).map_err(|_| "invalid hex")?;
+        }
+        Ok(res)
+    }
+
+    /// Разбор документа.
+    pub(super) fn parse(text: &str) -> Result<Value, &'static str> {
+        let mut parser = Parser { chars: text.chars().collect(), pos: 0, depth: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.chars.len() {
+            return Err("trailing characters after JSON value");
+        }
+        Ok(value)
+    }
+
+    /// Рекурсивный разбор.
+    struct Parser {
+        chars: Vec<char>,
+        pos: usize,
+        /// Число открытых массивов и объектов, не больше [MAX_DEPTH].
+        depth: usize,
+    }
+
+    impl Parser {
+        fn skip_whitespace(&mut self) {
+            while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+                self.pos += 1;
+            }
+        }
+
+        fn expect(&mut self, c: char) -> Result<(), &'static str> {
+            self.skip_whitespace();
+            if self.chars.get(self.pos) == Some(&c) {
+                self.pos += 1;
+                Ok(())
+            } else {
+                Err("malformed JSON")
+            }
+        }
+
+        /// Разбор элементов до закрывающей скобки close, разделённых запятыми.
+        fn items<T>(&mut self, close: char, mut item: impl FnMut(&mut Self) -> Result<T, &'static str>) -> Result<Vec<T>, &'static str> {
+            let mut res = Vec::new();
+            self.skip_whitespace();
+            if self.chars.get(self.pos) == Some(&close) {
+                self.pos += 1;
+                return Ok(res);
+            }
+            loop {
+                res.push(item(self)?);
+                self.skip_whitespace();
+                match self.chars.get(self.pos) {
+                    Some(',') => self.pos += 1,
+                    Some(&c) if c == close => {
+                        self.pos += 1;
+                        return Ok(res);
+                    }
+                    _ => return Err("malformed JSON"),
+                }
+            }
+        }
+
+        /// Вход в массив или объект; возвращается ошибка, если превышена [MAX_DEPTH].
+        fn open(&mut self) -> Result<(), &'static str> {
+            if self.depth == MAX_DEPTH {
+                return Err("JSON nesting is too deep");
+            }
+            self.depth += 1;
+            self.pos += 1;
+            Ok(())
+        }
+
+        fn value(&mut self) -> Result<Value, &'static str> {
+            self.skip_whitespace();
+            let value = match self.chars.get(self.pos) {
+                Some('{') => {
+                    self.open()?;
+                    let fields = self.items('}', |p| {
+                        let key = p.string()?;
+                        p.expect(':')?;
+                        Ok((key, p.value()?))
+                    })?;
+                    Value::Object(fields)
+                }
+                Some('[') => {
+                    self.open()?;
+                    Value::Array(self.items(']', Parser::value)?)
+                }
+                Some('"') => return Ok(Value::Str(self.string()?)),
+                Some(c) if c.is_ascii_digit() => {
+                    let start = self.pos;
+                    while self.chars.get(self.pos).is_some_and(|c| c.is_ascii_digit()) {
+                        self.pos += 1;
+                    }
+                    let digits: String = self.chars[start..self.pos].iter().collect();
+                    return digits.parse().map(Value::Number).map_err(|_| "number is too large");
+                }
+                _ => return Err("malformed JSON"),
+            };
+            self.depth -= 1;
+            Ok(value)
+        }
+
+        fn string(&mut self) -> Result<String, &'static str> {
+            self.expect('"')?;
+            let mut res = String::new();
+            loop {
+                let c = *self.chars.get(self.pos).ok_or("unterminated string")?;
+                self.pos += 1;
+                match c {
+                    '"' => return Ok(res),
+                    '\\' => {
+                        let escape = *self.chars.get(self.pos).ok_or("unterminated string")?;
+                        self.pos += 1;
+                        match escape {
+                            '"' | '\\' | '/' => res.push(escape),
+                            'n' => res.push('\n'),
+                            't' => res.push('\t'),
+                            'r' => res.push('\r'),
+                            'u' => {
+                                let code: String = self.chars.get(self.pos..self.pos + 4).ok_or("bad escape")?.iter().collect();
+                                self.pos += 4;
+                                let code = u32::from_str_radix(&code, 16).map_err(|_| "bad escape")?;
+                                res.push(char::from_u32(code).ok_or("bad escape")?);
+                            }
+                            _ => return Err("bad escape"),
+                        }
+                    }
+                    c => res.push(c),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::SharingScheme;
+
+    #[test]
+    fn test_manifest_roundtrip() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let key = [5u8; 32];
+        let parts = hsss.share(314159265).unwrap();
+        let custodians = ["alice", "bob", "carol \"c\" \\ o", "dave", "erin", "frank", "гриша\n"];
+        let manifest = hsss.manifest(&parts, &custodians, &key).unwrap();
+        assert_eq!(manifest.order(), 8);
+        assert_eq!(manifest.threshold(), 5);
+        assert!(manifest.created_at() > 0);
+
+        let restored = ShareSetManifest::from_json(&manifest.to_json()).unwrap();
+        assert_eq!(restored, manifest);
+        assert_eq!(restored.entries()[6].custodian(), "гриша\n");
+        let opened: Vec<(Part, ManifestOpening)> = parts.iter().map(|&part| (part, manifest.opening(part.number(), &key))).collect();
+        assert!(verify_manifest(&opened, &restored).is_empty());
+    }
+
+    #[test]
+    fn test_manifest_detects_substitution() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let key = [5u8; 32];
+        let parts = hsss.share(42).unwrap();
+        let manifest = hsss.manifest(&parts[0..4], &["a", "b", "c", "d"], &key).unwrap();
+        let open = |part: Part, key: &ManifestKey| (part, manifest.opening(part.number(), key));
+        let mut presented: Vec<(Part, ManifestOpening)> = parts[0..5].iter().map(|&part| open(part, &key)).collect();
+        presented[1].0 = parts[1].blinded(1);
+        assert_eq!(verify_manifest(&presented, &manifest), vec![1, 4]);
+        presented[2].1 = manifest.opening(3, &key);
+        assert_eq!(verify_manifest(&presented, &manifest), vec![1, 2, 4]);
+        let forged: Vec<(Part, ManifestOpening)> = parts[0..4].iter().map(|&part| open(part, &[6u8; 32])).collect();
+        assert_eq!(verify_manifest(&forged, &manifest), vec![0, 1, 2, 3]);
+        assert!(hsss.manifest(&parts, &["a"], &key).is_err());
+        assert!(ShareSetManifest::from_json("{\"version\":2}").is_err());
+        assert_eq!(ShareSetManifest::from_json(&manifest.to_json().replacen("\"version\":2", "\"version\":1", 1)).err(),
+                   Some("unsupported manifest version"));
+        assert!(ShareSetManifest::from_json("{\"version\":2,").is_err());
+        let nested = format!("{{\"version\":2,\"shares\":{}", "[".repeat(100_000));
+        assert_eq!(ShareSetManifest::from_json(&nested).err(), Some("JSON nesting is too deep"));
+    }
+}
@@ -0,0 +1,92 @@
+//! Библиотека матриц Адамара порядков из [KNOWN_ORDERS], не больше [MAX_KNOWN_ORDER].
+//!
+//! Доступно с функцией `matrices`. Матрицы не хранятся в виде данных: матрица порядка строится
+//! построениями модуля [crate::construction] при первом запросе, проверяется условием
+//! H * H.T = nI и запоминается, поэтому повторные запросы не повторяют ни построения, ни
+//! проверки. Тесты модуля строят все порядки [KNOWN_ORDERS]. Порядков 156, 172, 188 и 236
+//! построения пока не достигают, и библиотека их не содержит.
+use crate::construction::construct;
+use crate::hadamard_matrix::HadamardMatrix;
+use std::sync::OnceLock;
+
+/// Наибольший порядок матриц библиотеки.
+pub const MAX_KNOWN_ORDER: usize = 256;
+
+/// Порядки матриц, которые содержит библиотека, по возрастанию.
+pub const KNOWN_ORDERS: &[usize] = &KNOWN;
+
+/// Порядки, кратные 4 и не больше [MAX_KNOWN_ORDER], которых построения не достигают.
+const MISSING: [usize; 4] = [156, 172, 188, 236];
+
+/// Число порядков библиотеки: 1, 2 и кратные 4 без [MISSING].
+const KNOWN_LEN: usize = 2 + MAX_KNOWN_ORDER / 4 - MISSING.len();
+
+/// Порядки библиотеки, вычисленные при компиляции.
+const KNOWN: [usize; KNOWN_LEN] = {
+    let mut orders = [0; KNOWN_LEN];
+    orders[0] = 1;
+    orders[1] = 2;
+    let mut len = 2;
+    let mut order = 4;
+    while order <= MAX_KNOWN_ORDER {
+        let mut missing = false;
+        let mut i = 0;
+        while i < MISSING.len() {
+            missing |= MISSING[i] == order;
+            i += 1;
+        }
+        if !missing {
+            orders[len] = order;
+            len += 1;
+        }
+        order += 4;
+    }
+    assert!(len == KNOWN_LEN);
+    orders
+};
+
+/// Построенные и проверенные матрицы; элемент с индексом order хранит матрицу порядка order.
+static CACHE: [OnceLock<Option<HadamardMatrix>>; MAX_KNOWN_ORDER + 1] = [const { OnceLock::new() }; MAX_KNOWN_ORDER + 1];
+
+/// Матрицы библиотеки.
+impl HadamardMatrix {
+    /// Матрица Адамара данного порядка или `None`, если порядок больше [MAX_KNOWN_ORDER],
+    /// матрицы такого порядка не существует (порядок не 1, 2 и не кратен 4) или библиотека
+    /// её не содержит.
+    pub fn known(order: usize) -> Option<HadamardMatrix> {
+        if !KNOWN_ORDERS.contains(&order) {
+            return None;
+        }
+        CACHE[order]
+            .get_or_init(|| construct(order).and_then(|h_mtx| HadamardMatrix::from_i8(&h_mtx.view().to_owned()).ok()))
+            .clone()
+    }
+}
+
+/// Порядки матриц, которые содержит библиотека, по возрастанию.
+pub fn known_orders() -> Vec<usize> {
+    KNOWN_ORDERS.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_known_order_is_hadamard() {
+        for order in 1..=MAX_KNOWN_ORDER {
+            assert_eq!(construct(order).is_some(), KNOWN_ORDERS.contains(&order), "order {order}");
+        }
+        for &order in KNOWN_ORDERS {
+            let h_mtx = HadamardMatrix::known(order).unwrap();
+            assert_eq!(h_mtx.order(), order);
+            assert!(HadamardMatrix::from_i8(&h_mtx.view().to_owned()).is_ok(), "order {order}");
+        }
+        let missing: Vec<usize> = (4..=MAX_KNOWN_ORDER).step_by(4).filter(|order| !KNOWN_ORDERS.contains(order)).collect();
+        assert_eq!(missing, MISSING);
+        assert_eq!(known_orders()[0..3], [1, 2, 4]);
+        assert!(HadamardMatrix::known(6).is_none());
+        assert!(HadamardMatrix::known(156).is_none());
+        assert!(HadamardMatrix::known(260).is_none());
+    }
+}
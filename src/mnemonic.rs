@@ -0,0 +1,148 @@
+//! Запись долей словами для хранения на бумаге.
+//!
+//! Как в BIP-39, каждое слово кодирует 11 бит. Доля в формате [crate::wire] (104 бита)
+//! дополняется шестью нулевыми битами до десяти слов, за которыми идут два контрольных слова:
+//! первые 22 бита SHA-256 от байтов доли. Записи формата версии 1 (72 бита, семь слов данных
+//! и пять битов дополнения) тоже читаются. Слова -- четырёхбуквенные псевдослова вида
+//! согласная-гласная-согласная-гласная в духе proquint: их легко читать и переписывать,
+//! а список из 2048 слов вычисляется, а не хранится. С английским списком BIP-39 этот
+//! список не совпадает.
+use crate::scheme_impl::Part;
+use crate::wire::{LEGACY_WIRE_LEN, WIRE_LEN};
+use sha2::{Digest, Sha256};
+
+/// Согласные первой позиции.
+const FIRST: &[u8; 16] = b"bdfghjklmnprstvz";
+/// Согласные третьей позиции.
+const THIRD: &[u8; 8] = b"bdgklmnr";
+/// Гласные.
+const VOWELS: &[u8; 4] = b"aiou";
+/// Число бит в слове.
+const WORD_BITS: usize = 11;
+/// Число слов данных.
+const DATA_WORDS: usize = (WIRE_LEN * 8).div_ceil(WORD_BITS);
+/// Число слов данных формата версии 1.
+const LEGACY_DATA_WORDS: usize = (LEGACY_WIRE_LEN * 8).div_ceil(WORD_BITS);
+/// Число контрольных слов.
+const CHECKSUM_WORDS: usize = 2;
+/// Число нулевых битов дополнения.
+const PADDING: usize = DATA_WORDS * WORD_BITS - WIRE_LEN * 8;
+
+/// Слово с данным 11-битным номером.
+pub(crate) fn word(index: u16) -> String {
+    let index = usize::from(index);
+    let letters = [FIRST[index >> 7], VOWELS[index >> 5 & 3], THIRD[index >> 2 & 7], VOWELS[index & 3]];
+    letters.iter().map(|&c| char::from(c)).collect()
+}
+
+/// Номер слова или `None`, если слова нет в списке.
+pub(crate) fn index(word: &str) -> Option<u16> {
+    let position = |alphabet: &[u8], c: u8| alphabet.iter().position(|&a| a == c).map(|v| v as u16);
+    match word.to_ascii_lowercase().as_bytes() {
+        &[a, b, c, d] => Some(position(FIRST, a)? << 7 | position(VOWELS, b)? << 5 | position(THIRD, c)? << 2 | position(VOWELS, d)?),
+        _ => None,
+    }
+}
+
+/// Контрольные биты байтов доли.
+fn checksum(bytes: &[u8]) -> u128 {
+    let digest = Sha256::digest(bytes);
+    let head = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+    u128::from(head >> (32 - CHECKSUM_WORDS * WORD_BITS))
+}
+
+/// Запись доли словами.
+impl Part {
+    /// Запись доли двенадцатью словами через пробел. Возвращается ошибка, если доля
+    /// не записывается в [crate::wire].
+    /// # Пример
+    /// ```
+    /// use hadamard_sss::Part;
+    ///
+    /// let words = Part::from(3, 0xDEADBEEF).with_fingerprint(7).to_mnemonic().unwrap();
+    /// assert_eq!(words.split(' ').count(), 12);
+    /// assert_eq!(Part::from_mnemonic(&words).unwrap().data(), 0xDEADBEEF);
+    /// ```
+    pub fn to_mnemonic(&self) -> Result<String, &'static str> {
+        let bytes = self.to_bytes()?;
+        let data = bytes.iter().fold(0u128, |acc, &byte| (acc << 8) | u128::from(byte)) << PADDING;
+        let bits = data << (CHECKSUM_WORDS * WORD_BITS) | checksum(&bytes);
+        let words: Vec<String> = (0..DATA_WORDS + CHECKSUM_WORDS)
+            .rev()
+            .map(|i| word((bits >> (WORD_BITS * i)) as u16 & 0x7FF))
+            .collect();
+        Ok(words.join(" "))
+    }
+
+    /// Чтение доли, записанной [Part::to_mnemonic]. Регистр не важен, слова разделяются
+    /// любыми пробельными символами.
+    pub fn from_mnemonic(text: &str) -> Result<Part, &'static str> {
+        let indices = text
+            .split_whitespace()
+            .map(|w| index(w).ok_or("unknown mnemonic word"))
+            .collect::<Result<Vec<u16>, _>>()?;
+        let (words, len) = match indices.len().checked_sub(CHECKSUM_WORDS) {
+            Some(DATA_WORDS) => (DATA_WORDS, WIRE_LEN),
+            Some(LEGACY_DATA_WORDS) => (LEGACY_DATA_WORDS, LEGACY_WIRE_LEN),
+            _ => return Err("wrong number of mnemonic words"),
+        };
+        let bits = indices.iter().fold(0u128, |acc, &i| (acc << WORD_BITS) | u128::from(i));
+        let data = bits >> (CHECKSUM_WORDS * WORD_BITS);
+        let padding = words * WORD_BITS - len * 8;
+        if data & ((1 << padding) - 1) != 0 {
+            return Err("invalid padding bits");
+        }
+        let bytes: Vec<u8> = (0..len).rev().map(|i| (data >> (padding + 8 * i)) as u8).collect();
+        if bits & ((1 << (CHECKSUM_WORDS * WORD_BITS)) - 1) != checksum(&bytes) {
+            return Err("mnemonic checksum mismatch");
+        }
+        Part::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_list() {
+        let words: Vec<String> = (0..2048).map(word).collect();
+        for (i, w) in words.iter().enumerate() {
+            assert_eq!(index(w), Some(i as u16));
+        }
+        let mut sorted = words.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 2048);
+        assert_eq!(index("abcd"), None);
+        assert_eq!(index("bab"), None);
+    }
+
+    #[test]
+    fn test_roundtrip_and_errors() {
+        for (number, data) in [(0, 0), (6, 314159265), (u32::MAX as usize, u32::MAX)] {
+            let text = Part::from(number, data).with_fingerprint(!data).to_mnemonic().unwrap();
+            let part = Part::from_mnemonic(&text.to_uppercase().replace(' ', "\n ")).unwrap();
+            assert_eq!((part.number(), part.data(), part.fingerprint()), (number, data, Some(!data)));
+        }
+        // запись формата версии 1 читается как доля без отпечатка
+        let legacy = Part::from_mnemonic("baga baba baba buki vomo tunu ruba migi pagu").unwrap();
+        assert_eq!((legacy.number(), legacy.data(), legacy.fingerprint()), (6, 0xDEADBEEF, None));
+        let text = Part::from(4, 0xC0FFEE).with_fingerprint(0xF00D).to_mnemonic().unwrap();
+        let words: Vec<&str> = text.split(' ').collect();
+        // замена любого слова данных на соседнее обнаруживается
+        for position in 0..DATA_WORDS {
+            let mut typo = words.clone();
+            let replaced = word(index(words[position]).unwrap() ^ 1);
+            typo[position] = &replaced;
+            assert!(Part::from_mnemonic(&typo.join(" ")).is_err());
+        }
+        assert_eq!(Part::from_mnemonic(&words[..11].join(" ")).err(), Some("wrong number of mnemonic words"));
+        assert_eq!(Part::from_mnemonic(&text.replacen(words[0], "zzzz", 1)).err(), Some("unknown mnemonic word"));
+        let mut swapped = words.clone();
+        swapped.swap(1, 2);
+        if words[1] != words[2] {
+            assert!(Part::from_mnemonic(&swapped.join(" ")).is_err());
+        }
+    }
+}
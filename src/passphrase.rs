@@ -0,0 +1,159 @@
+//! Двойной контроль: для восстановления нужны и кворум долей, и парольная фраза.
+//!
+//! При раздаче из парольной фразы PBKDF2-HMAC-SHA256 со случайной солью выводится ключ,
+//! а разделяется не сам секрет, а secret ⊕ mask, где mask выводится из ключа HKDF.
+//! [PassphraseBinding] хранит соль, число итераций и тег, по которому при восстановлении
+//! проверяется, что фраза верна. Тег зависит и от ключа, и от секрета, поэтому без кворума
+//! долей подбирать фразу по нему нельзя. Восстановление выполняется запросом
+//! [crate::request::ReconstructRequest::passphrase]; обычное [crate::SharingScheme::reconstruct]
+//! возвращает замаскированное значение.
+use crate::scheme_impl::Part;
+use crate::{HadamardSSS, SharingScheme};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+/// Число итераций PBKDF2 по умолчанию.
+pub const DEFAULT_ITERATIONS: u32 = 600_000;
+
+/// Метка HKDF для маски секрета.
+const MASK_LABEL: &[u8] = b"hadamard_sss passphrase mask";
+/// Метка HKDF для тега.
+const TAG_LABEL: &[u8] = b"hadamard_sss passphrase tag";
+
+/// Параметры привязки секрета к парольной фразе, которые хранятся вместе с долями.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PassphraseBinding {
+    /// Соль PBKDF2.
+    salt: [u8; 16],
+    /// Число итераций PBKDF2.
+    iterations: u32,
+    /// Тег для проверки фразы.
+    tag: [u8; 16],
+}
+
+/// PBKDF2-HMAC-SHA256 с выходом в один блок.
+fn pbkdf2(passphrase: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let prf = Hmac::<Sha256>::new_from_slice(passphrase).expect("HMAC accepts keys of any length");
+    let mut block: [u8; 32] = prf.clone().chain_update(salt).chain_update(1u32.to_be_bytes()).finalize().into_bytes().into();
+    let mut res = block;
+    for _ in 1..iterations {
+        block = prf.clone().chain_update(block).finalize().into_bytes().into();
+        res.iter_mut().zip(block).for_each(|(r, b)| *r ^= b);
+    }
+    res
+}
+
+/// Маска секрета.
+fn mask(key: &[u8; 32], salt: &[u8; 16]) -> u32 {
+    let mut mask = [0u8; 4];
+    Hkdf::<Sha256>::new(Some(salt), key)
+        .expand(MASK_LABEL, &mut mask)
+        .expect("4 bytes is a valid HKDF output length");
+    u32::from_le_bytes(mask)
+}
+
+/// Тег секрета.
+fn tag(key: &[u8; 32], salt: &[u8; 16], secret: u32) -> [u8; 16] {
+    let mut tag = [0u8; 16];
+    Hkdf::<Sha256>::new(Some(salt), key)
+        .expand_multi_info(&[TAG_LABEL, &secret.to_le_bytes()], &mut tag)
+        .expect("16 bytes is a valid HKDF output length");
+    tag
+}
+
+/// Реализация методов привязки.
+impl PassphraseBinding {
+    /// Создание экземпляра структуры по ранее сохранённым полям.
+    pub fn from(salt: [u8; 16], iterations: u32, tag: [u8; 16]) -> Self {
+        PassphraseBinding { salt, iterations, tag }
+    }
+
+    /// Возвращение значения поля salt.
+    pub fn salt(&self) -> [u8; 16] {
+        self.salt
+    }
+
+    /// Возвращение значения поля iterations.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Возвращение значения поля tag.
+    pub fn tag(&self) -> [u8; 16] {
+        self.tag
+    }
+
+    /// Снятие маски с восстановленного значения и проверка фразы.
+    pub(crate) fn unbind(&self, word: u32, passphrase: &[u8]) -> Result<u32, &'static str> {
+        if self.iterations == 0 {
+            return Err("passphrase binding has zero iterations");
+        }
+        let key = pbkdf2(passphrase, &self.salt, self.iterations);
+        let secret = word ^ mask(&key, &self.salt);
+        if !bool::from(tag(&key, &self.salt, secret).ct_eq(&self.tag)) {
+            return Err("passphrase does not match the shares");
+        }
+        Ok(secret)
+    }
+}
+
+/// Раздача с парольной фразой.
+impl HadamardSSS {
+    /// Разделение секрета, привязанного к парольной фразе; iterations -- число итераций
+    /// PBKDF2 (см. [DEFAULT_ITERATIONS]). Возвращаются доли и параметры привязки.
+    pub fn share_with_passphrase(&self, secret: u32, passphrase: &[u8], iterations: u32) -> Result<(Vec<Part>, PassphraseBinding), &'static str> {
+        if passphrase.is_empty() {
+            return Err("passphrase must not be empty");
+        }
+        if iterations == 0 {
+            return Err("passphrase binding has zero iterations");
+        }
+        let mut salt = [0u8; 16];
+        crate::entropy::rng().fill_bytes(&mut salt);
+        let key = pbkdf2(passphrase, &salt, iterations);
+        let binding = PassphraseBinding { salt, iterations, tag: tag(&key, &salt, secret) };
+        Ok((self.share(secret ^ mask(&key, &salt))?, binding))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_pbkdf2_vectors() {
+        let hex = |bytes: [u8; 32]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        assert_eq!(hex(pbkdf2(b"password", b"salt", 1)), "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b");
+        assert_eq!(hex(pbkdf2(b"password", b"salt", 4096)), "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a");
+    }
+
+    #[test]
+    fn test_dual_control() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let (parts, binding) = hsss.share_with_passphrase(314159265, b"correct horse", 1000).unwrap();
+        assert_eq!(binding.iterations(), 1000);
+        assert_ne!(hsss.reconstruct(parts[0..5].to_vec()), Ok(314159265));
+        let outcome = hsss.reconstruct_request(parts[0..5].to_vec()).passphrase(b"correct horse", binding).run().unwrap();
+        assert_eq!(outcome.secret(), 314159265);
+        assert!(outcome.passphrase_verified());
+        assert_eq!(
+            hsss.reconstruct_request(parts[0..5].to_vec()).passphrase(b"battery staple", binding).run(),
+            Err("passphrase does not match the shares")
+        );
+        assert_eq!(
+            hsss.reconstruct_request(parts[0..4].to_vec()).passphrase(b"correct horse", binding).run(),
+            Err("less than threshold parties")
+        );
+        let stored = PassphraseBinding::from(binding.salt(), 0, binding.tag());
+        assert_eq!(
+            hsss.reconstruct_request(parts.clone()).passphrase(b"correct horse", stored).run(),
+            Err("passphrase binding has zero iterations")
+        );
+        assert_eq!(hsss.share_with_passphrase(1, b"", 1000).err(), Some("passphrase must not be empty"));
+        assert_eq!(hsss.share_with_passphrase(1, b"x", 0).err(), Some("passphrase binding has zero iterations"));
+    }
+}
@@ -0,0 +1,168 @@
+//! Сохранение и загрузка схемы.
+//!
+//! Дилер и комбинирующий сервис могут быть разными программами. [HadamardSSS::save]
+//! записывает всё, от чего зависят доли: нормализованную матрицу Адамара, порог, столбцы
+//! битов секрета и порядок байтов раскладки. [HadamardSSS::load] восстанавливает схему,
+//! для которой повторное сохранение даёт те же байты. Канарейки и аварийный режим
+//! содержат обработчики и не сохраняются: их нужно настроить заново.
+//!
+//! Формат (целые числа -- u32 big-endian):
+//!
+//! | поле        | длина              | содержимое                                      |
+//! |-------------|--------------------|-------------------------------------------------|
+//! | сигнатура   | 4                  | `HSSS`                                          |
+//! | версия      | 1                  | [SAVE_VERSION]                                  |
+//! | порядок     | 4                  | порядок N матрицы Адамара                       |
+//! | порог       | 4                  | порог схемы                                     |
+//! | байты       | 1                  | 0 -- little-endian, 1 -- big-endian             |
+//! | столбцы     | 4 * 32             | столбец матрицы инцидентности для каждого бита  |
+//! | матрица     | ceil(N * N / 8)    | элементы по строкам, бит 1 означает -1, старший бит первым |
+use crate::layout::ByteOrder;
+use crate::scheme_impl::HSS;
+use crate::{HadamardMatrix, HadamardSSS};
+use ndarray::Array2;
+
+/// Текущая версия формата.
+pub const SAVE_VERSION: u8 = 1;
+
+/// Сигнатура формата.
+const MAGIC: &[u8; 4] = b"HSSS";
+/// Длина заголовка до матрицы.
+const HEADER_LEN: usize = 4 + 1 + 4 + 4 + 1 + 4 * u32::BITS as usize;
+
+/// Последовательное чтение сохранённой схемы.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Следующие len байтов.
+    fn take(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        if self.bytes.len() < len {
+            return Err("saved scheme is truncated");
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    /// Следующее число u32.
+    fn u32(&mut self) -> Result<usize, &'static str> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+}
+
+/// Сохранение и загрузка схемы.
+impl HadamardSSS {
+    /// Сохранение схемы в байты.
+    pub fn save(&self) -> Vec<u8> {
+        let order = self.matrix.order();
+        let mut out = Vec::with_capacity(HEADER_LEN + (order * order).div_ceil(8));
+        out.extend_from_slice(MAGIC);
+        out.push(SAVE_VERSION);
+        out.extend_from_slice(&(order as u32).to_be_bytes());
+        out.extend_from_slice(&(self.threshold as u32).to_be_bytes());
+        out.push(match self.hss.byte_order() {
+            ByteOrder::Little => 0,
+            ByteOrder::Big => 1,
+        });
+        for &col in self.hss.columns() {
+            out.extend_from_slice(&(col as u32).to_be_bytes());
+        }
+        let bits: Vec<bool> = self.matrix.view().iter().map(|&x| x < 0).collect();
+        for chunk in bits.chunks(8) {
+            out.push(chunk.iter().enumerate().fold(0u8, |byte, (i, &bit)| byte | (u8::from(bit) << (7 - i))));
+        }
+        out
+    }
+
+    /// Загрузка схемы, сохранённой [HadamardSSS::save]. Матрица проверяется заново;
+    /// возвращается ошибка, если она не является нормализованной матрицей Адамара
+    /// или не согласована с порогом и столбцами.
+    pub fn load(bytes: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err("not a saved scheme");
+        }
+        if reader.take(1)?[0] != SAVE_VERSION {
+            return Err("unsupported saved scheme version");
+        }
+        let order = reader.u32()?;
+        let threshold = reader.u32()?;
+        let byte_order = match reader.take(1)?[0] {
+            0 => ByteOrder::Little,
+            1 => ByteOrder::Big,
+            _ => return Err("saved scheme has an unknown byte order"),
+        };
+        let columns = (0..u32::BITS).map(|_| reader.u32()).collect::<Result<Vec<usize>, _>>()?;
+        let cells = order.checked_mul(order).ok_or("saved scheme is truncated")?;
+        let packed = reader.take(cells.div_ceil(8))?;
+        if !reader.bytes.is_empty() {
+            return Err("saved scheme has trailing bytes");
+        }
+        let mtx = Array2::from_shape_fn((order, order), |(i, j)| {
+            let cell = i * order + j;
+            if packed[cell / 8] >> (7 - cell % 8) & 1 == 1 { -1 } else { 1 }
+        });
+        let matrix = HadamardMatrix::from_i8(&mtx).map_err(|_| "saved matrix is not a Hadamard matrix")?;
+        if !matrix.is_normalized() || order < 4 {
+            return Err("saved matrix is not a normalized Hadamard matrix of order 4n");
+        }
+        let incidence = matrix.get_incidence();
+        if threshold != HadamardSSS::get_threshold(&incidence) {
+            return Err("saved threshold does not match the matrix");
+        }
+        let hss = HSS::with_columns(&incidence, columns, byte_order)?;
+        Ok(HadamardSSS::assemble(hss, matrix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::layout::{BitMapping, ColumnSelection, Layout};
+    use crate::SharingScheme;
+
+    #[test]
+    fn test_roundtrip_is_byte_identical() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let saved = hsss.save();
+        assert_eq!(saved.len(), HEADER_LEN + 8);
+        let loaded = HadamardSSS::load(&saved).unwrap();
+        assert_eq!(loaded.save(), saved);
+        let parts = hsss.share(314159265).unwrap();
+        assert_eq!(loaded.reconstruct(parts[2..7].to_vec()).unwrap(), 314159265);
+
+        let layout = Layout { columns: ColumnSelection::Spread, mapping: BitMapping::Permuted(7), byte_order: ByteOrder::Big, ..Layout::default() };
+        let hsss = HadamardSSS::with_layout(&HadamardMatrix::sylvester(6).unwrap().to_i32(), &layout).unwrap();
+        let loaded = HadamardSSS::load(&hsss.save()).unwrap();
+        assert_eq!(loaded.save(), hsss.save());
+        assert_eq!(loaded.hss.byte_order(), ByteOrder::Big);
+        let parts = hsss.share(0xDEADBEEF).unwrap();
+        assert_eq!(loaded.reconstruct(parts[..33].to_vec()).unwrap(), 0xDEADBEEF);
+    }
+
+    #[test]
+    fn test_malformed_rejected() {
+        let saved = HadamardSSS::from(&matrix()).unwrap().save();
+        assert_eq!(HadamardSSS::load(&saved[..saved.len() - 1]).err(), Some("saved scheme is truncated"));
+        assert_eq!(HadamardSSS::load(&[saved.as_slice(), &[0]].concat()).err(), Some("saved scheme has trailing bytes"));
+        let mut corrupt = saved.clone();
+        corrupt[0] = b'X';
+        assert_eq!(HadamardSSS::load(&corrupt).err(), Some("not a saved scheme"));
+        let mut corrupt = saved.clone();
+        corrupt[4] = 2;
+        assert_eq!(HadamardSSS::load(&corrupt).err(), Some("unsupported saved scheme version"));
+        let mut corrupt = saved.clone();
+        corrupt[12] ^= 1;
+        assert_eq!(HadamardSSS::load(&corrupt).err(), Some("saved threshold does not match the matrix"));
+        let mut corrupt = saved.clone();
+        corrupt[HEADER_LEN - 1] = 7;
+        assert!(HadamardSSS::load(&corrupt).is_err());
+        let mut corrupt = saved.clone();
+        corrupt[HEADER_LEN + 3] ^= 0x10;
+        assert_eq!(HadamardSSS::load(&corrupt).err(), Some("saved matrix is not a Hadamard matrix"));
+    }
+}
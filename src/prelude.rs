@@ -0,0 +1,17 @@
+//! Стабильный публичный интерфейс библиотеки.
+//!
+//! ```
+//! use hadamard_sss::prelude::*;
+//! use hadamard_sss::fixtures::matrix;
+//!
+//! let scheme = HadamardSSS::from(&matrix()).unwrap();
+//! let parts: Vec<Part> = scheme.share(314159265).unwrap();
+//! assert_eq!(scheme.reconstruct(parts[1..6].to_vec()).unwrap(), 314159265);
+//! ```
+pub use crate::hadamard_matrix::HadamardMatrix;
+pub use crate::layout::{BitMapping, ColumnSelection, Layout};
+pub use crate::scheme_impl::{ChunkedPart, ParticipantRows, Part};
+pub use crate::scheme_traits::{SchemeExt, SharingScheme};
+pub use crate::secret::ShareableSecret;
+pub use crate::validation::{ShareVerdict, TieBreak, ValidationOutcome, ValidationPolicy};
+pub use crate::HadamardSSS;
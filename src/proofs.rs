@@ -0,0 +1,160 @@
+//! Доказательство корректности разделения.
+//!
+//! Дилер фиксирует каждый бит секрета хэш-обязательством `SHA-256(бит, значение, r)` со случайным
+//! ослеплением r и публикует корень дерева Меркла над 32 обязательствами. Участник получает
+//! вместе с долей открытия обязательств для известных ему битов и пути к корню. Проверив их,
+//! он убеждается, что его биты совпадают с зафиксированными; если проверку прошли все участники,
+//! их доли согласованы с единственным секретом. Чужие биты остаются скрытыми за ослеплением.
+use crate::scheme_impl::Part;
+use crate::{HadamardSSS, SharingScheme};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Корень дерева Меркла, публикуемый дилером.
+pub type Commitment = [u8; 32];
+
+/// Открытие обязательства одного бита.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitOpening {
+    /// Номер бита секрета.
+    bit: u32,
+    /// Ослепление обязательства.
+    blinding: [u8; 32],
+    /// Соседние узлы на пути от листа к корню.
+    path: Vec<[u8; 32]>,
+}
+
+/// Доказательство для одной доли: открытия всех известных её владельцу битов.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareProof {
+    /// Номер доли.
+    number: usize,
+    /// Открытия обязательств.
+    openings: Vec<BitOpening>,
+}
+
+/// Реализация методов доказательства для доли.
+impl ShareProof {
+    /// Возвращение значения поля number.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Маска битов, открытых доказательством.
+    pub fn mask(&self) -> u32 {
+        self.openings.iter().fold(0, |mask, opening| mask | 1 << opening.bit)
+    }
+}
+
+/// Обязательство бита (лист дерева).
+fn leaf(bit: u32, value: bool, blinding: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([0u8])
+        .chain_update(b"hadamard_sss bit")
+        .chain_update(bit.to_le_bytes())
+        .chain_update([value as u8])
+        .chain_update(blinding)
+        .finalize()
+        .into()
+}
+
+/// Внутренний узел дерева.
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new().chain_update([1u8]).chain_update(left).chain_update(right).finalize().into()
+}
+
+/// Уровни дерева Меркла от листьев до корня.
+fn levels(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut res = vec![leaves];
+    while res.last().map_or(0, Vec::len) > 1 {
+        let next = res.last().expect("tree has a level").chunks(2).map(|pair| node(&pair[0], &pair[1])).collect();
+        res.push(next);
+    }
+    res
+}
+
+/// Разделение с доказательством.
+impl HadamardSSS {
+    /// Разделение секрета с доказательством корректности. Возвращаются доли, доказательства
+    /// для каждой доли (в том же порядке) и корень, который публикуется для всех участников.
+    pub fn share_with_proof(&self, secret: u32) -> Result<(Vec<Part>, Vec<ShareProof>, Commitment), &'static str> {
+        let mut rng = crate::entropy::rng();
+        let blindings: Vec<[u8; 32]> = (0..u32::BITS)
+            .map(|_| {
+                let mut blinding = [0u8; 32];
+                rng.fill_bytes(&mut blinding);
+                blinding
+            })
+            .collect();
+        let tree = levels((0..u32::BITS).map(|bit| leaf(bit, secret >> bit & 1 == 1, &blindings[bit as usize])).collect());
+        let root = tree.last().expect("tree has a root")[0];
+
+        let parts = self.share(secret)?;
+        let proofs = parts
+            .iter()
+            .map(|part| {
+                let mask = self.hss.mask(part.number());
+                let openings = (0..u32::BITS)
+                    .filter(|&bit| mask >> bit & 1 == 1)
+                    .map(|bit| {
+                        let path = tree[..tree.len() - 1]
+                            .iter()
+                            .enumerate()
+                            .map(|(depth, level)| level[(bit as usize >> depth) ^ 1])
+                            .collect();
+                        BitOpening { bit, blinding: blindings[bit as usize], path }
+                    })
+                    .collect();
+                ShareProof { number: part.number(), openings }
+            })
+            .collect();
+        Ok((parts, proofs, root))
+    }
+}
+
+/// Проверка доли участником: каждое открытие должно соответствовать биту доли и вести к корню.
+pub fn verify_share(part: &Part, proof: &ShareProof, root: &Commitment) -> bool {
+    proof.number == part.number()
+        && !proof.openings.is_empty()
+        && proof.openings.iter().all(|opening| {
+            if opening.bit >= u32::BITS || opening.path.len() != 5 {
+                return false;
+            }
+            let mut hash = leaf(opening.bit, part.data() >> opening.bit & 1 == 1, &opening.blinding);
+            for (depth, sibling) in opening.path.iter().enumerate() {
+                hash = if opening.bit >> depth & 1 == 0 { node(&hash, sibling) } else { node(sibling, &hash) };
+            }
+            hash == *root
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_honest_sharing_verifies() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let (parts, proofs, root) = hsss.share_with_proof(314159265).unwrap();
+        for (part, proof) in parts.iter().zip(&proofs) {
+            assert!(verify_share(part, proof, &root));
+            assert_eq!(proof.mask(), hsss.hss.mask(part.number()));
+        }
+        assert_eq!(hsss.reconstruct(parts[0..5].to_vec()).unwrap(), 314159265);
+    }
+
+    #[test]
+    fn test_inconsistent_share_rejected() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let (parts, proofs, root) = hsss.share_with_proof(42).unwrap();
+        let (_, _, other_root) = hsss.share_with_proof(42).unwrap();
+        let forged = parts[2].blinded(hsss.hss.mask(2));
+        assert!(!verify_share(&forged, &proofs[2], &root));
+        assert!(!verify_share(&parts[2], &proofs[3], &root));
+        assert!(!verify_share(&parts[2], &proofs[2], &other_root));
+        // изменение только незнакомых владельцу битов доказательство не затрагивает
+        let padded = parts[2].blinded(!hsss.hss.mask(2));
+        assert!(verify_share(&padded, &proofs[2], &root));
+    }
+}
@@ -0,0 +1,219 @@
+//! Планирование обновления долей.
+//!
+//! [RefreshScheduler] хранит номер поколения долей, время последнего обновления и число
+//! восстановлений с тех пор и по [RefreshPolicy] решает, пора ли обновлять доли. Само обновление
+//! (например, восстановление и повторная раздача или [HadamardSSS::share_session] в новом сеансе)
+//! передаётся замыканием, так что планировщику не нужны ни асинхронная среда, ни таймеры:
+//! достаточно периодически вызывать [RefreshScheduler::run]. Время берётся из [Clock],
+//! который в тестах заменяется управляемыми часами. Каждая попытка обновления передаётся
+//! обработчику аудита как [RefreshEvent].
+#[cfg(doc)]
+use crate::HadamardSSS;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Источник времени в секундах от начала эпохи UNIX.
+pub trait Clock {
+    /// Текущее время.
+    fn now(&self) -> u64;
+}
+
+/// Системные часы.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+    }
+}
+
+/// Правила обновления долей.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RefreshPolicy {
+    /// Наибольший срок жизни поколения долей в секундах.
+    pub interval: u64,
+    /// Наибольшее число восстановлений, после которого доли обновляются досрочно.
+    pub max_uses: Option<u64>,
+}
+
+/// Причина обновления.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefreshReason {
+    /// Истёк срок жизни поколения.
+    Interval,
+    /// Достигнуто наибольшее число восстановлений.
+    Uses,
+    /// Обновление запрошено явно.
+    Forced,
+}
+
+/// Событие аудита о попытке обновления.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefreshEvent {
+    /// Поколение, которое должно было появиться в результате обновления.
+    pub generation: u64,
+    /// Время попытки.
+    pub timestamp: u64,
+    /// Причина обновления.
+    pub reason: RefreshReason,
+    /// Ошибка обновления, если оно не удалось.
+    pub error: Option<&'static str>,
+}
+
+/// Обработчик событий обновления.
+pub type RefreshSink = Box<dyn Fn(&RefreshEvent) + Send + Sync>;
+
+/// Планировщик обновления долей.
+pub struct RefreshScheduler<C: Clock> {
+    /// Источник времени.
+    clock: C,
+    /// Правила обновления.
+    policy: RefreshPolicy,
+    /// Номер текущего поколения долей.
+    generation: u64,
+    /// Время появления текущего поколения.
+    refreshed_at: u64,
+    /// Число восстановлений текущего поколения.
+    uses: u64,
+    /// Обработчик аудита.
+    sink: Option<RefreshSink>,
+}
+
+/// Реализация методов планировщика.
+impl<C: Clock> RefreshScheduler<C> {
+    /// Планировщик для только что разданных долей поколения 0.
+    pub fn new(clock: C, policy: RefreshPolicy) -> Self {
+        let refreshed_at = clock.now();
+        RefreshScheduler { clock, policy, generation: 0, refreshed_at, uses: 0, sink: None }
+    }
+
+    /// Регистрация обработчика аудита.
+    pub fn with_audit(mut self, sink: impl Fn(&RefreshEvent) + Send + Sync + 'static) -> Self {
+        self.sink = Some(Box::new(sink));
+        self
+    }
+
+    /// Возвращение значения поля generation.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Возвращение значения поля uses.
+    pub fn uses(&self) -> u64 {
+        self.uses
+    }
+
+    /// Учёт восстановления секрета по долям текущего поколения.
+    pub fn record_use(&mut self) {
+        self.uses += 1;
+    }
+
+    /// Причина, по которой доли пора обновить, или `None`.
+    pub fn due(&self) -> Option<RefreshReason> {
+        if self.policy.max_uses.is_some_and(|max| self.uses >= max) {
+            Some(RefreshReason::Uses)
+        } else if self.clock.now().saturating_sub(self.refreshed_at) >= self.policy.interval {
+            Some(RefreshReason::Interval)
+        } else {
+            None
+        }
+    }
+
+    /// Обновление, если оно положено по правилам. refresh получает номер нового поколения;
+    /// при успехе поколение увеличивается, а счётчики сбрасываются. Возвращается результат
+    /// refresh или `None`, если обновлять рано.
+    pub fn run<T>(&mut self, refresh: impl FnOnce(u64) -> Result<T, &'static str>) -> Result<Option<T>, &'static str> {
+        match self.due() {
+            Some(reason) => self.refresh(reason, refresh).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Обновление независимо от правил.
+    pub fn force<T>(&mut self, refresh: impl FnOnce(u64) -> Result<T, &'static str>) -> Result<T, &'static str> {
+        self.refresh(RefreshReason::Forced, refresh)
+    }
+
+    /// Выполнение обновления и запись события аудита.
+    fn refresh<T>(&mut self, reason: RefreshReason, refresh: impl FnOnce(u64) -> Result<T, &'static str>) -> Result<T, &'static str> {
+        let generation = self.generation + 1;
+        let result = refresh(generation);
+        let timestamp = self.clock.now();
+        if let Some(sink) = &self.sink {
+            sink(&RefreshEvent { generation, timestamp, reason, error: result.as_ref().err().copied() });
+        }
+        if result.is_ok() {
+            self.generation = generation;
+            self.refreshed_at = timestamp;
+            self.uses = 0;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::scheme_impl::Part;
+    use crate::{HadamardSSS, SharingScheme};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+
+    /// Часы, которые двигает тест.
+    struct ManualClock(Rc<Cell<u64>>);
+
+    impl Clock for ManualClock {
+        fn now(&self) -> u64 {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn test_scheduler() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut parts = hsss.share(314159265).unwrap();
+        let time = Rc::new(Cell::new(1000));
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&events);
+        let policy = RefreshPolicy { interval: 3600, max_uses: Some(3) };
+        let mut scheduler = RefreshScheduler::new(ManualClock(Rc::clone(&time)), policy)
+            .with_audit(move |event| log.lock().unwrap().push(event.clone()));
+
+        // обновление: восстановление и повторная раздача
+        let reshare = |parts: &mut Vec<Part>| {
+            let secret = hsss.reconstruct(parts.clone())?;
+            *parts = hsss.share(secret)?;
+            Ok(())
+        };
+
+        assert_eq!(scheduler.due(), None);
+        assert_eq!(scheduler.run(|_| reshare(&mut parts)), Ok(None));
+        time.set(1000 + 3600);
+        assert_eq!(scheduler.due(), Some(RefreshReason::Interval));
+        assert_eq!(scheduler.run(|_| reshare(&mut parts)), Ok(Some(())));
+        assert_eq!(scheduler.generation(), 1);
+        assert_eq!(scheduler.due(), None);
+
+        for _ in 0..3 {
+            scheduler.record_use();
+        }
+        assert_eq!(scheduler.due(), Some(RefreshReason::Uses));
+        assert_eq!(scheduler.run(|_| Err::<(), _>("storage unavailable")), Err("storage unavailable"));
+        assert_eq!(scheduler.generation(), 1);
+        assert_eq!(scheduler.uses(), 3);
+        assert_eq!(scheduler.force(Ok), Ok(2));
+        assert_eq!(scheduler.uses(), 0);
+        assert_eq!(hsss.reconstruct(parts).unwrap(), 314159265);
+
+        let events = events.lock().unwrap();
+        let summary: Vec<(u64, RefreshReason, Option<&str>)> = events.iter().map(|e| (e.generation, e.reason, e.error)).collect();
+        assert_eq!(summary, [
+            (1, RefreshReason::Interval, None),
+            (2, RefreshReason::Uses, Some("storage unavailable")),
+            (2, RefreshReason::Forced, None),
+        ]);
+        assert!(events.iter().all(|event| event.timestamp == 4600));
+    }
+}
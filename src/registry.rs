@@ -0,0 +1,148 @@
+//! Именованные участники.
+//!
+//! [ParticipantRegistry] сопоставляет строковые идентификаторы участников номерам долей:
+//! i-й идентификатор получает долю с номером i. Доля выдаётся вместе с идентификатором
+//! владельца, а восстановление сообщает, какие участники предъявили доли и кто из них
+//! подозревается в подлоге, по именам, а не по номерам.
+use crate::scheme_impl::Part;
+use crate::{HadamardSSS, SharingScheme};
+
+/// Доля вместе с идентификатором её владельца.
+#[derive(Clone)]
+pub struct NamedPart {
+    /// Идентификатор участника.
+    pub id: String,
+    /// Доля.
+    pub part: Part,
+}
+
+/// Итог восстановления по именованным долям.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamedReconstruction {
+    /// Восстановленный секрет или ошибка восстановления; при подозрительных долях
+    /// секрет не восстанавливается.
+    pub secret: Result<u32, &'static str>,
+    /// Участники, предъявившие доли, в порядке номеров.
+    pub contributors: Vec<String>,
+    /// Участники, доли которых признаны подозрительными.
+    pub suspected: Vec<String>,
+}
+
+/// Реестр участников.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParticipantRegistry {
+    /// Идентификаторы по номерам долей.
+    ids: Vec<String>,
+}
+
+/// Реализация методов реестра.
+impl ParticipantRegistry {
+    /// Реестр с данными идентификаторами. Возвращается ошибка, если идентификаторы
+    /// повторяются или пусты.
+    pub fn new(ids: &[&str]) -> Result<Self, &'static str> {
+        if ids.iter().any(|id| id.is_empty()) {
+            return Err("participant identifier must not be empty");
+        }
+        if ids.iter().enumerate().any(|(i, id)| ids[..i].contains(id)) {
+            return Err("duplicate participant identifier");
+        }
+        Ok(ParticipantRegistry { ids: ids.iter().map(|id| id.to_string()).collect() })
+    }
+
+    /// Возвращение значения поля ids.
+    pub fn ids(&self) -> &[String] {
+        &self.ids
+    }
+
+    /// Номер доли участника.
+    pub fn number(&self, id: &str) -> Option<usize> {
+        self.ids.iter().position(|known| known == id)
+    }
+
+    /// Идентификатор владельца доли с данным номером.
+    pub fn id(&self, number: usize) -> Option<&str> {
+        self.ids.get(number).map(String::as_str)
+    }
+
+    /// Разделение секрета между участниками реестра. Возвращается ошибка, если участников
+    /// больше, чем долей в схеме, или меньше порога: тогда участники не смогли бы восстановить
+    /// секрет. Если участников меньше, чем долей, доли с номерами от числа участников
+    /// и дальше никому не выдаются и отбрасываются.
+    pub fn share(&self, scheme: &HadamardSSS, secret: u32) -> Result<Vec<NamedPart>, &'static str> {
+        if self.ids.len() < scheme.threshold {
+            return Err("fewer participants than the threshold");
+        }
+        let parts = scheme.share(secret)?;
+        if self.ids.len() > parts.len() {
+            return Err("more participants than shares in the scheme");
+        }
+        Ok(self.ids.iter().zip(parts).map(|(id, part)| NamedPart { id: id.clone(), part }).collect())
+    }
+
+    /// Восстановление секрета по именованным долям. Возвращается ошибка, если участник
+    /// не из реестра или номер его доли не совпадает с номером по реестру.
+    pub fn reconstruct(&self, scheme: &HadamardSSS, parts: Vec<NamedPart>) -> Result<NamedReconstruction, &'static str> {
+        for named in &parts {
+            let number = self.number(&named.id).ok_or("unknown participant identifier")?;
+            if number != named.part.number() {
+                return Err("participant identifier does not match the share number");
+            }
+        }
+        let parts: Vec<Part> = parts.into_iter().map(|named| named.part).collect();
+        let names = |numbers: &[usize]| -> Vec<String> {
+            numbers.iter().filter_map(|&number| self.id(number)).map(str::to_string).collect()
+        };
+        let mut numbers: Vec<usize> = parts.iter().map(Part::number).collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        let suspected = names(&scheme.validate(parts.clone()).suspicious());
+        let secret = if suspected.is_empty() { scheme.reconstruct(parts) } else { Err("suspicious shares detected") };
+        Ok(NamedReconstruction { secret, contributors: names(&numbers), suspected })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_named_reconstruction() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let registry = ParticipantRegistry::new(&["alice", "bob", "carol", "dave", "erin", "frank", "grace"]).unwrap();
+        let mut parts = registry.share(&hsss, 314159265).unwrap();
+        assert_eq!(parts.len(), 7);
+        assert_eq!((parts[2].id.as_str(), parts[2].part.number()), ("carol", 2));
+
+        let res = registry.reconstruct(&hsss, parts[1..6].to_vec()).unwrap();
+        assert_eq!(res.secret, Ok(314159265));
+        assert_eq!(res.contributors, ["bob", "carol", "dave", "erin", "frank"]);
+        assert!(res.suspected.is_empty());
+
+        parts[3].part = parts[3].part.blinded(1 << hsss.hss.mask(3).trailing_zeros());
+        let res = registry.reconstruct(&hsss, parts.clone()).unwrap();
+        assert_eq!((res.secret, res.suspected), (Err("suspicious shares detected"), vec!["dave".to_string()]));
+
+        parts[0].id = "bob".to_string();
+        assert_eq!(registry.reconstruct(&hsss, parts.clone()).err(), Some("participant identifier does not match the share number"));
+        parts[0].id = "mallory".to_string();
+        assert_eq!(registry.reconstruct(&hsss, parts).err(), Some("unknown participant identifier"));
+    }
+
+    #[test]
+    fn test_registry_errors() {
+        assert!(ParticipantRegistry::new(&["a", "b", "a"]).is_err());
+        assert!(ParticipantRegistry::new(&["a", ""]).is_err());
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let crowd = ParticipantRegistry::new(&["1", "2", "3", "4", "5", "6", "7", "8"]).unwrap();
+        assert_eq!(crowd.share(&hsss, 1).err(), Some("more participants than shares in the scheme"));
+        let few = ParticipantRegistry::new(&["1", "2", "3", "4"]).unwrap();
+        assert_eq!(few.share(&hsss, 1).err(), Some("fewer participants than the threshold"));
+        let five = ParticipantRegistry::new(&["1", "2", "3", "4", "5"]).unwrap();
+        let parts = five.share(&hsss, 1).unwrap();
+        assert_eq!(parts.iter().map(|named| named.part.number()).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+        assert_eq!(five.reconstruct(&hsss, parts).unwrap().secret, Ok(1));
+        assert_eq!(crowd.number("3"), Some(2));
+        assert_eq!(crowd.id(8), None);
+    }
+}
@@ -0,0 +1,210 @@
+//! Восстановление с параметрами, задаваемыми для отдельного вызова.
+//!
+//! Вместо отдельного метода на каждое сочетание проверок [ReconstructRequest] собирает
+//! параметры восстановления и возвращает [ReconstructOutcome] с секретом и итогом проверки.
+use crate::passphrase::PassphraseBinding;
+use crate::scheme_impl::Part;
+use crate::validation::ValidationOutcome;
+use crate::{HadamardSSS, SharingScheme};
+use sha2::{Digest, Sha256};
+
+/// Дайджест секрета для параметра [ReconstructRequest::expected_digest].
+///
+/// Секрет из 32 бит находится по дайджесту полным перебором, поэтому дайджест
+/// нужно хранить так же, как сам секрет; он защищает только от ошибок восстановления.
+pub fn secret_digest(secret: u32) -> [u8; 32] {
+    Sha256::new().chain_update(b"hadamard_sss secret").chain_update(secret.to_le_bytes()).finalize().into()
+}
+
+/// Параметры восстановления.
+pub struct ReconstructRequest<'a> {
+    /// Схема, по которой восстанавливается секрет.
+    scheme: &'a HadamardSSS,
+    /// Предъявленные доли.
+    parts: Vec<Part>,
+    /// Отказывать при подозрительных долях.
+    strict: bool,
+    /// Восстанавливать голосованием по большинству.
+    error_correction: bool,
+    /// Наименьшее допустимое число долей.
+    min_shares: usize,
+    /// Ожидаемый дайджест секрета.
+    expected_digest: Option<[u8; 32]>,
+    /// Парольная фраза и параметры привязки к ней.
+    passphrase: Option<(&'a [u8], PassphraseBinding)>,
+}
+
+/// Результат восстановления с диагностикой.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReconstructOutcome {
+    /// Восстановленный секрет.
+    secret: u32,
+    /// Итог проверки предъявленных долей.
+    validation: ValidationOutcome,
+    /// Секрет получен голосованием по большинству.
+    corrected: bool,
+    /// Секрет сверен с ожидаемым дайджестом.
+    digest_verified: bool,
+    /// Маска парольной фразы снята и фраза проверена.
+    passphrase_verified: bool,
+}
+
+/// Реализация методов результата восстановления.
+impl ReconstructOutcome {
+    /// Возвращение значения поля secret.
+    pub fn secret(&self) -> u32 {
+        self.secret
+    }
+
+    /// Возвращение значения поля validation.
+    pub fn validation(&self) -> &ValidationOutcome {
+        &self.validation
+    }
+
+    /// Возвращение значения поля corrected.
+    pub fn corrected(&self) -> bool {
+        self.corrected
+    }
+
+    /// Возвращение значения поля digest_verified.
+    pub fn digest_verified(&self) -> bool {
+        self.digest_verified
+    }
+
+    /// Возвращение значения поля passphrase_verified.
+    pub fn passphrase_verified(&self) -> bool {
+        self.passphrase_verified
+    }
+}
+
+/// Реализация методов запроса на восстановление.
+impl<'a> ReconstructRequest<'a> {
+    /// Отказ, если проверка нашла подозрительные доли (по умолчанию выключен).
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Восстановление голосованием по большинству, как в [HadamardSSS::reconstruct_robust]
+    /// (по умолчанию выключено).
+    pub fn error_correction(mut self, enabled: bool) -> Self {
+        self.error_correction = enabled;
+        self
+    }
+
+    /// Наименьшее число долей. Значения ниже порога схемы не действуют;
+    /// для пониженного порога см. [HadamardSSS::reconstruct_emergency].
+    pub fn min_shares(mut self, min_shares: usize) -> Self {
+        self.min_shares = min_shares.max(self.scheme.threshold);
+        self
+    }
+
+    /// Ожидаемый дайджест секрета, см. [secret_digest].
+    pub fn expected_digest(mut self, digest: [u8; 32]) -> Self {
+        self.expected_digest = Some(digest);
+        self
+    }
+
+    /// Парольная фраза для секрета, разделённого [HadamardSSS::share_with_passphrase].
+    /// Неверная фраза даёт ошибку, а не другой секрет.
+    pub fn passphrase(mut self, passphrase: &'a [u8], binding: PassphraseBinding) -> Self {
+        self.passphrase = Some((passphrase, binding));
+        self
+    }
+
+    /// Восстановление с заданными параметрами.
+    pub fn run(self) -> Result<ReconstructOutcome, &'static str> {
+        let scheme = self.scheme;
+        scheme.check_canaries(&self.parts);
+        if self.parts.len() < scheme.threshold {
+            return Err("less than threshold parties");
+        }
+        if self.parts.len() < self.min_shares {
+            return Err("less than requested number of parties");
+        }
+        let validation = scheme.hss.validate(self.parts.clone());
+        if self.strict && matches!(validation, ValidationOutcome::Suspicious(_)) {
+            return Err("suspicious shares detected");
+        }
+        let mut secret = if self.error_correction {
+            scheme.majority(&self.parts)?
+        } else {
+            scheme.hss.reconstruct(self.parts)?
+        };
+        if let Some((passphrase, binding)) = self.passphrase {
+            secret = binding.unbind(secret, passphrase)?;
+        }
+        if let Some(expected) = self.expected_digest {
+            if secret_digest(secret) != expected {
+                return Err("secret does not match the expected digest");
+            }
+        }
+        Ok(ReconstructOutcome {
+            secret,
+            validation,
+            corrected: self.error_correction,
+            digest_verified: self.expected_digest.is_some(),
+            passphrase_verified: self.passphrase.is_some(),
+        })
+    }
+}
+
+/// Создание запроса на восстановление.
+impl HadamardSSS {
+    /// Запрос на восстановление секрета по данным долям с параметрами по умолчанию,
+    /// которые совпадают с [SharingScheme::reconstruct].
+    pub fn reconstruct_request(&self, parts: Vec<Part>) -> ReconstructRequest<'_> {
+        ReconstructRequest {
+            scheme: self,
+            parts,
+            strict: false,
+            error_correction: false,
+            min_shares: self.threshold,
+            expected_digest: None,
+            passphrase: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_default_matches_reconstruct() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let outcome = hsss.reconstruct_request(parts[1..6].to_vec()).run().unwrap();
+        assert_eq!(outcome.secret(), 314159265);
+        assert!(outcome.validation().is_clean());
+        assert!(!outcome.corrected() && !outcome.digest_verified());
+        assert_eq!(hsss.reconstruct_request(parts[0..4].to_vec()).run(), Err("less than threshold parties"));
+        assert_eq!(
+            hsss.reconstruct_request(parts[0..5].to_vec()).min_shares(6).run(),
+            Err("less than requested number of parties")
+        );
+        assert!(hsss.reconstruct_request(parts[0..5].to_vec()).min_shares(2).run().is_ok());
+    }
+
+    #[test]
+    fn test_options() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut parts = hsss.share(314159265).unwrap();
+        parts[3] = parts[3].blinded(u32::MAX);
+        let digest = secret_digest(314159265);
+
+        let plain = hsss.reconstruct_request(parts.clone()).run().unwrap();
+        assert_ne!(plain.secret(), 314159265);
+        assert_eq!(plain.validation().suspicious(), [3]);
+        assert_eq!(hsss.reconstruct_request(parts.clone()).strict(true).run(), Err("suspicious shares detected"));
+        assert_eq!(
+            hsss.reconstruct_request(parts.clone()).expected_digest(digest).run(),
+            Err("secret does not match the expected digest")
+        );
+
+        let corrected = hsss.reconstruct_request(parts).error_correction(true).expected_digest(digest).run().unwrap();
+        assert_eq!(corrected.secret(), 314159265);
+        assert!(corrected.corrected() && corrected.digest_verified());
+    }
+}
@@ -0,0 +1,126 @@
+//! Восстановление, устойчивое к искажённым долям.
+//!
+//! Каждый столбец матрицы инцидентности 2-(v, k, λ)-схемы содержит k = (v - 1) / 2 единиц,
+//! то есть каждый бит секрета знают ровно k участников. Если предъявлено m долей, бит знают
+//! не меньше k - (v - m) из них, и голосование по большинству среди знающих бит выдерживает
+//! любые c искажённых долей при 2c < k - (v - m).
+use crate::scheme_impl::Part;
+use crate::validation::Tally;
+use crate::HadamardSSS;
+
+/// Доли с различными номерами: точные повторы отбрасываются, а доля, предъявленная
+/// с разными значениями, даёт ошибку. Иначе один участник, повторив поддельную долю,
+/// получил бы несколько голосов.
+pub(crate) fn distinct(parts: &[Part]) -> Result<Vec<Part>, &'static str> {
+    let mut res: Vec<Part> = Vec::with_capacity(parts.len());
+    for part in parts {
+        match res.iter().find(|seen| seen.number() == part.number()) {
+            Some(seen) if seen.data() != part.data() => return Err("conflicting shares with the same number"),
+            Some(_) => {}
+            None => res.push(*part),
+        }
+    }
+    Ok(res)
+}
+
+/// Устойчивое восстановление.
+impl HadamardSSS {
+    /// Восстановление секрета голосованием: каждый бит берётся по большинству предъявленных
+    /// долей, которым он известен. Повторно предъявленная доля учитывается один раз.
+    /// Возвращается ошибка, если различных долей меньше порога, среди них есть доля вне схемы,
+    /// одна доля предъявлена с разными значениями или голоса по какому-то биту разделились поровну.
+    pub fn reconstruct_robust(&self, parts: Vec<Part>) -> Result<u32, &'static str> {
+        self.check_canaries(&parts);
+        self.majority(&parts)
+    }
+
+    /// Голосование по битам различных долей с проверкой порога; голоса считаются [Tally]
+    /// без ветвлений по значениям долей.
+    pub(crate) fn majority(&self, parts: &[Part]) -> Result<u32, &'static str> {
+        self.hss.check_parts(parts)?;
+        let parts = distinct(parts)?;
+        if parts.len() < self.threshold {
+            return Err("less than threshold parties");
+        }
+        let mut tally = Tally::default();
+        for part in &parts {
+            tally.add(self.hss.mask(part.number()), part.data());
+        }
+        let (winners, ties) = tally.majority();
+        if ties != 0 {
+            return Err("tied vote on a bit of the secret");
+        }
+        Ok(winners)
+    }
+
+    /// Число искажённых долей, которое [HadamardSSS::reconstruct_robust] гарантированно
+    /// выдерживает, если предъявлено num_shares различных долей (больше порядка схемы
+    /// предъявить нельзя). При num_shares меньше порога возвращается 0.
+    pub fn cheater_tolerance(&self, num_shares: usize) -> usize {
+        let v = self.hss.mtx_len();
+        let k = (v - 1) / 2;
+        let knowers = k.saturating_sub(v - num_shares.min(v));
+        knowers.saturating_sub(1) / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Combinations;
+    use crate::equivalence::classes;
+    use crate::fixtures::matrix;
+    use crate::SharingScheme;
+
+    #[test]
+    fn test_tolerance_values() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let tolerance: Vec<usize> = (0..=8).map(|m| hsss.cheater_tolerance(m)).collect();
+        assert_eq!(tolerance, [0, 0, 0, 0, 0, 0, 0, 1, 1]);
+        let hsss = HadamardSSS::from_matrix(&classes(16).unwrap().next().unwrap()).unwrap();
+        assert_eq!(hsss.cheater_tolerance(15), 3);
+        assert_eq!(hsss.cheater_tolerance(13), 2);
+        assert_eq!(hsss.cheater_tolerance(9), 0);
+    }
+
+    #[test]
+    fn test_tolerance_is_attained() {
+        let hsss = HadamardSSS::from_matrix(&classes(16).unwrap().next().unwrap()).unwrap();
+        let secret = 314159265;
+        let parts = hsss.share(secret).unwrap();
+        for m in [11, 13, 15] {
+            let tolerance = hsss.cheater_tolerance(m);
+            for cheaters in Combinations::new(m, tolerance) {
+                let mut presented = parts[0..m].to_vec();
+                for &i in &cheaters {
+                    presented[i] = presented[i].blinded(u32::MAX);
+                }
+                assert_eq!(hsss.reconstruct_robust(presented).unwrap(), secret);
+            }
+        }
+        // на один больше: сговор четырёх из семи знающих бит меняет результат
+        let bit = 1 << hsss.hss.mask(0).trailing_zeros();
+        let knowers: Vec<usize> = (0..15).filter(|&i| hsss.hss.mask(i) & bit != 0).collect();
+        assert_eq!(knowers.len(), 7);
+        let mut presented = parts.clone();
+        for &i in &knowers[0..4] {
+            presented[i] = presented[i].blinded(bit);
+        }
+        assert_ne!(hsss.reconstruct_robust(presented), Ok(secret));
+    }
+
+    #[test]
+    fn test_repeated_forgery() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        assert_eq!(hsss.cheater_tolerance(7), 1);
+        let forged = parts[0].blinded(hsss.hss.mask(0));
+        let mut presented = vec![forged, forged, forged];
+        presented.extend_from_slice(&parts[1..7]);
+        assert_eq!(hsss.reconstruct_robust(presented.clone()), Ok(314159265));
+        // повторы не добирают порог
+        assert_eq!(hsss.reconstruct_robust(vec![parts[1]; 5]), Err("less than threshold parties"));
+        presented.push(parts[0]);
+        assert_eq!(hsss.reconstruct_robust(presented), Err("conflicting shares with the same number"));
+    }
+}
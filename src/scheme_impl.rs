@@ -1,11 +1,13 @@
 //! Реализация библиотечных структур.
 
 use crate::scheme_traits::SharingScheme;
-use crate::hadamard_matrix::HadamardMatrix;
+use crate::layout::{ByteOrder, Layout};
+use crate::hadamard_matrix::{check_design, DesignError};
+use crate::validation::{judge, ValidationOutcome, ValidationPolicy};
 use rand::Rng;
 use anyhow::Result;
-use ndarray::{arr2, Array2};
-use std::mem::size_of;
+use ndarray::Array2;
+use sha2::{Digest, Sha256};
 
 #[cfg(feature = "zeroize_memory")]
 use zeroize::Zeroize;
@@ -13,13 +15,20 @@ use zeroize::Zeroize;
 #[derive(Clone)]
 #[derive(Copy)]
 #[cfg_attr(feature = "zeroize_memory", derive(Zeroize))]
-#[cfg_attr(feature = "zeroize_memory", zeroize(drop))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
 /// Структура отдельной доли, получаемой при разделении секрета.
+/// С функцией `serde` доля сериализуется как структура с полями `number`, `data`
+/// и необязательным `fingerprint`.
 pub struct Part {
     /// Номер, соответвуюший строке матрицы Адамара, по которой была получена доля.
     number: usize,
     /// Значение доли.
     data: u32,
+    /// Отпечаток схемы, в которой получена доля, см. [HSS::fingerprint]. Доли, собранные
+    /// вручную или прочитанные из форматов без отпечатка, его не имеют.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    fingerprint: Option<u32>,
 }
 
 /// Реализация методов структуры доли.
@@ -29,9 +38,15 @@ impl Part {
         Part{
             number: number_,
             data: data_,
+            fingerprint: None,
         }
     }
 
+    /// Доля с данным отпечатком схемы.
+    pub fn with_fingerprint(self, fingerprint: u32) -> Self {
+        Part { fingerprint: Some(fingerprint), ..self }
+    }
+
     /// Возвращение значения поля number.
     pub fn number(&self) -> usize {
         self.number
@@ -41,28 +56,227 @@ impl Part {
     pub fn data(&self) -> u32 {
         self.data
     }
+
+    /// Возвращение значения поля fingerprint.
+    pub fn fingerprint(&self) -> Option<u32> {
+        self.fingerprint
+    }
+
+    /// Доля, ослеплённая маской: значение складывается с маской по модулю 2.
+    /// Из ослеплённых одной маской долей восстанавливается secret ⊕ mask.
+    pub fn blinded(&self, mask: u32) -> Self {
+        Part { data: self.data ^ mask, ..*self }
+    }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "zeroize_memory", derive(Zeroize))]
 #[cfg_attr(feature = "zeroize_memory", zeroize(drop))]
+/// Доля секрета, состоящего из нескольких блоков типа u32.
+/// Каждый блок разделяется независимо, доля участника хранит свои значения по всем блокам.
+pub struct ChunkedPart {
+    /// Номер, соответвуюший строке матрицы Адамара, по которой была получена доля.
+    number: usize,
+    /// Значения доли по каждому блоку секрета.
+    data: Vec<u32>,
+    /// Отпечаток схемы, в которой получены блоки, см. [Part::fingerprint].
+    fingerprint: Option<u32>,
+}
+
+/// Реализация методов структуры составной доли.
+impl ChunkedPart {
+    /// Создание экземпляра структуры [scheme_impl::ChunkedPart] по данному номеру и значениям.
+    pub fn from(number: usize, data: Vec<u32>) -> Self {
+        ChunkedPart { number, data, fingerprint: None }
+    }
+
+    /// Доля с данным отпечатком схемы.
+    pub fn with_fingerprint(mut self, fingerprint: u32) -> Self {
+        self.fingerprint = Some(fingerprint);
+        self
+    }
+
+    /// Возвращение значения поля fingerprint.
+    pub fn fingerprint(&self) -> Option<u32> {
+        self.fingerprint
+    }
+
+    /// Возвращение значения поля number.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Возвращение значений доли по блокам.
+    pub fn data(&self) -> &[u32] {
+        &self.data
+    }
+
+    /// Доля, соответствующая блоку с номером block, если такой блок есть; отпечаток
+    /// составной доли переходит к ней.
+    pub fn block(&self, block: usize) -> Option<Part> {
+        self.data.get(block).map(|&data| Part { number: self.number, data, fingerprint: self.fingerprint })
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
 /// Структура схемы разделения секрета, содержащая поле с матрицей инцидентности.
 pub struct HSS {
     /// Матрица инцидентности, построенная по матрице Адамара.
     mtx: Array2<i32>,
+    /// Номер столбца матрицы инцидентности, за которым закреплён каждый бит секрета.
+    columns: Vec<usize>,
+    /// Порядок байтов значения доли в байтовом виде.
+    byte_order: ByteOrder,
+    /// Отпечаток матрицы инцидентности и раскладки битов.
+    fingerprint: u32,
+    /// Принимаются ли доли без отпечатка.
+    accept_legacy: bool,
+}
+
+/// Затирание матрицы инцидентности при удалении структуры.
+#[cfg(feature = "zeroize_memory")]
+impl Zeroize for HSS {
+    fn zeroize(&mut self) {
+        self.mtx.iter_mut().for_each(Zeroize::zeroize);
+        self.columns.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize_memory")]
+impl Drop for HSS {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// Отпечаток матрицы инцидентности, столбцов битов секрета и порядка байтов.
+fn fingerprint(mtx: &Array2<i32>, columns: &[usize], byte_order: ByteOrder) -> u32 {
+    let mut hasher = Sha256::new();
+    hasher.update(b"hadamard_sss fingerprint");
+    hasher.update((mtx.shape()[0] as u64).to_le_bytes());
+    hasher.update(mtx.iter().map(|&x| x as u8).collect::<Vec<u8>>());
+    for &col in columns {
+        hasher.update((col as u64).to_le_bytes());
+    }
+    hasher.update([byte_order as u8]);
+    let digest = hasher.finalize();
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]])
 }
 
 /// Реализация базовых методов структуры схемы разделения секрета.
 impl HSS {
-    /// Создание экземпляра структуры по данной матрице инцидентности.
-    pub fn from(mtx: &Array2<i32>) -> Self {
-        HSS {
-            mtx:mtx.clone()
+    /// Разрядность секрета в битах.
+    const SECRET_BITS: usize = u32::BITS as usize;
+
+    /// Создание экземпляра структуры по данной матрице инцидентности
+    /// с раскладкой битов по умолчанию.
+    pub fn from(mtx: &Array2<i32>) -> Result<Self, &'static str> {
+        HSS::with_layout(mtx, &Layout::default())
+    }
+
+    /// Создание экземпляра структуры по данной матрице инцидентности и раскладке битов [layout::Layout].
+    /// Матрица должна быть квадратной, состоять из 0 и 1 и задавать блок-дизайн
+    /// 2-(4n-1, 2n-1, n-1), иначе возвращается ошибка.
+    pub fn with_layout(mtx: &Array2<i32>, layout: &Layout) -> Result<Self, &'static str> {
+        check_design(mtx.view()).map_err(|err| match err {
+            DesignError::Shape { .. } => "incidence matrix is not square of order 4n-1",
+            DesignError::Entry { .. } => "incidence matrix contains values other than 0 and 1",
+            DesignError::BlockSize { .. } | DesignError::PairCount { .. } => "incidence matrix is not a 2-(4n-1, 2n-1, n-1) design",
+        })?;
+        let columns = layout.columns(mtx.shape()[0], Self::SECRET_BITS)?;
+        Ok(HSS {
+            mtx: mtx.clone(),
+            fingerprint: fingerprint(mtx, &columns, layout.byte_order),
+            columns,
+            byte_order: layout.byte_order,
+            accept_legacy: false,
+        })
+    }
+
+    /// Отпечаток схемы: первые 4 байта SHA-256 от порядка и элементов матрицы инцидентности,
+    /// столбцов, за которыми закреплены биты секрета, и порядка байтов.
+    /// Доли, полученные разделением, несут отпечаток, и доли с чужим отпечатком или без
+    /// отпечатка отклоняются при восстановлении и считаются подозрительными при проверке.
+    pub fn fingerprint(&self) -> u32 {
+        self.fingerprint
+    }
+
+    /// Разрешение принимать доли без отпечатка, например прочитанные из форматов версии 1
+    /// или собранные вручную через [Part::from]. По умолчанию такие доли отклоняются.
+    pub fn set_accept_legacy(&mut self, accept: bool) {
+        self.accept_legacy = accept;
+    }
+
+    /// Доля получена не в этой схеме: номера нет в схеме, отпечаток чужой или его нет,
+    /// а доли без отпечатка не принимаются.
+    pub(crate) fn is_foreign(&self, part: &Part) -> bool {
+        part.number >= self.mtx_len() || part.fingerprint.map_or(!self.accept_legacy, |f| f != self.fingerprint)
+    }
+
+    /// Проверка, что доли получены в этой схеме, см. [HSS::is_foreign].
+    pub(crate) fn check_parts(&self, parts: &[Part]) -> Result<(), &'static str> {
+        if parts.iter().any(|part| part.number >= self.mtx_len()) {
+            return Err("scheme mismatch: part number exceeds the order of the scheme");
+        }
+        if parts.iter().any(|part| part.fingerprint.is_some_and(|f| f != self.fingerprint)) {
+            return Err("scheme mismatch: part fingerprint does not match the scheme");
+        }
+        if !self.accept_legacy && parts.iter().any(|part| part.fingerprint.is_none()) {
+            return Err("scheme mismatch: part has no fingerprint");
+        }
+        Ok(())
+    }
+
+    /// Возвращение значения поля byte_order.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Возвращение значения поля columns.
+    pub(crate) fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// Создание экземпляра структуры по матрице инцидентности и уже вычисленным столбцам
+    /// битов секрета, например сохранённым [crate::persist].
+    pub(crate) fn with_columns(mtx: &Array2<i32>, columns: Vec<usize>, byte_order: ByteOrder) -> Result<Self, &'static str> {
+        let mut hss = HSS::from(mtx)?;
+        if columns.len() != Self::SECRET_BITS || columns.iter().any(|&col| col >= hss.mtx_len()) {
+            return Err("columns must map every bit of the secret to a column of the matrix");
         }
+        hss.fingerprint = fingerprint(mtx, &columns, byte_order);
+        hss.columns = columns;
+        hss.byte_order = byte_order;
+        Ok(hss)
     }
 
     /// Возвращение размерности хранимой матрицы -- максимального числа долей, на которые будет разбит секрет.
     pub fn mtx_len(&self) -> usize {
-        self.mtx.len()
+        self.mtx.shape()[0]
+    }
+
+    /// Проверка, известен ли участнику с данным номером бит секрета с номером bit.
+    fn knows(&self, number: usize, bit: usize) -> bool {
+        self.mtx.get([number, self.columns[bit]]) == Some(&1)
+    }
+
+    /// Маска битов секрета, известных участнику с данным номером. Участнику с номером
+    /// вне схемы не известен ни один бит.
+    pub fn mask(&self, number: usize) -> u32 {
+        (0..Self::SECRET_BITS)
+            .filter(|&bit| self.knows(number, bit))
+            .fold(0, |mask, bit| mask | (1 << bit))
+    }
+
+    /// Разделение секрета с заданными битами-заполнителями: padding(i) возвращает слово,
+    /// из которого берутся биты i-й доли, не известные её владельцу.
+    pub fn share_padded(&self, secret: u32, mut padding: impl FnMut(usize) -> u32) -> Vec<Part> {
+        (0..self.mtx_len())
+            .map(|i| {
+                let mask = self.mask(i);
+                Part { number: i, data: (secret & mask) | (padding(i) & !mask), fingerprint: Some(self.fingerprint) }
+            })
+            .collect()
     }
 }
 
@@ -76,58 +290,34 @@ impl SharingScheme for HSS {
     ///
     /// В цикле по i обрабатывается i-я строка матрицы инцидентности.
     ///
-    /// Цикл по s_ind обеспечивает пробегание по всем 32-м битам секрета, в случае размерности матрицы инцидентности меньшей 32.
-    ///
-    /// Рассмотрим, что происходит с j_id-м битом секрета (j_id = j + s_ind * n):
-    /// - mtx[[i, j]] == 1, j_id-й бит приравнивается j_id-му биту секрета
-    /// - mtx[[i, j]] == 0, j_id-й бит приравнивается рандомному значению {0, 1}
+    /// Рассмотрим, что происходит с bit-м битом секрета, закреплённым за столбцом j = columns[bit]:
+    /// - mtx[[i, j]] == 1, bit-й бит приравнивается bit-му биту секрета
+    /// - mtx[[i, j]] == 0, bit-й бит приравнивается рандомному значению {0, 1}
     ///
     /// # Пример.
-    /// ```
+    /// ```ignore
     /// let res = hss.share(secret).unwrap();
     /// ```
     fn share(&self, secret: Self::SecretType) -> Result<Vec<Part>, Self::Error> {
-        let n = self.mtx.shape()[0];
-        let mut rng = rand::thread_rng();
-        let mut res: Vec<Part> = vec![Part{number: 0, data: 0}; n];
-        let secret_size = size_of::<Self::SecretType>() * 8;
-        let times = (secret_size + n - 1) / n;
-        for i in 0..n {
-            res[i].number = i;
-            for s_ind in 0..times {
-                for j in 0..n {
-                    let j_id = j + s_ind * n;
-                    if j_id < secret_size {
-                        if self.mtx[[i, j]] == 1 {
-                            res[i].data |= (1 << j_id) & secret;
-                        } else {
-                            res[i].data |= (1 << j_id) * rng.gen_range(0..=1);
-                        }
-                    }
-                }
-            }
-        }
-        Ok(res)
+        let mut rng = crate::entropy::rng();
+        Ok(self.share_padded(secret, |_| rng.gen()))
     }
 
     /// Восстановление секрета по данному набору долей. Не происходит никаких проверок. Как следствие, в случае ошибки в какой-то доли, восстановленный секрет может отличаться от исходного.
     ///
-    /// Проходимся по строке матрицы Адамара, если в j-м элементе стоит 1, то в итоговом значении секрета соответствующему j_id-му биту проставляем j_id-й бит из доли.
+    /// Проходимся по строке матрицы инцидентности, если в столбце, за которым закреплён bit-й бит,
+    /// стоит 1, то в итоговом значении секрета bit-й бит проставляем из доли.
+    ///
+    /// Доля с номером, не меньшим порядка матрицы, получена в схеме другого порядка,
+    /// а доля с чужим отпечатком -- по другой матрице или раскладке; в этих случаях, как и для
+    /// доли без отпечатка (если такие не разрешены [HSS::set_accept_legacy]), возвращается ошибка.
     fn reconstruct(&self, parts: Vec<Part>) -> Result<Self::SecretType, Self::Error> {
-        let n = self.mtx.shape()[0];
+        self.check_parts(&parts)?;
         let mut res: Self::SecretType = 0;
-        let secret_size = size_of::<Self::SecretType>() * 8;
-        let times = (secret_size + n - 1) / n;
-        for i in 0..parts.len() {
-            let ind = parts[i].number;
-            for s_ind in 0..times {
-                for j in 0..n {
-                    let j_id = j + s_ind * n;
-                    if j_id < secret_size {
-                        if self.mtx[[ind, j]] == 1 {
-                            res |= (1 << j_id) & parts[i].data;
-                        }
-                    }
+        for part in &parts {
+            for bit in 0..Self::SECRET_BITS {
+                if self.knows(part.number, bit) {
+                    res |= (1 << bit) & part.data;
                 }
             }
         }
@@ -136,52 +326,125 @@ impl SharingScheme for HSS {
 
     /// Проверка на корректность пришедшего набора долей.
     ///
-    /// Формируем трёхмерный вектор cells[bit_number][bit_value][part_number] хранящий
-    /// информацию для каждого j_id-го бита, номера каких частей дают значение 1, а каких 0.
+    /// Первым проходом для каждого бита секрета считаем, сколько знающих его долей дают
+    /// значение 1 (ones[bit]) и сколько 0 (zeros[bit]). Бит спорный, если оба счётчика ненулевые.
     ///
-    /// Далее по вектору cells определяем подозрительные части -- в вектор флагов suspicious
-    /// в случае присутствия одновременно номеров долей в cells[i][0] и cells[i][1]
-    /// проставляем 1 в индексах элементов, соответвуюших долям из наименьшего множества
-    /// (из cells[i][0] или cells[i][1]).
+    /// Вторым проходом доля считается подозрительной, если хотя бы по одному спорному биту
+    /// она попала в наименьшую из двух групп (при равенстве -- в группу давших 0).
+    /// Другие правила задаются [ValidationPolicy], см. [crate::HadamardSSS::validate_with].
+    /// Счётчики хранятся в массивах фиксированного размера, память выделяется только под заключения.
     ///
-    /// По проставленным флагам формируем вектор, хранящий номера подозрительных долей.
-    fn validate(&self, parts: Vec<Part>) -> Vec<usize> {
-        let n = self.mtx.shape()[0];
-        let secret_size = size_of::<Self::SecretType>() * 8;
-        let times = (secret_size + n - 1) / n;
-        let mut cells: Vec<Vec<Vec<i32>>> = vec![vec![vec![]; 2]; secret_size];
-        for i in 0..parts.len() {
-            let ind = parts[i].number;
-            for s_ind in 0..times {
-                for j in 0..n {
-                    let j_id = j + s_ind * n;
-                    if j_id < secret_size {
-                        if self.mtx[[ind, j]] == 1{
-                            let bit = (((1 << j_id) & parts[i].data) > 0) as usize;
-                            cells[j_id][bit].push(ind as i32);
-                        }
-                    }
-                }
-            }
+    /// Заключения идут по возрастанию номеров долей. Доли с номерами, не меньшими порядка
+    /// матрицы (из схемы другого порядка), доли с чужим отпечатком и без отпечатка
+    /// (если такие не разрешены) всегда считаются подозрительными и в голосовании не участвуют.
+    /// Если ни один бит не известен сразу двум долям, итог -- [ValidationOutcome::Inconclusive].
+    fn validate(&self, parts: Vec<Part>) -> ValidationOutcome {
+        judge(|part| self.is_foreign(part), |number| self.mask(number), &parts, &ValidationPolicy::default())
+    }
+}
+
+
+/// Соответствие участников строкам матрицы. Участник с весом w получает w строк,
+/// и его доля засчитывается в порог как w долей. Доля участника -- [ChunkedPart]
+/// с номером участника, i-й блок которой -- значение доли i-й строки участника.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParticipantRows {
+    /// Строки каждого участника по возрастанию.
+    rows: Vec<Vec<usize>>,
+}
+
+/// Реализация методов соответствия участников строкам.
+impl ParticipantRows {
+    /// Участникам выдаются подряд идущие строки в количестве их весов.
+    /// Возвращается ошибка, если какой-то вес равен нулю.
+    pub fn weighted(weights: &[usize]) -> Result<Self, &'static str> {
+        if weights.contains(&0) {
+            return Err("participant weight must be positive");
         }
+        let mut next = 0;
+        let rows = weights
+            .iter()
+            .map(|&weight| {
+                next += weight;
+                (next - weight..next).collect()
+            })
+            .collect();
+        Ok(ParticipantRows { rows })
+    }
 
-        let mut suspicious: Vec<bool> = vec![false; secret_size];
-        for i in 0..secret_size {
-            if !cells[i][0].is_empty() && !cells[i][1].is_empty() {
-                let more = (cells[i][0].len() > cells[i][1].len()) as usize;
-                for ind in &cells[i][more] {
-                    suspicious[*ind as usize] = true;
-                }
-            }
+    /// Явно заданные строки участников. Возвращается ошибка, если у участника нет строк
+    /// или строка выдана дважды.
+    pub fn from_rows(mut rows: Vec<Vec<usize>>) -> Result<Self, &'static str> {
+        if rows.iter().any(Vec::is_empty) {
+            return Err("participant weight must be positive");
         }
+        rows.iter_mut().for_each(|participant| participant.sort_unstable());
+        let mut all: Vec<usize> = rows.iter().flatten().copied().collect();
+        all.sort_unstable();
+        if all.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("row is assigned to several participants");
+        }
+        Ok(ParticipantRows { rows })
+    }
 
-        let mut res: Vec<usize> = Vec::new();
-        for i in 0..secret_size {
-            if suspicious[i] {
-                res.push(i);
+    /// Число участников.
+    pub fn participants(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Строки участника.
+    pub fn rows(&self, participant: usize) -> Option<&[usize]> {
+        self.rows.get(participant).map(Vec::as_slice)
+    }
+
+    /// Вес участника: число его строк; 0 для участника вне соответствия.
+    pub fn weight(&self, participant: usize) -> usize {
+        self.rows.get(participant).map_or(0, Vec::len)
+    }
+
+    /// Участник, которому выдана строка.
+    pub fn owner(&self, row: usize) -> Option<usize> {
+        self.rows.iter().position(|rows| rows.contains(&row))
+    }
+
+    /// Наибольший номер выданной строки плюс один.
+    pub fn span(&self) -> usize {
+        self.rows.iter().flatten().max().map_or(0, |&row| row + 1)
+    }
+
+    /// Доли участников по долям всех строк. Доли строк одного участника должны иметь
+    /// один отпечаток, он переходит к доле участника.
+    pub fn group(&self, parts: &[Part]) -> Result<Vec<ChunkedPart>, &'static str> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(participant, rows)| {
+                let own = rows.iter().map(|&row| parts.iter().find(|part| part.number() == row).copied());
+                let own: Vec<Part> = own.collect::<Option<_>>().ok_or("missing share of a participant row")?;
+                let fingerprint = own.first().and_then(Part::fingerprint);
+                if own.iter().any(|part| part.fingerprint != fingerprint) {
+                    return Err("participant rows have different fingerprints");
+                }
+                let data = own.iter().map(Part::data).collect();
+                Ok(ChunkedPart { number: participant, data, fingerprint })
+            })
+            .collect()
+    }
+
+    /// Доли строк по долям различных участников; повторно предъявленные доли участника пропускаются.
+    pub fn expand(&self, parts: &[ChunkedPart]) -> Result<Vec<Part>, &'static str> {
+        let mut seen = vec![false; self.participants()];
+        let mut res = Vec::new();
+        for part in parts {
+            let rows = self.rows(part.number()).ok_or("scheme mismatch: participant number exceeds the number of participants")?;
+            if part.data().len() != rows.len() {
+                return Err("participant share has wrong number of blocks");
+            }
+            if !std::mem::replace(&mut seen[part.number()], true) {
+                res.extend(rows.iter().zip(part.data()).map(|(&row, &data)| Part { number: row, data, fingerprint: part.fingerprint }));
             }
         }
-        res
+        Ok(res)
     }
 }
 
@@ -189,6 +452,8 @@ impl SharingScheme for HSS {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hadamard_matrix::HadamardMatrix;
+    use ndarray::arr2;
 
     #[test]
     fn test_reconstruction() {
@@ -203,7 +468,7 @@ mod tests {
             .unwrap()
             .normalize()
             .get_incidence();
-        let hss = HSS::from(&h_mtx);
+        let hss = HSS::from(&h_mtx).unwrap();
         for secret in 0..100 {
             let res = hss.share(secret).unwrap();
             let secret_res = hss.reconstruct(res[0..5].to_vec()).unwrap();
@@ -224,20 +489,94 @@ mod tests {
             .unwrap()
             .normalize()
             .get_incidence();
-        let hss = HSS::from(&h_mtx);
+        let hss = HSS::from(&h_mtx).unwrap();
         for secret in 0..100 {
             let res = hss.share(secret).unwrap();
-            let valid = hss.validate(res[0..5].to_vec()).is_empty();
+            let valid = hss.validate(res[0..5].to_vec()).is_clean();
             let secret_res = hss.reconstruct(res[0..5].to_vec()).unwrap();
             println!("secret {}, secret_res {}", secret, secret_res);
             assert_eq!(valid, (secret == secret_res));
         }
         for secret in 0..100 {
             let mut res = hss.share(secret).unwrap();
-            res[0] = Part::from(res[0].number(), res[0].data() ^ 43);
-            let valid = hss.validate(res[0..5].to_vec()).is_empty();
+            res[0] = res[0].blinded(43);
+            let valid = hss.validate(res[0..5].to_vec()).is_clean();
             let secret_res = hss.reconstruct(res[0..5].to_vec()).unwrap();
             assert_eq!(valid, (secret == secret_res));
         }
     }
+
+    #[test]
+    fn test_foreign_parts() {
+        let h_mtx = HadamardMatrix::from(&arr2(&[[1, 1, 1, 1],
+                                                [1, -1, 1, -1],
+                                                [1, 1, -1, -1],
+                                                [1, -1, -1, 1]]))
+            .unwrap()
+            .get_incidence();
+        let hss = HSS::from(&h_mtx).unwrap();
+        let mut parts = hss.share(77).unwrap();
+        parts.push(Part::from(5, 77));
+        assert_eq!(hss.reconstruct(parts.clone()), Err("scheme mismatch: part number exceeds the order of the scheme"));
+        assert_eq!(hss.validate(parts).suspicious(), vec![5]);
+    }
+
+    #[test]
+    fn test_from_rejects_broken_incidence() {
+        assert_eq!(HSS::from(&arr2(&[[0, 1, 0], [1, 0, 0]])).err(), Some("incidence matrix is not square of order 4n-1"));
+        assert_eq!(HSS::from(&arr2(&[[0]])).err(), Some("incidence matrix is not square of order 4n-1"));
+        assert_eq!(HSS::from(&arr2(&[[0, -1, 0], [1, 0, 0], [0, 0, 1]])).err(),
+                   Some("incidence matrix contains values other than 0 and 1"));
+        assert_eq!(HSS::from(&arr2(&[[1, 1, 0], [1, 0, 0], [0, 0, 1]])).err(),
+                   Some("incidence matrix is not a 2-(4n-1, 2n-1, n-1) design"));
+        // матрица инцидентности ненормализованной матрицы Адамара
+        let unnormalized = HadamardMatrix::from(&arr2(&[[1, -1, 1, 1],
+                                                        [1, 1, 1, -1],
+                                                        [1, -1, -1, -1],
+                                                        [1, 1, -1, 1]])).unwrap();
+        assert!(HSS::from(&unnormalized.get_incidence()).is_err());
+        assert!(HSS::from(&unnormalized.normalized().get_incidence()).is_ok());
+    }
+
+    #[test]
+    fn test_participant_rows() {
+        let mapping = ParticipantRows::weighted(&[3, 1, 2]).unwrap();
+        assert_eq!(mapping.rows(0), Some(&[0, 1, 2][..]));
+        assert_eq!(mapping.rows(2), Some(&[4, 5][..]));
+        assert_eq!((mapping.weight(1), mapping.weight(3), mapping.owner(4), mapping.span()), (1, 0, Some(2), 6));
+        assert!(ParticipantRows::weighted(&[1, 0]).is_err());
+        assert!(ParticipantRows::from_rows(vec![vec![0, 2], vec![2]]).is_err());
+        assert_eq!(ParticipantRows::from_rows(vec![vec![6, 0], vec![3]]).unwrap().rows(0), Some(&[0, 6][..]));
+
+        let mut parts: Vec<Part> = (0..6).map(|row| Part::from(row, row as u32 * 10).with_fingerprint(9)).collect();
+        let grouped = mapping.group(&parts).unwrap();
+        assert_eq!(grouped[2], ChunkedPart::from(2, vec![40, 50]).with_fingerprint(9));
+        let expanded = mapping.expand(&[grouped[2].clone(), grouped[1].clone(), grouped[2].clone()]).unwrap();
+        assert_eq!(expanded.iter().map(|part| (part.number(), part.data())).collect::<Vec<_>>(), [(4, 40), (5, 50), (3, 30)]);
+        assert!(expanded.iter().all(|part| part.fingerprint() == Some(9)));
+        assert_eq!(grouped[2].block(1).and_then(|part| part.fingerprint()), Some(9));
+        assert!(mapping.group(&parts[0..5]).is_err());
+        parts[5] = Part::from(5, 50);
+        assert_eq!(mapping.group(&parts).err(), Some("participant rows have different fingerprints"));
+        assert!(mapping.expand(&[ChunkedPart::from(3, vec![0])]).is_err());
+        assert!(mapping.expand(&[ChunkedPart::from(0, vec![0])]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_fields() {
+        use serde::de::value::{Error, MapDeserializer};
+        use serde::Deserialize;
+        fn serializable<T: serde::Serialize>(_: &T) {}
+
+        let fields = MapDeserializer::<_, Error>::new([("number", 3u64), ("data", 0xDEADBEEF)].into_iter());
+        let part = Part::deserialize(fields).unwrap();
+        assert_eq!((part.number(), part.data()), (3, 0xDEADBEEF));
+        serializable(&part);
+        let extra = MapDeserializer::<_, Error>::new([("number", 3u64), ("data", 1), ("salt", 2)].into_iter());
+        assert!(Part::deserialize(extra).is_err());
+        let wide = MapDeserializer::<_, Error>::new([("number", 3u64), ("data", 1 << 32)].into_iter());
+        assert!(Part::deserialize(wide).is_err());
+        assert_eq!(part.fingerprint(), None);
+    }
 }
@@ -2,16 +2,164 @@
 
 use std::vec::Vec;
 use anyhow::Result;
+use crate::keys::{pack_bytes, unpack_bytes};
+use crate::validation::ValidationOutcome;
 
+/// Схема разделения секрета. Трейт открыт для реализации сторонними схемами;
+/// все реализации автоматически получают вспомогательные методы [SchemeExt].
 pub trait SharingScheme {
+    /// Тип ошибки схемы.
     type Error;
+    /// Тип разделяемого секрета.
     type SecretType;
+    /// Тип доли.
     type PartType;
     /// Разделение секрета на доли.
     fn share(&self, secret: Self::SecretType) -> Result<Vec<Self::PartType>, Self::Error>;
     /// Восстановление секрета по вектору долей.
     fn reconstruct(&self, shares: Vec<Self::PartType>) -> Result<Self::SecretType, Self::Error>;
-    /// Валидация множества долей: возвращение номеров долей, предположительно
+    /// Валидация множества долей: заключения о долях, предположительно
     /// используемых злоумышленниками.
-    fn validate(&self, shares: Vec<Self::PartType>) -> Vec<usize>;
+    fn validate(&self, shares: Vec<Self::PartType>) -> ValidationOutcome;
+}
+
+/// Реализация [SharingScheme] для ссылок и умных указателей на схему,
+/// чтобы схему можно было передавать в обобщённый код без обёрток.
+macro_rules! forward_scheme {
+    ($($pointer:ty),*) => {$(
+        impl<S: SharingScheme + ?Sized> SharingScheme for $pointer {
+            type Error = S::Error;
+            type SecretType = S::SecretType;
+            type PartType = S::PartType;
+
+            fn share(&self, secret: Self::SecretType) -> Result<Vec<Self::PartType>, Self::Error> {
+                (**self).share(secret)
+            }
+
+            fn reconstruct(&self, shares: Vec<Self::PartType>) -> Result<Self::SecretType, Self::Error> {
+                (**self).reconstruct(shares)
+            }
+
+            fn validate(&self, shares: Vec<Self::PartType>) -> ValidationOutcome {
+                (**self).validate(shares)
+            }
+        }
+    )*};
+}
+
+forward_scheme!(&S, Box<S>, std::sync::Arc<S>);
+
+mod sealed {
+    /// Закрытый трейт, не позволяющий реализовывать [super::SchemeExt] вне библиотеки.
+    pub trait Sealed {}
+
+    impl<T: super::SharingScheme + ?Sized> Sealed for T {}
+}
+
+/// Вспомогательные методы, доступные любой схеме [SharingScheme].
+///
+/// Трейт запечатан: реализовать его самостоятельно нельзя, он реализован для всех схем.
+pub trait SchemeExt: SharingScheme + sealed::Sealed {
+    /// Разделение байтовой строки произвольной длины на схеме с секретом u32.
+    ///
+    /// Строка упаковывается в блоки u32 (первый блок хранит длину), каждый блок разделяется
+    /// отдельно. i-й элемент результата содержит доли i-го участника по всем блокам;
+    /// предполагается, что share возвращает доли участников в одном и том же порядке.
+    fn share_bytes(&self, secret: &[u8]) -> Result<Vec<Vec<Self::PartType>>, Self::Error>
+    where
+        Self: SharingScheme<SecretType = u32>,
+        Self::Error: From<&'static str>,
+    {
+        let mut res: Vec<Vec<Self::PartType>> = Vec::new();
+        for word in pack_bytes(secret)? {
+            let parts = self.share(word)?;
+            if res.is_empty() {
+                res.resize_with(parts.len(), Vec::new);
+            }
+            if parts.len() != res.len() {
+                return Err("scheme returned different number of parts".into());
+            }
+            for (participant, part) in res.iter_mut().zip(parts) {
+                participant.push(part);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Восстановление байтовой строки, разделённой с помощью [SchemeExt::share_bytes].
+    fn reconstruct_bytes(&self, shares: Vec<Vec<Self::PartType>>) -> Result<Vec<u8>, Self::Error>
+    where
+        Self: SharingScheme<SecretType = u32>,
+        Self::Error: From<&'static str>,
+    {
+        let blocks = shares.first().map_or(0, Vec::len);
+        if shares.iter().any(|participant| participant.len() != blocks) {
+            return Err("participants have different number of blocks".into());
+        }
+        let mut iters: Vec<_> = shares.into_iter().map(Vec::into_iter).collect();
+        let mut words = Vec::with_capacity(blocks);
+        for _ in 0..blocks {
+            let parts = iters.iter_mut().filter_map(Iterator::next).collect();
+            words.push(self.reconstruct(parts)?);
+        }
+        Ok(unpack_bytes(&words)?)
+    }
+
+    /// Восстановление секрета, только если проверка не нашла подозрительных долей.
+    fn reconstruct_strict(&self, shares: Vec<Self::PartType>) -> Result<Self::SecretType, Self::Error>
+    where
+        Self::PartType: Clone,
+        Self::Error: From<&'static str>,
+    {
+        if let ValidationOutcome::Suspicious(_) = self.validate(shares.clone()) {
+            return Err("suspicious shares detected".into());
+        }
+        self.reconstruct(shares)
+    }
+}
+
+impl<T: SharingScheme + ?Sized> SchemeExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, HadamardSSS, Part};
+
+    #[test]
+    fn test_share_bytes_roundtrip() {
+        let hsss = HadamardSSS::from(&fixtures::matrix()).unwrap();
+        for secret in [&b""[..], b"a", b"correct horse battery staple"] {
+            let shares = hsss.share_bytes(secret).unwrap();
+            assert_eq!(shares.len(), 7);
+            assert_eq!(hsss.reconstruct_bytes(shares[2..7].to_vec()).unwrap(), secret);
+        }
+        let mut shares = hsss.share_bytes(b"abc").unwrap();
+        shares[0].pop();
+        assert!(hsss.reconstruct_bytes(shares).is_err());
+    }
+
+    #[test]
+    fn test_pointer_impls() {
+        fn roundtrip<S: SharingScheme<SecretType = u32, PartType = Part>>(scheme: S) -> u32 {
+            let parts = scheme.share(314159265).ok().unwrap();
+            scheme.reconstruct(parts).ok().unwrap()
+        }
+        let hsss = HadamardSSS::from(&fixtures::matrix()).unwrap();
+        assert_eq!(roundtrip(&hsss), 314159265);
+        let shared = std::sync::Arc::new(hsss);
+        assert_eq!(roundtrip(std::sync::Arc::clone(&shared)), 314159265);
+        let boxed: Box<dyn SharingScheme<Error = &'static str, SecretType = u32, PartType = Part>> =
+            Box::new(HadamardSSS::from(&fixtures::matrix()).unwrap());
+        assert_eq!(roundtrip(boxed), 314159265);
+        assert_eq!(shared.reconstruct_strict(shared.share(7).unwrap()), Ok(7));
+    }
+
+    #[test]
+    fn test_reconstruct_strict() {
+        let hsss = HadamardSSS::from(&fixtures::matrix()).unwrap();
+        let mut parts = hsss.share(314159265).unwrap();
+        assert_eq!(hsss.reconstruct_strict(parts[0..5].to_vec()).unwrap(), 314159265);
+        parts[1] = parts[1].blinded(0xFF);
+        assert!(hsss.reconstruct_strict(parts[0..5].to_vec()).is_err());
+    }
 }
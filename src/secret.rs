@@ -0,0 +1,151 @@
+//! Секреты разной разрядности.
+//!
+//! Схема разделяет блоки u32, поэтому секрет другого типа раскладывается на блоки
+//! трейтом [ShareableSecret] и разделяется через [HadamardSSS::share_words]. Один и тот же
+//! объект схемы защищает секреты любой разрядности, для которой реализован трейт.
+//! Строки произвольной длины разделяются [HadamardSSS::share_str].
+//!
+//! В отличие от исходного запроса, [HadamardSSS] и внутренняя схема не параметризованы типом
+//! секрета: тип выбирается при вызове [HadamardSSS::share_secret], а не при построении схемы.
+//! Реализация [crate::SharingScheme] по-прежнему разделяет u32 (`SecretType = u32`), доли
+//! [crate::Part], их форматы и проверки рассчитаны на один блок. Параметр типа у схемы
+//! потребовал бы отдельного объекта на каждую разрядность, а одна схема здесь защищает
+//! секреты любой разрядности.
+use crate::keys::{pack_bytes, sensitive, unpack_bytes};
+use crate::scheme_impl::ChunkedPart;
+use crate::HadamardSSS;
+
+/// Секрет, который раскладывается на фиксированное число блоков u32.
+pub trait ShareableSecret: Sized {
+    /// Число блоков u32.
+    const WORDS: usize;
+
+    /// Блоки, в которых хранятся биты секрета, начиная с младших.
+    fn to_bits(&self) -> Vec<u32>;
+
+    /// Секрет по блокам; возвращается ошибка, если блоков не [ShareableSecret::WORDS]
+    /// или лишние биты последнего блока не равны нулю.
+    fn from_bits(words: &[u32]) -> Result<Self, &'static str>;
+}
+
+/// Реализация трейта для беззнаковых целых, разрядность которых кратна 32.
+macro_rules! shareable_int {
+    ($($secret:ty),*) => {$(
+        impl ShareableSecret for $secret {
+            const WORDS: usize = (<$secret>::BITS / u32::BITS) as usize;
+
+            fn to_bits(&self) -> Vec<u32> {
+                (0..Self::WORDS).map(|i| (*self >> (32 * i)) as u32).collect()
+            }
+
+            fn from_bits(words: &[u32]) -> Result<Self, &'static str> {
+                if words.len() != Self::WORDS {
+                    return Err("wrong number of blocks for the secret type");
+                }
+                // сдвиг в два приёма: для u32 сдвиг на 32 переполняет
+                Ok(words.iter().rev().fold(0, |acc, &word| (acc << 16 << 16) | <$secret>::from(word)))
+            }
+        }
+    )*};
+}
+
+shareable_int!(u32, u64, u128);
+
+/// Массив байтов раскладывается в блоки little-endian, последний блок дополняется нулями.
+impl<const N: usize> ShareableSecret for [u8; N] {
+    const WORDS: usize = N.div_ceil(4);
+
+    fn to_bits(&self) -> Vec<u32> {
+        self.chunks(4)
+            .map(|chunk| {
+                let mut word = [0u8; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(word)
+            })
+            .collect()
+    }
+
+    fn from_bits(words: &[u32]) -> Result<Self, &'static str> {
+        if words.len() != Self::WORDS {
+            return Err("wrong number of blocks for the secret type");
+        }
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        if bytes[N..].iter().any(|&byte| byte != 0) {
+            return Err("nonzero padding in the last block");
+        }
+        Ok(std::array::from_fn(|i| bytes[i]))
+    }
+}
+
+/// Разделение секретов произвольного типа.
+impl HadamardSSS {
+    /// Разделение секрета типа S; i-я доля содержит значения i-го участника по всем блокам.
+    pub fn share_secret<S: ShareableSecret>(&self, secret: &S) -> Result<Vec<ChunkedPart>, &'static str> {
+        self.share_words(&sensitive(secret.to_bits()))
+    }
+
+    /// Восстановление секрета типа S, разделённого с помощью [HadamardSSS::share_secret].
+    pub fn reconstruct_secret<S: ShareableSecret>(&self, parts: Vec<ChunkedPart>) -> Result<S, &'static str> {
+        S::from_bits(&sensitive(self.reconstruct_words(parts)?))
+    }
+
+    /// Разделение строки: байты UTF-8 упаковываются в блоки u32, первый блок хранит длину,
+    /// последний дополняется нулями.
+    pub fn share_str(&self, secret: &str) -> Result<Vec<ChunkedPart>, &'static str> {
+        self.share_words(&pack_bytes(secret.as_bytes())?)
+    }
+
+    /// Восстановление строки, разделённой с помощью [HadamardSSS::share_str].
+    pub fn reconstruct_str(&self, parts: Vec<ChunkedPart>) -> Result<String, &'static str> {
+        let bytes = unpack_bytes(&self.reconstruct_words(parts)?)?;
+        String::from_utf8(bytes).map_err(|_| "secret is not valid UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_bits_roundtrip() {
+        assert_eq!(0x0123_4567_89AB_CDEFu64.to_bits(), [0x89AB_CDEF, 0x0123_4567]);
+        assert_eq!(u128::from_bits(&u128::MAX.to_bits()), Ok(u128::MAX));
+        assert_eq!(u32::from_bits(&[1, 2]), Err("wrong number of blocks for the secret type"));
+        assert_eq!(<[u8; 5]>::WORDS, 2);
+        assert_eq!([1u8, 2, 3, 4, 5].to_bits(), [0x0403_0201, 5]);
+        assert_eq!(<[u8; 5]>::from_bits(&[0x0403_0201, 5]), Ok([1, 2, 3, 4, 5]));
+        assert_eq!(<[u8; 5]>::from_bits(&[0, 0x100]), Err("nonzero padding in the last block"));
+    }
+
+    #[test]
+    fn test_one_scheme_many_widths() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share_secret(&0x0123_4567_89AB_CDEFu64).unwrap();
+        assert_eq!(hsss.reconstruct_secret::<u64>(parts[2..7].to_vec()), Ok(0x0123_4567_89AB_CDEF));
+        assert!(hsss.reconstruct_secret::<u128>(parts[2..7].to_vec()).is_err());
+
+        let secret = u128::MAX - 314159265;
+        let parts = hsss.share_secret(&secret).unwrap();
+        assert_eq!(hsss.reconstruct_secret::<u128>(parts[0..5].to_vec()), Ok(secret));
+        assert!(hsss.reconstruct_secret::<u128>(parts[0..4].to_vec()).is_err());
+
+        let parts = hsss.share_secret(b"hadamard").unwrap();
+        assert_eq!(&hsss.reconstruct_secret::<[u8; 8]>(parts[1..6].to_vec()).unwrap(), b"hadamard");
+        assert_eq!(hsss.reconstruct_secret::<u32>(hsss.share_secret(&7u32).unwrap()), Ok(7));
+    }
+
+    #[test]
+    fn test_strings() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        for secret in ["", "a", "correct horse battery staple", "пароль"] {
+            let parts = hsss.share_str(secret).unwrap();
+            assert_eq!(parts.len(), 7);
+            assert_eq!(hsss.reconstruct_str(parts[2..7].to_vec()).unwrap(), secret);
+        }
+        let parts = hsss.share_words(&[2, 0xFFFF]).unwrap();
+        assert_eq!(hsss.reconstruct_str(parts).err(), Some("secret is not valid UTF-8"));
+        let parts = hsss.share_words(&[9, 0]).unwrap();
+        assert!(hsss.reconstruct_str(parts).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Перестановка битов секрета по столбцам матрицы для каждого сеанса раздачи.
+//!
+//! При обычной раздаче участник с данной строкой всегда знает одни и те же позиции битов
+//! секрета. Противник, собравший доли одной строки из многих сеансов, может сопоставлять
+//! утёкшие позиции между ними. Здесь бит b секрета переносится в позицию π(b) слова,
+//! которое разделяется обычной схемой; перестановка π выводится HKDF-SHA256 из
+//! идентификатора сеанса. Поэтому разным сеансам соответствуют разные наборы известных
+//! строке позиций. Идентификатор сеанса нужен для восстановления и хранится вместе с долями.
+//!
+//! Повторная раздача одного и того же секрета в разных сеансах открывает строке разные
+//! биты этого секрета, поэтому один секрет следует раздавать в одном сеансе.
+use crate::labels::SessionId;
+use crate::scheme_impl::Part;
+use crate::{HadamardSSS, SharingScheme};
+use hkdf::Hkdf;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::Sha256;
+
+/// Метка HKDF для перестановки сеанса.
+const LABEL: &[u8] = b"hadamard_sss session permutation";
+
+/// Перестановка сеанса: бит b секрета переносится в позицию permutation[b].
+pub fn session_permutation(session: &SessionId) -> [u32; 32] {
+    let mut seed = [0u8; 32];
+    Hkdf::<Sha256>::new(None, session)
+        .expand(LABEL, &mut seed)
+        .expect("32 bytes is a valid HKDF output length");
+    let mut permutation: [u32; 32] = std::array::from_fn(|b| b as u32);
+    permutation.shuffle(&mut ChaCha20Rng::from_seed(seed));
+    permutation
+}
+
+/// Перенос битов секрета в позиции перестановки.
+fn scatter(secret: u32, permutation: &[u32; 32]) -> u32 {
+    (0..32).fold(0, |word, b| word | (secret >> b & 1) << permutation[b])
+}
+
+/// Обратный перенос.
+fn gather(word: u32, permutation: &[u32; 32]) -> u32 {
+    (0..32).fold(0, |secret, b| secret | (word >> permutation[b] & 1) << b)
+}
+
+/// Раздача с перестановкой сеанса.
+impl HadamardSSS {
+    /// Разделение секрета в данном сеансе.
+    pub fn share_session(&self, secret: u32, session: &SessionId) -> Result<Vec<Part>, &'static str> {
+        self.share(scatter(secret, &session_permutation(session)))
+    }
+
+    /// Восстановление секрета, разделённого [HadamardSSS::share_session]. Доли проверяются
+    /// так же, как в [SharingScheme::reconstruct]; проверка [SharingScheme::validate]
+    /// применима к долям сеанса без изменений.
+    pub fn reconstruct_session(&self, parts: Vec<Part>, session: &SessionId) -> Result<u32, &'static str> {
+        Ok(gather(self.reconstruct(parts)?, &session_permutation(session)))
+    }
+
+    /// Маска битов секрета, известных доле с данным номером в данном сеансе.
+    pub fn session_mask(&self, number: usize, session: &SessionId) -> u32 {
+        gather(self.hss.mask(number), &session_permutation(session))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_permutation() {
+        let permutation = session_permutation(&[1; 16]);
+        let mut sorted = permutation;
+        sorted.sort_unstable();
+        assert_eq!(sorted, std::array::from_fn(|b| b as u32));
+        assert_eq!(session_permutation(&[1; 16]), permutation);
+        assert_ne!(session_permutation(&[2; 16]), permutation);
+        for secret in [0, 1, 0x8000_0000, 314159265, u32::MAX] {
+            assert_eq!(gather(scatter(secret, &permutation), &permutation), secret);
+        }
+    }
+
+    #[test]
+    fn test_session_sharing() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let (first, second) = ([1; 16], [2; 16]);
+        let parts = hsss.share_session(314159265, &first).unwrap();
+        assert_eq!(hsss.reconstruct_session(parts[2..7].to_vec(), &first).unwrap(), 314159265);
+        assert_ne!(hsss.reconstruct_session(parts[2..7].to_vec(), &second), Ok(314159265));
+        assert!(hsss.validate(parts.clone()).is_clean());
+        assert!(hsss.reconstruct_session(parts[0..4].to_vec(), &first).is_err());
+
+        // строка знает в сеансах разные биты, но столько же, сколько без перестановки
+        assert_ne!(hsss.session_mask(0, &first), hsss.session_mask(0, &second));
+        for part in &parts {
+            let mask = hsss.session_mask(part.number(), &first);
+            assert_eq!(mask.count_ones(), hsss.hss.mask(part.number()).count_ones());
+            assert_eq!(part.data() & hsss.hss.mask(part.number()), scatter(314159265 & mask, &session_permutation(&first)));
+        }
+    }
+}
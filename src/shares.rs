@@ -0,0 +1,159 @@
+//! Набор долей как отдельный тип.
+//!
+//! [Shares] владеет долями и при включённой функции `zeroize_memory` затирает их при удалении,
+//! так что пользовательскому коду не нужно следить за каждым `Vec<Part>`. Копии, полученные
+//! через [Shares::to_vec], затираются только если сам пользователь позаботится об этом.
+use crate::analysis::Combinations;
+use crate::scheme_impl::Part;
+use crate::{HadamardSSS, SharingScheme};
+use rand::seq::index::sample;
+use rand::seq::SliceRandom;
+use rand::Rng;
+#[cfg(feature = "zeroize_memory")]
+use zeroize::Zeroize;
+
+/// Набор долей.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "zeroize_memory", derive(Zeroize))]
+#[cfg_attr(feature = "zeroize_memory", zeroize(drop))]
+pub struct Shares {
+    /// Доли набора.
+    parts: Vec<Part>,
+}
+
+/// Реализация методов набора долей.
+impl Shares {
+    /// Возвращение долей набора.
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// Число долей.
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Пуст ли набор.
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// Копия долей для передачи в [SharingScheme::reconstruct] и другие методы схемы.
+    pub fn to_vec(&self) -> Vec<Part> {
+        self.parts.clone()
+    }
+
+    /// Доля с данным номером.
+    pub fn by_number(&self, number: usize) -> Option<&Part> {
+        self.parts.iter().find(|part| part.number() == number)
+    }
+
+    /// Набор из долей с данными номерами в указанном порядке. Возвращается ошибка,
+    /// если какой-то доли нет в наборе.
+    pub fn subset(&self, numbers: &[usize]) -> Result<Shares, &'static str> {
+        let parts = numbers
+            .iter()
+            .map(|&number| self.by_number(number).copied().ok_or("no share with this number in the set"))
+            .collect::<Result<_, _>>()?;
+        Ok(Shares { parts })
+    }
+
+    /// Случайный набор из k долей; возвращается ошибка, если долей меньше k.
+    pub fn sample_quorum(&self, k: usize, rng: &mut impl Rng) -> Result<Shares, &'static str> {
+        if k > self.parts.len() {
+            return Err("not enough shares for the quorum");
+        }
+        Ok(sample(rng, self.parts.len(), k).into_iter().map(|i| self.parts[i]).collect())
+    }
+
+    /// Все наборы из k долей в лексикографическом порядке позиций. Наборов C(n, k),
+    /// поэтому полный перебор годится только для небольших n.
+    pub fn subsets(&self, k: usize) -> impl Iterator<Item = Shares> + '_ {
+        Combinations::new(self.parts.len(), k).map(move |positions| positions.into_iter().map(|i| self.parts[i]).collect())
+    }
+
+    /// Случайное перемешивание долей.
+    pub fn shuffle(&mut self, rng: &mut impl Rng) {
+        self.parts.shuffle(rng);
+    }
+}
+
+impl From<Vec<Part>> for Shares {
+    fn from(parts: Vec<Part>) -> Self {
+        Shares { parts }
+    }
+}
+
+impl FromIterator<Part> for Shares {
+    fn from_iter<I: IntoIterator<Item = Part>>(iter: I) -> Self {
+        Shares { parts: iter.into_iter().collect() }
+    }
+}
+
+/// Разделение в набор долей.
+impl HadamardSSS {
+    /// Разделение секрета; доли возвращаются набором [Shares].
+    pub fn share_set(&self, secret: u32) -> Result<Shares, &'static str> {
+        self.share(secret).map(Shares::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_shares_helpers() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut shares = hsss.share_set(314159265).unwrap();
+        assert_eq!(shares.len(), 7);
+        assert_eq!(shares.by_number(3).unwrap().number(), 3);
+        assert!(shares.by_number(7).is_none());
+
+        let subset = shares.subset(&[6, 0, 2, 4, 5]).unwrap();
+        let numbers: Vec<usize> = subset.parts().iter().map(Part::number).collect();
+        assert_eq!(numbers, [6, 0, 2, 4, 5]);
+        assert_eq!(hsss.reconstruct(subset.to_vec()).unwrap(), 314159265);
+        assert!(shares.subset(&[0, 7]).is_err());
+
+        shares.shuffle(&mut ChaCha20Rng::seed_from_u64(988));
+        let mut numbers: Vec<usize> = shares.parts().iter().map(Part::number).collect();
+        assert_ne!(numbers, [0, 1, 2, 3, 4, 5, 6]);
+        numbers.sort_unstable();
+        assert_eq!(numbers, [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(hsss.reconstruct(shares.to_vec()).unwrap(), 314159265);
+    }
+
+    #[test]
+    fn test_quorums() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let shares = hsss.share_set(314159265).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(989);
+        for _ in 0..20 {
+            let quorum = shares.sample_quorum(5, &mut rng).unwrap();
+            let mut numbers: Vec<usize> = quorum.parts().iter().map(Part::number).collect();
+            numbers.sort_unstable();
+            numbers.dedup();
+            assert_eq!(numbers.len(), 5);
+            assert_eq!(hsss.reconstruct(quorum.to_vec()).unwrap(), 314159265);
+        }
+        assert!(shares.sample_quorum(8, &mut rng).is_err());
+
+        // порог схемы проверяется полным перебором
+        assert_eq!(shares.subsets(5).count(), 21);
+        assert!(shares.subsets(5).all(|set| hsss.reconstruct(set.to_vec()) == Ok(314159265)));
+        assert!(shares.subsets(4).all(|set| hsss.reconstruct(set.to_vec()).is_err()));
+        assert_eq!(shares.subsets(8).count(), 0);
+    }
+
+    #[cfg(feature = "zeroize_memory")]
+    #[test]
+    fn test_zeroize() {
+        let mut shares: Shares = (0..3).map(|i| Part::from(i, 0xFFFF)).collect();
+        shares.zeroize();
+        assert!(shares.is_empty());
+    }
+}
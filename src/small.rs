@@ -0,0 +1,117 @@
+//! Схемы для коротких секретов (u8, u16) на устройствах класса смарт-карт.
+//!
+//! [Small8] работает с матрицами порядка 8 (7 долей, порог 5), [Small16] -- с матрицами
+//! порядка 16 (15 долей, порог 9). Маски известных битов вычисляются один раз при создании,
+//! доли хранятся в массивах фиксированной длины, поэтому разделение и восстановление не
+//! выделяют память и не обращаются к ndarray: циклы фиксированной длины компилятор разворачивает.
+//! Доли совпадают с младшими битами долей [HadamardSSS] с той же матрицей и раскладкой.
+use crate::HadamardSSS;
+use rand::RngCore;
+
+macro_rules! small_scheme {
+    ($(#[$doc:meta])* $name:ident, $secret:ty, $order:literal, $threshold:literal) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct $name {
+            /// Маски известных битов по номерам долей.
+            masks: [$secret; $order - 1],
+        }
+
+        impl $name {
+            /// Число долей.
+            pub const PARTS: usize = $order - 1;
+            /// Пороговое значение.
+            pub const THRESHOLD: usize = $threshold;
+
+            /// Создание по схеме общего вида; её матрица должна иметь порядок
+            #[doc = concat!(stringify!($order), ".")]
+            pub fn from_scheme(scheme: &HadamardSSS) -> Result<Self, &'static str> {
+                if scheme.hss.mtx_len() != Self::PARTS {
+                    return Err(concat!("small scheme requires a matrix of order ", stringify!($order)));
+                }
+                Ok($name { masks: std::array::from_fn(|i| scheme.hss.mask(i) as $secret) })
+            }
+
+            /// Маска битов секрета, известных доле с данным номером.
+            pub fn mask(&self, number: usize) -> Option<$secret> {
+                self.masks.get(number).copied()
+            }
+
+            /// Разделение секрета с заданными битами-заполнителями; i-я доля -- элемент i массива.
+            pub fn share_padded(&self, secret: $secret, padding: [$secret; $order - 1]) -> [$secret; $order - 1] {
+                std::array::from_fn(|i| (secret & self.masks[i]) | (padding[i] & !self.masks[i]))
+            }
+
+            /// Разделение секрета со случайными битами-заполнителями.
+            pub fn share(&self, secret: $secret) -> [$secret; $order - 1] {
+                let mut bytes = [0u8; ($order - 1) * std::mem::size_of::<$secret>()];
+                crate::entropy::rng().fill_bytes(&mut bytes);
+                let mut words = bytes.chunks_exact(std::mem::size_of::<$secret>());
+                let padding = std::array::from_fn(|_| {
+                    <$secret>::from_le_bytes(words.next().expect("padding is sized to the parts").try_into().expect("chunk has the secret size"))
+                });
+                self.share_padded(secret, padding)
+            }
+
+            /// Восстановление секрета по парам (номер доли, значение). Как и в [HadamardSSS],
+            /// проверяется только число долей и их номера.
+            pub fn reconstruct(&self, parts: &[(usize, $secret)]) -> Result<$secret, &'static str> {
+                if parts.len() < Self::THRESHOLD {
+                    return Err("less than threshold parties");
+                }
+                parts.iter().try_fold(0, |res, &(number, data)| {
+                    let mask = self.mask(number).ok_or("scheme mismatch: part number exceeds the order of the scheme")?;
+                    Ok(res | (data & mask))
+                })
+            }
+        }
+    };
+}
+
+small_scheme!(
+    /// Схема для секрета u8 по матрице порядка 8.
+    Small8, u8, 8, 5
+);
+small_scheme!(
+    /// Схема для секрета u16 по матрице порядка 16.
+    Small16, u16, 16, 9
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equivalence::classes;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_small8_matches_general_scheme() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let small = Small8::from_scheme(&hsss).unwrap();
+        for secret in 0..=u8::MAX {
+            let parts = small.share(secret);
+            let presented: Vec<(usize, u8)> = parts.iter().copied().enumerate().skip(2).collect();
+            assert_eq!(small.reconstruct(&presented).unwrap(), secret);
+            let general = hsss.hss.share_padded(u32::from(secret), |i| u32::from(parts[i]));
+            for (i, part) in general.iter().enumerate() {
+                assert_eq!(part.data() as u8, parts[i]);
+            }
+        }
+        assert_eq!(small.reconstruct(&[(0, 1), (1, 1), (2, 1), (3, 1)]), Err("less than threshold parties"));
+        assert!(small.reconstruct(&[(0, 1), (1, 1), (2, 1), (3, 1), (7, 1)]).is_err());
+        assert!(Small16::from_scheme(&hsss).is_err());
+    }
+
+    #[test]
+    fn test_small16_roundtrip() {
+        let hsss = HadamardSSS::from_matrix(&classes(16).unwrap().next().unwrap()).unwrap();
+        let small = Small16::from_scheme(&hsss).unwrap();
+        for secret in [0, 1, 0xBEEF, u16::MAX] {
+            let parts = small.share(secret);
+            let presented: Vec<(usize, u16)> = parts.iter().copied().enumerate().take(9).collect();
+            assert_eq!(small.reconstruct(&presented).unwrap(), secret);
+            let general = hsss.hss.share_padded(u32::from(secret), |i| u32::from(parts[i]));
+            assert!(general.iter().all(|part| part.data() as u16 == parts[part.number()]));
+        }
+        assert!(Small8::from_scheme(&hsss).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Побайтовая спецификация преобразования разделения и эталонные векторы для сторонних реализаций.
+//!
+//! Реализация на C, Go, Python и т. п. совместима с библиотекой, если для каждого вектора
+//! из [spec_vectors] и [chunk_vectors] она получает те же значения.
+//!
+//! # Матрица инцидентности
+//! Строки и столбцы матрицы Адамара H порядка 4n умножаются на ±1 так, чтобы первая строка
+//! и первый столбец состояли из одних 1 (такой результат единственен).
+//! Матрица инцидентности I порядка v = 4n-1 равна `I[i][j] = (H[i+1][j+1] + 1) / 2`.
+//! Строка i матрицы I соответствует доле с номером i.
+//!
+//! # Биты и столбцы
+//! Секрет -- беззнаковое 32-битное число; бит b -- это `(secret >> b) & 1`, бит 0 младший.
+//! Раскладка по умолчанию закрепляет бит b за столбцом `columns[b] = b mod v`, если v <= 32,
+//! и за столбцом `columns[b] = b`, если v > 32. Маска доли i содержит бит b,
+//! если `I[i][columns[b]] == 1`.
+//!
+//! # Значение доли
+//! `data_i = (secret & mask_i) | (padding_i & !mask_i)`, где padding_i -- слово-заполнитель.
+//!
+//! # Заполнитель в детерминированном режиме
+//! Для [crate::HadamardSSS::share_seeded] заполнители берутся из потока ChaCha20 (20 раундов,
+//! вариант Бернстайна): ключ -- 32 байта зерна, 64-битный nonce равен нулю, 64-битный счётчик
+//! блоков начинается с нуля. padding_i -- байты 4i..4i+4 потока, прочитанные как little-endian u32.
+//!
+//! # Байтовые секреты
+//! [crate::SchemeExt::share_bytes] упаковывает строку в слова: слово 0 равно длине строки в байтах,
+//! далее идут четвёрки байтов, прочитанные как little-endian u32; последняя четвёрка дополняется нулями.
+//! Каждое слово разделяется независимо.
+use crate::equivalence::classes;
+use crate::hadamard_matrix::HadamardMatrix;
+use crate::keys::pack_bytes;
+use crate::layout::Layout;
+use crate::HadamardSSS;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Эталонный вектор разделения одного слова в детерминированном режиме.
+#[derive(Clone)]
+pub struct SpecVector {
+    /// Матрица Адамара, по которой построена схема.
+    pub matrix: HadamardMatrix,
+    /// Разделяемый секрет.
+    pub secret: u32,
+    /// Зерно ChaCha20.
+    pub seed: [u8; 32],
+    /// Столбец матрицы инцидентности, за которым закреплён каждый бит секрета.
+    pub columns: Vec<usize>,
+    /// Маски известных битов по долям.
+    pub masks: Vec<u32>,
+    /// Слова-заполнители по долям.
+    pub padding: Vec<u32>,
+    /// Доли в виде пар (номер, значение).
+    pub shares: Vec<(usize, u32)>,
+}
+
+/// Эталонный вектор упаковки байтового секрета в слова.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkVector {
+    /// Байтовый секрет.
+    pub bytes: Vec<u8>,
+    /// Слова, которые разделяются независимо.
+    pub words: Vec<u32>,
+}
+
+/// Эталонные векторы разделения: матрицы Сильвестра порядков 4, 8 и 64 и матрица Пэли порядка 12
+/// ([crate::equivalence::classes]), по четыре секрета на матрицу.
+pub fn spec_vectors() -> Vec<SpecVector> {
+    let paley = classes(12).expect("order 12 is supported").next().expect("order 12 has a class");
+    let sylvester = |k| HadamardMatrix::sylvester(k).expect("power is within the limit");
+    let matrices = [sylvester(2), sylvester(3), paley, sylvester(6)];
+    let inputs: [(u32, [u8; 32]); 4] = [(0, [1; 32]), (314159265, [2; 32]), (u32::MAX, [3; 32]), (0xDEADBEEF, [4; 32])];
+    let mut res = Vec::new();
+    for matrix in matrices {
+        let scheme = HadamardSSS::from_matrix(&matrix).expect("orders of the vectors are multiples of 4");
+        let v = matrix.order() - 1;
+        let columns = Layout::default().columns(v, u32::BITS as usize).expect("default layout fits any order");
+        let masks: Vec<u32> = (0..v).map(|i| scheme.hss.mask(i)).collect();
+        for (secret, seed) in inputs {
+            let mut rng = ChaCha20Rng::from_seed(seed);
+            let padding = (0..v).map(|_| rng.next_u32()).collect();
+            let shares = scheme
+                .share_seeded(secret, seed)
+                .expect("sharing a u32 secret does not fail")
+                .iter()
+                .map(|part| (part.number(), part.data()))
+                .collect();
+            res.push(SpecVector {
+                matrix: matrix.clone(),
+                secret,
+                seed,
+                columns: columns.clone(),
+                masks: masks.clone(),
+                padding,
+                shares,
+            });
+        }
+    }
+    res
+}
+
+/// Эталонные векторы упаковки байтовых секретов.
+pub fn chunk_vectors() -> Vec<ChunkVector> {
+    [&b""[..], b"a", b"abcd", b"abcde", b"correct horse battery staple"]
+        .iter()
+        .map(|&bytes| ChunkVector {
+            bytes: bytes.to_vec(),
+            words: pack_bytes(bytes).expect("short secret"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::FIXTURES;
+
+    #[test]
+    fn test_vectors_follow_spec() {
+        let vectors = spec_vectors();
+        assert_eq!(vectors.len(), 16);
+        for vector in &vectors {
+            let incidence = vector.matrix.normalized().get_incidence();
+            let v = incidence.shape()[0];
+            assert_eq!(vector.shares.len(), v);
+            for (b, &column) in vector.columns.iter().enumerate() {
+                assert_eq!(column, if v <= 32 { b % v } else { b });
+            }
+            for (i, &(number, data)) in vector.shares.iter().enumerate() {
+                let mask = (0..32).filter(|&b| incidence[[i, vector.columns[b]]] == 1).fold(0, |m, b| m | 1 << b);
+                assert_eq!(number, i);
+                assert_eq!(vector.masks[i], mask);
+                assert_eq!(data, (vector.secret & mask) | (vector.padding[i] & !mask));
+            }
+        }
+        // векторы порядка 8 совпадают с зафиксированными наборами
+        for (vector, fixture) in vectors[4..8].iter().zip(&FIXTURES) {
+            assert_eq!(vector.shares, fixture.shares);
+        }
+    }
+
+    #[test]
+    fn test_vectors_frozen() {
+        use sha2::{Digest, Sha256};
+        // значения долей порядков 4 и 12; векторы порядка 8 закреплены в FIXTURES
+        const ORDER_4: [[u32; 3]; 4] = [[540416768, 2219975730, 405022209],
+                                        [976069092, 640340407, 3365672170],
+                                        [3126556090, 1567601259, 4292017148],
+                                        [2584442858, 4008547177, 3471679095]];
+        const ORDER_12: [[u32; 11]; 4] = [
+            [1120770, 69346354, 403071013, 537678016, 3423896372, 2451193920, 3026618497, 673454208, 2148665350, 2694841990, 40397833],
+            [2552799410, 102400247, 146575560, 1613983859, 1941016853, 1388049801, 2258614385, 1520281762, 1140425447, 857601701, 1389974712],
+            [4219434492, 4133481387, 3757752282, 4286000887, 2548457039, 637451927, 4222219054, 4294110814, 2120601272, 2144070525, 4170668795],
+            [1483503356, 3482127081, 3471826531, 2897178229, 2142781131, 1319632615, 4207754926, 3194006350, 250582703, 4278818919, 3668835046],
+        ];
+        // SHA-256 всех полей всех векторов, включая порядок 64
+        const DIGEST: [u8; 32] = [0x39, 0x55, 0x8d, 0x6f, 0x94, 0xe7, 0x78, 0x77, 0x64, 0x3f, 0xb0, 0x42, 0x61, 0x99, 0xc8, 0xfa,
+                                  0x31, 0x91, 0x7c, 0xe5, 0x60, 0x7e, 0xe0, 0x38, 0x63, 0xe7, 0xad, 0x4d, 0x8d, 0x6d, 0x78, 0xf9];
+
+        let vectors = spec_vectors();
+        let data = |vector: &SpecVector| vector.shares.iter().map(|&(_, data)| data).collect::<Vec<u32>>();
+        for (vector, frozen) in vectors[0..4].iter().zip(&ORDER_4) {
+            assert_eq!(data(vector), frozen);
+        }
+        for (vector, frozen) in vectors[8..12].iter().zip(&ORDER_12) {
+            assert_eq!(data(vector), frozen);
+        }
+        let mut hasher = Sha256::new();
+        for vector in &vectors {
+            hasher.update((vector.matrix.order() as u64).to_le_bytes());
+            vector.matrix.view().iter().for_each(|&x| hasher.update([x as u8]));
+            hasher.update(vector.secret.to_le_bytes());
+            hasher.update(vector.seed);
+            vector.columns.iter().for_each(|&column| hasher.update((column as u64).to_le_bytes()));
+            vector.masks.iter().for_each(|mask| hasher.update(mask.to_le_bytes()));
+            vector.padding.iter().for_each(|word| hasher.update(word.to_le_bytes()));
+            for &(number, data) in &vector.shares {
+                hasher.update((number as u64).to_le_bytes());
+                hasher.update(data.to_le_bytes());
+            }
+        }
+        assert_eq!(hasher.finalize().as_slice(), DIGEST);
+    }
+
+    #[test]
+    fn test_chacha_keystream_order() {
+        // первое слово потока ChaCha20 с нулевыми ключом и nonce (байты 76 b8 e0 ad)
+        assert_eq!(ChaCha20Rng::from_seed([0; 32]).next_u32(), 0xade0b876);
+    }
+
+    #[test]
+    fn test_chunk_vectors() {
+        let vectors = chunk_vectors();
+        assert_eq!(vectors[0].words, vec![0]);
+        assert_eq!(vectors[1].words, vec![1, 0x61]);
+        assert_eq!(vectors[2].words, vec![4, 0x64636261]);
+        assert_eq!(vectors[3].words, vec![5, 0x64636261, 0x65]);
+    }
+}
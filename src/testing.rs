@@ -0,0 +1,176 @@
+//! Внесение искажений в доли для интеграционных тестов.
+//!
+//! Доступно с функцией `test-utils` и позволяет проверить, как приложение обрабатывает
+//! повреждённые, перепутанные и поддельные доли. [MockScheme] заменяет настоящую схему
+//! в тестах кода, обобщённого по [SharingScheme].
+use crate::scheme_impl::Part;
+use crate::validation::{ShareVerdict, ValidationOutcome};
+use crate::SharingScheme;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Доля с инвертированным битом bit (0..32) значения; отпечаток сохраняется.
+pub fn corrupt_bit(part: &Part, bit: u32) -> Part {
+    part.blinded(1 << (bit % u32::BITS))
+}
+
+/// Обмен номерами двух долей при сохранении значений и отпечатков, как при перепутанных метках.
+pub fn swap_numbers(a: &Part, b: &Part) -> (Part, Part) {
+    (b.blinded(a.data() ^ b.data()), a.blinded(a.data() ^ b.data()))
+}
+
+/// Поддельная доля: случайное значение под случайным номером из 0..parts,
+/// где parts -- число долей схемы. Отпечатка схемы у подделки нет.
+pub fn forge_share<R: Rng + ?Sized>(rng: &mut R, parts: usize) -> Part {
+    Part::from(rng.gen_range(0..parts.max(1)), rng.gen())
+}
+
+/// Детерминированная схема для тестов: каждая доля равна секрету, восстановление требует
+/// threshold долей с одинаковыми значениями. Ошибки можно заранее поставить в очередь.
+pub struct MockScheme {
+    /// Число долей.
+    parts: usize,
+    /// Пороговое значение.
+    threshold: usize,
+    /// Ошибки, которые вернут следующие вызовы share.
+    share_failures: Mutex<VecDeque<&'static str>>,
+    /// Ошибки, которые вернут следующие вызовы reconstruct.
+    reconstruct_failures: Mutex<VecDeque<&'static str>>,
+    /// Номера долей, которые validate всегда признаёт подозрительными.
+    suspicious: Vec<usize>,
+}
+
+/// Реализация методов тестовой схемы.
+impl MockScheme {
+    /// Схема с данными числом долей и порогом.
+    pub fn new(parts: usize, threshold: usize) -> Self {
+        MockScheme {
+            parts,
+            threshold,
+            share_failures: Mutex::new(VecDeque::new()),
+            reconstruct_failures: Mutex::new(VecDeque::new()),
+            suspicious: Vec::new(),
+        }
+    }
+
+    /// Доли с данными номерами validate будет признавать подозрительными, если они предъявлены.
+    pub fn with_suspicious(mut self, numbers: Vec<usize>) -> Self {
+        self.suspicious = numbers;
+        self
+    }
+
+    /// Следующий вызов share вернёт данную ошибку.
+    pub fn fail_next_share(&self, error: &'static str) {
+        self.share_failures.lock().expect("mock is not poisoned").push_back(error);
+    }
+
+    /// Следующий вызов reconstruct вернёт данную ошибку.
+    pub fn fail_next_reconstruct(&self, error: &'static str) {
+        self.reconstruct_failures.lock().expect("mock is not poisoned").push_back(error);
+    }
+}
+
+/// Реализация трейта SharingScheme в тестовой схеме.
+impl SharingScheme for MockScheme {
+    type Error = &'static str;
+    type SecretType = u32;
+    type PartType = Part;
+
+    fn share(&self, secret: u32) -> Result<Vec<Part>, &'static str> {
+        if let Some(error) = self.share_failures.lock().expect("mock is not poisoned").pop_front() {
+            return Err(error);
+        }
+        Ok((0..self.parts).map(|i| Part::from(i, secret)).collect())
+    }
+
+    fn reconstruct(&self, parts: Vec<Part>) -> Result<u32, &'static str> {
+        if let Some(error) = self.reconstruct_failures.lock().expect("mock is not poisoned").pop_front() {
+            return Err(error);
+        }
+        if parts.len() < self.threshold {
+            return Err("less than threshold parties");
+        }
+        let value = parts[0].data();
+        if parts.iter().any(|part| part.data() != value || part.number() >= self.parts) {
+            return Err("inconsistent mock shares");
+        }
+        Ok(value)
+    }
+
+    /// Подозрительны заданные заранее доли, а также доли вне схемы и доли, значение
+    /// которых расходится с самым частым.
+    fn validate(&self, parts: Vec<Part>) -> ValidationOutcome {
+        let common = parts
+            .iter()
+            .map(Part::data)
+            .max_by_key(|&value| (parts.iter().filter(|part| part.data() == value).count(), std::cmp::Reverse(value)));
+        let mut numbers: Vec<usize> = parts
+            .iter()
+            .filter(|part| {
+                self.suspicious.contains(&part.number()) || part.number() >= self.parts || Some(part.data()) != common
+            })
+            .map(Part::number)
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        if !numbers.is_empty() {
+            let verdicts = numbers
+                .into_iter()
+                .map(|number| if number >= self.parts { ShareVerdict::foreign(number) } else { ShareVerdict::conflicting(number, Vec::new()) })
+                .collect();
+            ValidationOutcome::Suspicious(verdicts)
+        } else if parts.len() < 2 {
+            ValidationOutcome::Inconclusive
+        } else {
+            ValidationOutcome::Clean
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::scheme_traits::SchemeExt;
+    use crate::{HadamardSSS, SharingScheme};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_corruptions_detected() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let known = hsss.hss.mask(2).trailing_zeros();
+        let mut corrupted = parts.clone();
+        corrupted[2] = corrupt_bit(&parts[2], known);
+        assert_eq!(hsss.validate(corrupted).suspicious(), [2]);
+
+        let mut swapped = parts.clone();
+        (swapped[1], swapped[4]) = swap_numbers(&parts[1], &parts[4]);
+        assert_eq!((swapped[1].number(), swapped[4].data()), (4, parts[4].data()));
+        assert!(!hsss.validate(swapped).is_clean());
+
+        let mut rng = ChaCha20Rng::seed_from_u64(966);
+        let forged = forge_share(&mut rng, 7);
+        assert!(forged.number() < 7);
+    }
+
+    #[test]
+    fn test_mock_scheme() {
+        let mock = MockScheme::new(5, 3).with_suspicious(vec![4]);
+        let parts = mock.share(42).unwrap();
+        assert_eq!(mock.reconstruct(parts[0..3].to_vec()), Ok(42));
+        assert_eq!(mock.reconstruct(parts[0..2].to_vec()), Err("less than threshold parties"));
+        assert_eq!(mock.validate(parts[0..3].to_vec()), ValidationOutcome::Clean);
+        assert_eq!(mock.validate(parts[3..5].to_vec()).suspicious(), [4]);
+        assert_eq!(mock.validate(vec![parts[0], corrupt_bit(&parts[1], 0), parts[2]]).suspicious(), [1]);
+
+        mock.fail_next_share("device unavailable");
+        assert_eq!(mock.share(1).err(), Some("device unavailable"));
+        assert!(mock.share(1).is_ok());
+        mock.fail_next_reconstruct("timeout");
+        assert_eq!(mock.reconstruct(parts.clone()), Err("timeout"));
+        assert_eq!(mock.reconstruct_bytes(mock.share_bytes(b"mock").unwrap()).unwrap(), b"mock");
+    }
+}
@@ -0,0 +1,551 @@
+//! Пакетная и потоковая проверка наборов долей.
+//!
+//! Маски известных битов всех строк вычисляются один раз на пакет, после чего каждый набор
+//! проверяется тем же голосованием, что и [SharingScheme::validate], поэтому результаты совпадают.
+//! [Validator] принимает доли по одной и сообщает о противоречии сразу при поступлении доли.
+//! [ValidationPolicy] позволяет сдвинуть баланс между ложными срабатываниями и пропусками.
+use crate::scheme_impl::{Part, HSS};
+use crate::HadamardSSS;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater, ConstantTimeLess};
+#[cfg(doc)]
+use crate::SharingScheme;
+
+/// Голоса по одному спорному биту секрета.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitVote {
+    /// Номер бита секрета.
+    pub bit: u32,
+    /// Число долей, давших то же значение, что и проверяемая доля (включая её саму).
+    pub agree: usize,
+    /// Число долей, давших противоположное значение.
+    pub disagree: usize,
+}
+
+/// Заключение о подозрительной доле.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareVerdict {
+    /// Номер доли.
+    number: usize,
+    /// Доля получена в схеме другого порядка или по другой матрице.
+    foreign: bool,
+    /// Голоса по битам, на которых доля оказалась в меньшинстве.
+    votes: Vec<BitVote>,
+}
+
+/// Реализация методов заключения о доле.
+impl ShareVerdict {
+    /// Заключение о доле, оказавшейся в меньшинстве по данным битам.
+    pub fn conflicting(number: usize, votes: Vec<BitVote>) -> Self {
+        ShareVerdict { number, foreign: false, votes }
+    }
+
+    /// Заключение о доле с номером вне схемы или с чужим отпечатком.
+    pub fn foreign(number: usize) -> Self {
+        ShareVerdict { number, foreign: true, votes: Vec::new() }
+    }
+
+    /// Возвращение значения поля number.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Возвращение значения поля foreign.
+    pub fn is_foreign(&self) -> bool {
+        self.foreign
+    }
+
+    /// Возвращение значения поля votes.
+    pub fn votes(&self) -> &[BitVote] {
+        &self.votes
+    }
+
+    /// Номера спорных битов.
+    pub fn bits(&self) -> Vec<u32> {
+        self.votes.iter().map(|vote| vote.bit).collect()
+    }
+}
+
+/// Итог проверки набора долей методом [SharingScheme::validate].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationOutcome {
+    /// Доли согласованы.
+    Clean,
+    /// Найдены подозрительные доли; заключения упорядочены по номерам долей.
+    Suspicious(Vec<ShareVerdict>),
+    /// Сверить доли нельзя: ни один бит не известен сразу двум предъявленным долям.
+    Inconclusive,
+}
+
+/// Реализация методов итога проверки.
+impl ValidationOutcome {
+    /// Доли согласованы.
+    pub fn is_clean(&self) -> bool {
+        *self == ValidationOutcome::Clean
+    }
+
+    /// Заключения о подозрительных долях (пусто, если таких нет).
+    pub fn verdicts(&self) -> &[ShareVerdict] {
+        match self {
+            ValidationOutcome::Suspicious(verdicts) => verdicts,
+            _ => &[],
+        }
+    }
+
+    /// Номера подозрительных долей по возрастанию.
+    pub fn suspicious(&self) -> Vec<usize> {
+        self.verdicts().iter().map(ShareVerdict::number).collect()
+    }
+}
+
+/// Кого считать подозрительным, если по биту доли разделились поровну.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Доли, давшие 0 (поведение по умолчанию).
+    #[default]
+    BlameZeros,
+    /// Доли, давшие 1.
+    BlameOnes,
+    /// Доли обеих групп.
+    BlameBoth,
+    /// Никого: бит не учитывается.
+    BlameNone,
+}
+
+/// Правила проверки долей. По умолчанию совпадают с [SharingScheme::validate]:
+/// доля подозрительна, если хотя бы по одному биту она в меньшинстве.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationPolicy {
+    /// Сколько спорных битов, где доля в меньшинстве, нужно, чтобы признать её подозрительной.
+    pub min_conflicts: u32,
+    /// Сколько долей должно противоречить доле по биту, чтобы бит засчитывался против неё.
+    pub confirmations: usize,
+    /// Правило для битов, по которым доли разделились поровну.
+    pub tie_break: TieBreak,
+}
+
+impl Default for ValidationPolicy {
+    fn default() -> Self {
+        ValidationPolicy { min_conflicts: 1, confirmations: 1, tie_break: TieBreak::default() }
+    }
+}
+
+/// Голоса долей по битам секрета: сколько знающих бит долей дали 1 и сколько 0.
+///
+/// Подсчёт и решения по битам не содержат ветвлений и обращений к памяти, зависящих
+/// от значений долей: счётчики увеличиваются маскированной арифметикой, сравнения
+/// выполняются через [subtle]. Результат -- маски битов, по которым уже принято решение.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Tally {
+    /// Число долей, давших 1, по битам.
+    ones: [u64; 32],
+    /// Число долей, давших 0, по битам.
+    zeros: [u64; 32],
+}
+
+impl Tally {
+    /// Учёт доли со значением data, знающей биты маски known.
+    pub(crate) fn add(&mut self, known: u32, data: u32) {
+        for bit in 0..32 {
+            let (knows, value) = (u64::from(known >> bit & 1), u64::from(data >> bit & 1));
+            self.ones[bit] += knows & value;
+            self.zeros[bit] += knows & (value ^ 1);
+        }
+    }
+
+    /// Голоса за 1 и за 0 по биту.
+    pub(crate) fn votes(&self, bit: u32) -> (usize, usize) {
+        (self.ones[bit as usize] as usize, self.zeros[bit as usize] as usize)
+    }
+
+    /// Маска битов, которые известны хотя бы одной учтённой доле, давшей значение,
+    /// противоположное соответствующему биту data.
+    pub(crate) fn opposed(&self, data: u32) -> u32 {
+        let mut res = 0;
+        for bit in 0..32 {
+            let value = Choice::from((data >> bit & 1) as u8);
+            let other = u64::conditional_select(&self.ones[bit], &self.zeros[bit], value);
+            res |= u32::from((!other.ct_eq(&0)).unwrap_u8()) << bit;
+        }
+        res
+    }
+
+    /// Маска битов, по которым доли разошлись.
+    pub(crate) fn disputed(&self) -> u32 {
+        let mut res = 0;
+        for bit in 0..32 {
+            let disputed = !self.ones[bit].ct_eq(&0) & !self.zeros[bit].ct_eq(&0);
+            res |= u32::from(disputed.unwrap_u8()) << bit;
+        }
+        res
+    }
+
+    /// Хотя бы один бит известен сразу двум учтённым долям.
+    pub(crate) fn checked(&self) -> bool {
+        let mut checked = Choice::from(0);
+        for bit in 0..32 {
+            checked |= (self.ones[bit] + self.zeros[bit]).ct_gt(&1);
+        }
+        bool::from(checked)
+    }
+
+    /// Маски битов, по которым большинство дало 1, и битов, по которым голоса разделились поровну.
+    pub(crate) fn majority(&self) -> (u32, u32) {
+        let (mut winners, mut ties) = (0u32, 0u32);
+        for bit in 0..32 {
+            winners |= u32::from(self.ones[bit].ct_gt(&self.zeros[bit]).unwrap_u8()) << bit;
+            ties |= u32::from(self.ones[bit].ct_eq(&self.zeros[bit]).unwrap_u8()) << bit;
+        }
+        (winners, ties)
+    }
+
+    /// Маски битов, по которым под подозрение попадают давшие 1 и давшие 0.
+    fn blame(&self, policy: &ValidationPolicy) -> (u32, u32) {
+        let (tie_ones, tie_zeros) = match policy.tie_break {
+            TieBreak::BlameZeros => (0, 1),
+            TieBreak::BlameOnes => (1, 0),
+            TieBreak::BlameBoth => (1, 1),
+            TieBreak::BlameNone => (0, 0),
+        };
+        let (tie_ones, tie_zeros) = (Choice::from(tie_ones), Choice::from(tie_zeros));
+        let confirmations = policy.confirmations as u64;
+        let (mut blame_ones, mut blame_zeros) = (0u32, 0u32);
+        for bit in 0..32 {
+            let (o, z) = (self.ones[bit], self.zeros[bit]);
+            let disputed = !o.ct_eq(&0) & !z.ct_eq(&0);
+            let tie = o.ct_eq(&z);
+            let against_ones = disputed & (o.ct_lt(&z) | (tie & tie_ones)) & !z.ct_lt(&confirmations);
+            let against_zeros = disputed & (o.ct_gt(&z) | (tie & tie_zeros)) & !o.ct_lt(&confirmations);
+            blame_ones |= u32::from(against_ones.unwrap_u8()) << bit;
+            blame_zeros |= u32::from(against_zeros.unwrap_u8()) << bit;
+        }
+        (blame_ones, blame_zeros)
+    }
+}
+
+/// Проверка набора долей по данным правилам; mask(i) -- маска известных битов i-й строки,
+/// foreign отличает доли чужих схем, см. [crate::scheme_impl::HSS::is_foreign]. Вместе
+/// с итогом возвращается маска битов, по которым доли схемы разошлись.
+///
+/// Голоса считаются `Tally` без ветвлений по значениям долей. От значений долей зависит
+/// только разбор уже принятого решения -- какие доли подозрительны.
+pub(crate) fn examine(foreign: impl Fn(&Part) -> bool, mask: impl Fn(usize) -> u32, parts: &[Part], policy: &ValidationPolicy) -> (ValidationOutcome, u32) {
+    let mut tally = Tally::default();
+    for part in parts.iter().filter(|part| !foreign(part)) {
+        tally.add(mask(part.number()), part.data());
+    }
+    let (blame_ones, blame_zeros) = tally.blame(policy);
+    let conflicts = |part: &Part| mask(part.number()) & ((part.data() & blame_ones) | (!part.data() & blame_zeros));
+    let flagged = |part: &Part| conflicts(part).count_ones() >= policy.min_conflicts.max(1);
+
+    let mut numbers: Vec<usize> = parts
+        .iter()
+        .filter(|part| foreign(part) || flagged(part))
+        .map(|part| part.number())
+        .collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    if numbers.is_empty() {
+        let outcome = if tally.checked() { ValidationOutcome::Clean } else { ValidationOutcome::Inconclusive };
+        return (outcome, tally.disputed());
+    }
+    let verdicts = numbers
+        .into_iter()
+        .map(|number| {
+            if parts.iter().any(|part| part.number() == number && foreign(part)) {
+                return ShareVerdict::foreign(number);
+            }
+            let mut votes: Vec<BitVote> = Vec::new();
+            for part in parts.iter().filter(|part| part.number() == number && flagged(part)) {
+                let disputed = conflicts(part);
+                for bit in (0..u32::BITS).filter(|&bit| disputed >> bit & 1 == 1) {
+                    if votes.iter().all(|vote| vote.bit != bit) {
+                        let (o, z) = tally.votes(bit);
+                        let (agree, disagree) = if part.data() >> bit & 1 == 1 { (o, z) } else { (z, o) };
+                        votes.push(BitVote { bit, agree, disagree });
+                    }
+                }
+            }
+            votes.sort_unstable_by_key(|vote| vote.bit);
+            ShareVerdict::conflicting(number, votes)
+        })
+        .collect();
+    (ValidationOutcome::Suspicious(verdicts), tally.disputed())
+}
+
+/// Итог [examine] без маски спорных битов.
+pub(crate) fn judge(foreign: impl Fn(&Part) -> bool, mask: impl Fn(usize) -> u32, parts: &[Part], policy: &ValidationPolicy) -> ValidationOutcome {
+    examine(foreign, mask, parts, policy).0
+}
+
+/// Результат проверки одного набора долей.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Номера подозрительных долей по возрастанию.
+    suspicious: Vec<usize>,
+    /// Маска битов секрета, по которым доли набора противоречат друг другу.
+    conflicts: u32,
+}
+
+/// Реализация методов результата проверки.
+impl ValidationReport {
+    /// Возвращение значения поля suspicious.
+    pub fn suspicious(&self) -> &[usize] {
+        &self.suspicious
+    }
+
+    /// Возвращение значения поля conflicts.
+    pub fn conflicts(&self) -> u32 {
+        self.conflicts
+    }
+
+    /// Набор не содержит подозрительных долей.
+    pub fn is_valid(&self) -> bool {
+        self.suspicious.is_empty()
+    }
+}
+
+/// Проверка набора долей схемы hss по заранее вычисленным маскам строк с правилами
+/// по умолчанию, то есть тем же [judge], что и [SharingScheme::validate].
+pub(crate) fn check(hss: &HSS, masks: &[u32], parts: &[Part]) -> ValidationReport {
+    let mask = |number: usize| masks.get(number).copied().unwrap_or(0);
+    let (outcome, conflicts) = examine(|part| hss.is_foreign(part), mask, parts, &ValidationPolicy::default());
+    ValidationReport { suspicious: outcome.suspicious(), conflicts }
+}
+
+/// Пакетная проверка.
+impl HadamardSSS {
+    /// Маски известных битов всех строк.
+    pub(crate) fn masks(&self) -> Vec<u32> {
+        (0..self.hss.mtx_len()).map(|i| self.hss.mask(i)).collect()
+    }
+
+    /// Проверка набора долей по данным правилам. С правилами по умолчанию результат
+    /// совпадает с [SharingScheme::validate].
+    pub fn validate_with(&self, parts: Vec<Part>, policy: &ValidationPolicy) -> ValidationOutcome {
+        self.check_canaries(&parts);
+        judge(|part| self.hss.is_foreign(part), |number| self.hss.mask(number), &parts, policy)
+    }
+
+    /// Проверка многих наборов долей, например при регулярной проверке хранилища долей.
+    pub fn validate_many(&self, sets: &[Vec<Part>]) -> Vec<ValidationReport> {
+        let masks = self.masks();
+        sets.iter()
+            .map(|parts| {
+                self.check_canaries(parts);
+                check(&self.hss, &masks, parts)
+            })
+            .collect()
+    }
+
+    /// То же, что [HadamardSSS::validate_many], но наборы распределяются по threads потокам.
+    pub fn validate_many_parallel(&self, sets: &[Vec<Part>], threads: usize) -> Vec<ValidationReport> {
+        let masks = self.masks();
+        let chunk = sets.len().div_ceil(threads.max(1)).max(1);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = sets
+                .chunks(chunk)
+                .map(|chunk| {
+                    let masks = &masks;
+                    scope.spawn(move || {
+                        chunk.iter()
+                            .map(|parts| {
+                                self.check_canaries(parts);
+                                check(&self.hss, masks, parts)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles.into_iter().flat_map(|handle| handle.join().expect("validation thread panicked")).collect()
+        })
+    }
+}
+
+/// Потоковая проверка: доли поступают по одной, голоса по битам секрета копятся в `Tally`.
+pub struct Validator<'a> {
+    /// Схема, доли которой проверяются.
+    scheme: &'a HadamardSSS,
+    /// Маски известных битов всех строк.
+    masks: Vec<u32>,
+    /// Поступившие доли.
+    parts: Vec<Part>,
+    /// Голоса поступивших долей.
+    tally: Tally,
+}
+
+/// Реализация методов потоковой проверки.
+impl<'a> Validator<'a> {
+    /// Добавление доли. Возвращается маска битов, по которым доля противоречит уже
+    /// поступившим; ненулевая маска означает, что в наборе есть поддельная доля.
+    /// Доля чужой схемы не принимается, ошибки те же, что при восстановлении.
+    pub fn push(&mut self, part: Part) -> Result<u32, &'static str> {
+        self.scheme.hss.check_parts(&[part])?;
+        let mask = self.masks[part.number()];
+        self.scheme.check_canaries(&[part]);
+        let contradicts = mask & self.tally.opposed(part.data());
+        self.tally.add(mask, part.data());
+        self.parts.push(part);
+        Ok(contradicts)
+    }
+
+    /// Число поступивших долей.
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    /// Доли ещё не поступали.
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// Маска битов, по которым поступившие доли противоречат друг другу.
+    pub fn conflicts(&self) -> u32 {
+        self.tally.disputed()
+    }
+
+    /// Текущий результат проверки; совпадает с результатом [HadamardSSS::validate_many]
+    /// для поступивших долей.
+    pub fn report(&self) -> ValidationReport {
+        check(&self.scheme.hss, &self.masks, &self.parts)
+    }
+}
+
+/// Создание потоковой проверки.
+impl HadamardSSS {
+    /// Потоковая проверка долей этой схемы.
+    pub fn validator(&self) -> Validator<'_> {
+        Validator { scheme: self, masks: self.masks(), parts: Vec::new(), tally: Tally::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::matrix;
+    use crate::{SharingScheme, ValidationOutcome};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_matches_validate() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(952);
+        let sets: Vec<Vec<Part>> = (0..200)
+            .map(|_| {
+                let mut parts = hsss.share(rng.gen()).unwrap();
+                for _ in 0..rng.gen_range(0..3) {
+                    let i = rng.gen_range(0..parts.len());
+                    parts[i] = parts[i].blinded(rng.gen::<u32>());
+                }
+                parts.truncate(rng.gen_range(1..=7));
+                parts
+            })
+            .collect();
+        let reports = hsss.validate_many(&sets);
+        for (parts, report) in sets.iter().zip(&reports) {
+            assert_eq!(report.suspicious(), hsss.validate(parts.clone()).suspicious());
+            assert_eq!(report.is_valid(), report.conflicts() == 0);
+        }
+        assert_eq!(hsss.validate_many_parallel(&sets, 4), reports);
+        assert_eq!(hsss.validate_many_parallel(&sets, 0), reports);
+        assert!(hsss.validate_many_parallel(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_validation_outcome() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut parts = hsss.share(314159265).unwrap();
+        assert_eq!(hsss.validate(parts.clone()), ValidationOutcome::Clean);
+        assert_eq!(hsss.validate(parts[0..1].to_vec()), ValidationOutcome::Inconclusive);
+        assert_eq!(hsss.validate(Vec::new()), ValidationOutcome::Inconclusive);
+
+        parts[2] = parts[2].blinded(hsss.hss.mask(2));
+        parts.push(Part::from(9, 0));
+        let outcome = hsss.validate(parts);
+        assert_eq!(outcome.suspicious(), [2, 9]);
+        let verdict = &outcome.verdicts()[0];
+        assert!(!verdict.is_foreign());
+        assert_eq!(verdict.bits().len() as u32, hsss.hss.mask(2).count_ones());
+        // каждый бит доли 2 знают ещё две доли из семи, и обе с ней не согласны
+        assert!(verdict.votes().iter().all(|vote| vote.agree == 1 && vote.disagree == 2));
+        assert!(outcome.verdicts()[1].is_foreign());
+    }
+
+    #[test]
+    fn test_validation_policy() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(962);
+        for _ in 0..100 {
+            let mut parts = hsss.share(rng.gen()).unwrap();
+            let i = rng.gen_range(0..parts.len());
+            parts[i] = parts[i].blinded(rng.gen::<u32>());
+            parts.truncate(rng.gen_range(1..=7));
+            assert_eq!(hsss.validate_with(parts.clone(), &ValidationPolicy::default()), hsss.validate(parts));
+        }
+
+        let mut parts = hsss.share(314159265).unwrap();
+        let mask = hsss.hss.mask(2);
+        parts[2] = parts[2].blinded(mask);
+        let strict = ValidationPolicy { confirmations: 3, ..Default::default() };
+        assert_eq!(hsss.validate_with(parts.clone(), &strict), ValidationOutcome::Clean);
+        let one_bit = parts[2].blinded(mask ^ (mask & mask.wrapping_neg()));
+        parts[2] = one_bit;
+        assert_eq!(hsss.validate(parts.clone()).suspicious(), [2]);
+        let lenient = ValidationPolicy { min_conflicts: 2, ..Default::default() };
+        assert_eq!(hsss.validate_with(parts, &lenient), ValidationOutcome::Clean);
+
+        // две доли с общими битами, расходящиеся по ним: голоса делятся поровну
+        let parts = hsss.share(0).unwrap();
+        let pair = vec![Part::from(0, 0).with_fingerprint(hsss.fingerprint()), parts[1].blinded(!parts[1].data() & hsss.hss.mask(1))];
+        let tie = |tie_break| hsss.validate_with(pair.clone(), &ValidationPolicy { tie_break, ..Default::default() }).suspicious();
+        assert_eq!(tie(TieBreak::BlameZeros), [0]);
+        assert_eq!(tie(TieBreak::BlameOnes), [1]);
+        assert_eq!(tie(TieBreak::BlameBoth), [0, 1]);
+        assert!(tie(TieBreak::BlameNone).is_empty());
+    }
+
+    #[test]
+    fn test_conflicting_bits() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut parts = hsss.share(314159265).unwrap();
+        parts[2] = parts[2].blinded(0xF);
+        parts.push(Part::from(9, 0));
+        let report = &hsss.validate_many(&[parts])[0];
+        assert_eq!(report.conflicts() & !0xF, 0);
+        assert_ne!(report.conflicts(), 0);
+        assert_eq!(report.suspicious(), [2, 9]);
+    }
+
+    #[test]
+    fn test_validator_matches_batch() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(953);
+        for _ in 0..100 {
+            let mut parts = hsss.share(rng.gen()).unwrap();
+            let i = rng.gen_range(0..parts.len());
+            parts[i] = parts[i].blinded(rng.gen::<u32>());
+            let mut validator = hsss.validator();
+            for (k, part) in parts.iter().enumerate() {
+                validator.push(*part).unwrap();
+                assert_eq!(validator.report(), check(&hsss.hss, &hsss.masks(), &parts[..=k]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_validator_flags_on_arrival() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let mut validator = hsss.validator();
+        assert!(validator.is_empty());
+        for part in &parts[0..3] {
+            assert_eq!(validator.push(*part), Ok(0));
+        }
+        let forged = parts[3].blinded(hsss.hss.mask(3));
+        let contradicts = validator.push(forged).unwrap();
+        assert_ne!(contradicts, 0);
+        assert_eq!(validator.conflicts(), contradicts);
+        assert_eq!(validator.len(), 4);
+        assert!(validator.push(Part::from(7, 0)).is_err());
+    }
+}
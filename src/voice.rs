@@ -0,0 +1,174 @@
+//! Кодирование долей для чтения вслух (Crockford base32).
+//!
+//! При восстановлении по телефону доля диктуется по символам. Алфавит Crockford base32
+//! не содержит похожих на слух и на вид символов I, L, O и U; при чтении I и L понимаются
+//! как 1, O -- как 0, регистр не важен. Номер доли (u16), значение (u32) и отпечаток схемы
+//! (u32) записываются в big-endian и кодируются шестнадцатью символами, которые делятся
+//! на четыре группы по четыре. За каждой группой следует контрольный символ, поэтому ошибка
+//! в диктовке обнаруживается сразу, а переспросить достаточно одну группу. Например, доля
+//! с номером 3, значением 0xDEADBEEF и отпечатком 7 записывается как `001XB-XBDY7-XW000-0007Z`.
+//!
+//! Записи формата без отпечатка (две группы по пять символов, например `001XXD-BDYXWC`)
+//! тоже читаются.
+use crate::scheme_impl::Part;
+
+/// Алфавит Crockford base32.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+/// Число символов данных в группе.
+const GROUP: usize = 4;
+/// Число групп.
+const GROUPS: usize = 4;
+/// Число символов данных в группе записи без отпечатка.
+const LEGACY_GROUP: usize = 5;
+/// Число групп записи без отпечатка.
+const LEGACY_GROUPS: usize = 2;
+/// Ошибки контрольных символов по номерам групп.
+const MISMATCH: [&str; GROUPS] = ["checksum mismatch in group 1", "checksum mismatch in group 2", "checksum mismatch in group 3", "checksum mismatch in group 4"];
+
+/// Умножение в поле GF(32) с образующим многочленом x^5 + x^2 + 1.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut res = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            res ^= a;
+        }
+        a <<= 1;
+        if a & 32 != 0 {
+            a ^= 0b100101;
+        }
+        b >>= 1;
+    }
+    res
+}
+
+/// Контрольный символ группы: сумма значений символов с весами x, x^2, ... в поле GF(32).
+/// Веса различны и ненулевые, поэтому обнаруживается любая одиночная ошибка
+/// и любая перестановка двух символов группы.
+fn check(values: &[u8]) -> u8 {
+    let mut weight = 1;
+    values.iter().fold(0, |acc, &v| {
+        weight = gf_mul(weight, 2);
+        acc ^ gf_mul(v, weight)
+    })
+}
+
+/// Значение символа с учётом правил Crockford.
+fn value(c: char) -> Option<u8> {
+    let c = match c.to_ascii_uppercase() {
+        'O' => '0',
+        'I' | 'L' => '1',
+        c => c,
+    };
+    ALPHABET.iter().position(|&a| char::from(a) == c).map(|v| v as u8)
+}
+
+/// Кодирование доли. Возвращается ошибка, если номер доли не помещается в u16
+/// или у доли нет отпечатка схемы.
+/// # Пример
+/// ```
+/// use hadamard_sss::{voice, Part};
+///
+/// let text = voice::encode(&Part::from(3, 0xDEADBEEF).with_fingerprint(7)).unwrap();
+/// assert_eq!(text, "001XB-XBDY7-XW000-0007Z");
+/// assert_eq!(voice::decode("OO1xb xbdy7 xwOOO OOO7z").unwrap().data(), 0xDEADBEEF);
+/// ```
+pub fn encode(part: &Part) -> Result<String, &'static str> {
+    let number = u16::try_from(part.number()).map_err(|_| "part number is too large for voice encoding")?;
+    let fingerprint = part.fingerprint().ok_or("part has no scheme fingerprint")?;
+    // 80 бит полезной нагрузки ровно заполняют 16 символов
+    let payload = (u128::from(number) << 64) | (u128::from(part.data()) << 32) | u128::from(fingerprint);
+    let values: Vec<u8> = (0..GROUP * GROUPS).rev().map(|i| (payload >> (5 * i) & 31) as u8).collect();
+    let groups: Vec<String> = values
+        .chunks(GROUP)
+        .map(|chunk| chunk.iter().chain([check(chunk)].iter()).map(|&v| char::from(ALPHABET[v as usize])).collect())
+        .collect();
+    Ok(groups.join("-"))
+}
+
+/// Декодирование доли, записанной [encode]. Пробелы и дефисы игнорируются.
+/// Ошибка указывает, в какой группе не сошёлся контрольный символ.
+pub fn decode(text: &str) -> Result<Part, &'static str> {
+    let values = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| value(c).ok_or("invalid character"))
+        .collect::<Result<Vec<u8>, _>>()?;
+    let group = match values.len() {
+        len if len == (GROUP + 1) * GROUPS => GROUP,
+        len if len == (LEGACY_GROUP + 1) * LEGACY_GROUPS => LEGACY_GROUP,
+        _ => return Err("wrong number of characters"),
+    };
+    let mut payload = 0u128;
+    for (index, chunk) in values.chunks(group + 1).enumerate() {
+        if check(&chunk[..group]) != chunk[group] {
+            return Err(MISMATCH[index]);
+        }
+        payload = chunk[..group].iter().fold(payload, |acc, &v| (acc << 5) | u128::from(v));
+    }
+    if group == GROUP {
+        return Ok(Part::from((payload >> 64) as usize, (payload >> 32) as u32).with_fingerprint(payload as u32));
+    }
+    // в записи без отпечатка за 48 битами нагрузки следуют 2 нулевых бита
+    if payload & 3 != 0 {
+        return Err("invalid padding bits");
+    }
+    let payload = payload >> 2;
+    Ok(Part::from((payload >> 32) as usize, payload as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        for (number, data) in [(0, 0), (6, 314159265), (u16::MAX as usize, u32::MAX)] {
+            let text = encode(&Part::from(number, data).with_fingerprint(!data)).unwrap();
+            assert_eq!(text.len(), 23);
+            assert!(!text.contains(['I', 'L', 'O', 'U']));
+            let part = decode(&text).unwrap();
+            assert_eq!((part.number(), part.data(), part.fingerprint()), (number, data, Some(!data)));
+        }
+        assert!(encode(&Part::from(1 << 16, 0).with_fingerprint(0)).is_err());
+        assert_eq!(encode(&Part::from(1, 0)).err(), Some("part has no scheme fingerprint"));
+        let legacy = decode("003DXG-BDYXWC").unwrap();
+        assert_eq!((legacy.number(), legacy.data(), legacy.fingerprint()), (6, 0xDEADBEEF, None));
+    }
+
+    #[test]
+    fn test_lenient_reading() {
+        let text = encode(&Part::from(0, 0).with_fingerprint(0)).unwrap();
+        assert_eq!(text, "00000-00000-00000-00000");
+        let part = decode("oOoOo OOOoo 0oOOo OOOOO").unwrap();
+        assert_eq!((part.number(), part.data()), (0, 0));
+        let text = encode(&Part::from(1, 0x11111111).with_fingerprint(0x11111111)).unwrap();
+        let misheard = text.replace('1', "l").to_lowercase();
+        assert_eq!(decode(&misheard).unwrap().data(), 0x11111111);
+    }
+
+    #[test]
+    fn test_errors_detected() {
+        let text = encode(&Part::from(4, 0xC0FFEE).with_fingerprint(0xF00D)).unwrap();
+        let corrupt = |position: usize, symbol: u8| {
+            let mut bytes = text.clone().into_bytes();
+            bytes[position] = symbol;
+            decode(std::str::from_utf8(&bytes).unwrap())
+        };
+        // любая замена одного символа данных обнаруживается
+        for position in (0..text.len()).filter(|position| position % 6 < 4) {
+            for &symbol in ALPHABET.iter().filter(|&&a| a != text.as_bytes()[position]) {
+                assert_eq!(corrupt(position, symbol).err(), Some(MISMATCH[position / 6]));
+            }
+        }
+        // как и перестановка двух различных символов группы
+        for (a, b) in [(0, 3), (1, 2), (6, 9), (13, 14), (18, 21)] {
+            let mut bytes = text.clone().into_bytes();
+            if bytes[a] != bytes[b] {
+                bytes.swap(a, b);
+                assert!(decode(std::str::from_utf8(&bytes).unwrap()).is_err());
+            }
+        }
+        assert_eq!(decode("00000-00000-00000-0000").err(), Some("wrong number of characters"));
+        assert_eq!(decode("0000U-00000-00000-00000").err(), Some("invalid character"));
+    }
+}
@@ -0,0 +1,93 @@
+//! Водяные знаки в битах-заполнителях долей.
+//!
+//! Биты доли, не известные её владельцу, обычно случайны. При разделении с водяным знаком
+//! они берутся из HKDF-SHA256 от ключа дилера, случайного значения сеанса и номера строки.
+//! Без ключа такие биты неотличимы от случайных, а дилер по утёкшей доле может определить,
+//! копия какого участника была раскрыта (даже если номер доли был изменён).
+use crate::scheme_impl::Part;
+use crate::HadamardSSS;
+use hkdf::Hkdf;
+use rand::RngCore;
+use sha2::Sha256;
+
+/// Ключ дилера, из которого выводятся водяные знаки.
+pub type WatermarkKey = [u8; 32];
+
+/// Случайное значение сеанса разделения, которое дилер хранит вместе с ключом.
+pub type WatermarkNonce = [u8; 16];
+
+/// Вычисление водяного знака для строки с данным номером.
+pub(crate) fn watermark(key: &WatermarkKey, nonce: &WatermarkNonce, label: &[u8], number: usize) -> u32 {
+    let mut out = [0u8; 4];
+    Hkdf::<Sha256>::new(Some(nonce), key)
+        .expand_multi_info(&[label, &(number as u64).to_le_bytes()], &mut out)
+        .expect("4 bytes is a valid HKDF output length");
+    u32::from_le_bytes(out)
+}
+
+/// Метка HKDF для водяных знаков.
+const LABEL: &[u8] = b"hadamard_sss watermark";
+
+/// Разделение секрета с водяными знаками и поиск источника утечки.
+impl HadamardSSS {
+    /// Разделение секрета, при котором биты-заполнители каждой доли являются водяным знаком её строки.
+    /// Возвращает доли и значение сеанса, которое понадобится для отслеживания.
+    pub fn share_watermarked(&self, secret: u32, key: &WatermarkKey) -> (Vec<Part>, WatermarkNonce) {
+        let mut nonce = [0u8; 16];
+        crate::entropy::rng().fill_bytes(&mut nonce);
+        let parts = self.hss.share_padded(secret, |i| watermark(key, &nonce, LABEL, i));
+        (parts, nonce)
+    }
+
+    /// Определение строки, водяной знак которой содержится в доле.
+    ///
+    /// Сначала проверяется строка, указанная в доле, затем все остальные. Возвращается `None`,
+    /// если ни одна строка не подходит, то есть доля получена не в данном сеансе или изменена.
+    pub fn trace_watermark(&self, part: &Part, key: &WatermarkKey, nonce: &WatermarkNonce) -> Option<usize> {
+        let n = self.hss.mtx_len();
+        let matches = |i: usize| {
+            let padding = !self.hss.mask(i);
+            (part.data() ^ watermark(key, nonce, LABEL, i)) & padding == 0
+        };
+        std::iter::once(part.number())
+            .filter(|&i| i < n)
+            .chain(0..n)
+            .find(|&i| matches(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SharingScheme;
+    use crate::fixtures::matrix;
+
+    fn scheme() -> HadamardSSS {
+        HadamardSSS::from(&matrix()).unwrap()
+    }
+
+    #[test]
+    fn test_watermarked_shares_reconstruct() {
+        let hsss = scheme();
+        let (parts, _) = hsss.share_watermarked(314159265, &[1u8; 32]);
+        assert_eq!(hsss.reconstruct(parts[0..5].to_vec()).unwrap(), 314159265);
+        assert!(hsss.validate(parts[0..5].to_vec()).is_clean());
+    }
+
+    #[test]
+    fn test_trace_leaked_share() {
+        let hsss = scheme();
+        let key = [7u8; 32];
+        let (parts, nonce) = hsss.share_watermarked(271828, &key);
+        for part in &parts {
+            assert_eq!(hsss.trace_watermark(part, &key, &nonce), Some(part.number()));
+            let renumbered = Part::from(0, part.data());
+            assert_eq!(hsss.trace_watermark(&renumbered, &key, &nonce), Some(part.number()));
+        }
+        let other_key = [8u8; 32];
+        assert_eq!(hsss.trace_watermark(&parts[3], &other_key, &nonce), None);
+
+        let plain = hsss.share(271828).unwrap();
+        assert!(plain.iter().filter(|p| hsss.trace_watermark(p, &key, &nonce).is_some()).count() < 2);
+    }
+}
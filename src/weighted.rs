@@ -0,0 +1,95 @@
+//! Взвешенные участники: несколько строк матрицы на одну долю.
+//!
+//! [ParticipantRows] сопоставляет участнику несколько строк, и его доля засчитывается
+//! в порог схемы как число этих строк: например, доля администратора с весом 3 заменяет
+//! три обычные доли. Проверка сверяет доли строк и сообщает номера участников.
+use crate::scheme_impl::{ChunkedPart, ParticipantRows};
+use crate::validation::{ShareVerdict, ValidationOutcome};
+use crate::{HadamardSSS, SharingScheme};
+
+/// Итог проверки долей строк в терминах участников: подозрительна доля участника,
+/// которому принадлежит хотя бы одна подозрительная строка; foreign -- номера долей
+/// участников, не подходящих к соответствию.
+pub(crate) fn participant_outcome(mapping: &ParticipantRows, outcome: ValidationOutcome, foreign: &[usize]) -> ValidationOutcome {
+    let mut verdicts: Vec<ShareVerdict> = foreign.iter().map(|&number| ShareVerdict::foreign(number)).collect();
+    let mut suspicious: Vec<usize> = outcome.suspicious().iter().filter_map(|&row| mapping.owner(row)).collect();
+    suspicious.sort_unstable();
+    suspicious.dedup();
+    for participant in suspicious {
+        let votes = outcome
+            .verdicts()
+            .iter()
+            .filter(|verdict| mapping.owner(verdict.number()) == Some(participant))
+            .flat_map(|verdict| verdict.votes().iter().copied())
+            .collect();
+        verdicts.push(ShareVerdict::conflicting(participant, votes));
+    }
+    if verdicts.is_empty() {
+        return outcome;
+    }
+    verdicts.sort_by_key(ShareVerdict::number);
+    ValidationOutcome::Suspicious(verdicts)
+}
+
+/// Разделение между взвешенными участниками.
+impl HadamardSSS {
+    /// Разделение секрета между участниками соответствия mapping; i-я доля принадлежит
+    /// i-му участнику. Возвращается ошибка, если соответствие выдаёт строки вне схемы.
+    pub fn share_weighted(&self, secret: u32, mapping: &ParticipantRows) -> Result<Vec<ChunkedPart>, &'static str> {
+        if mapping.span() > self.hss.mtx_len() {
+            return Err("scheme mismatch: part number exceeds the order of the scheme");
+        }
+        mapping.group(&self.share(secret)?)
+    }
+
+    /// Восстановление секрета по долям участников: порог считается по сумме весов
+    /// различных предъявленных участников.
+    pub fn reconstruct_weighted(&self, parts: Vec<ChunkedPart>, mapping: &ParticipantRows) -> Result<u32, &'static str> {
+        self.reconstruct(mapping.expand(&parts)?)
+    }
+
+    /// Проверка долей участников; в заключениях стоят номера участников.
+    pub fn validate_weighted(&self, parts: Vec<ChunkedPart>, mapping: &ParticipantRows) -> ValidationOutcome {
+        let (valid, foreign): (Vec<ChunkedPart>, Vec<ChunkedPart>) = parts
+            .into_iter()
+            .partition(|part| mapping.rows(part.number()).is_some_and(|rows| rows.len() == part.data().len()));
+        // доли участников уже сверены с соответствием, и expand ошибки не возвращает
+        let rows = mapping.expand(&valid).unwrap_or_default();
+        let foreign: Vec<usize> = foreign.iter().map(ChunkedPart::number).collect();
+        participant_outcome(mapping, self.validate(rows), &foreign)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::equivalence::classes;
+    use crate::fixtures::matrix;
+
+    #[test]
+    fn test_weighted_threshold() {
+        let hsss = HadamardSSS::from(&matrix()).unwrap();
+        // администратор весом 3 и четыре обычных участника, порог 5 строк
+        let mapping = ParticipantRows::weighted(&[3, 1, 1, 1, 1]).unwrap();
+        let parts = hsss.share_weighted(314159265, &mapping).unwrap();
+        assert_eq!(parts.len(), 5);
+        assert_eq!(hsss.reconstruct_weighted(vec![parts[0].clone(), parts[3].clone(), parts[4].clone()], &mapping), Ok(314159265));
+        assert_eq!(hsss.reconstruct_weighted(parts[1..5].to_vec(), &mapping), Err("less than threshold parties"));
+        assert_eq!(hsss.reconstruct_weighted(vec![parts[0].clone(), parts[0].clone(), parts[1].clone()], &mapping), Err("less than threshold parties"));
+        assert!(hsss.share_weighted(1, &ParticipantRows::weighted(&[4, 4]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_weighted_validation() {
+        let hsss = HadamardSSS::from_matrix(&classes(16).unwrap().next().unwrap()).unwrap();
+        let mapping = ParticipantRows::weighted(&[4, 2, 2, 2, 2, 1, 1, 1]).unwrap();
+        let mut parts = hsss.share_weighted(7, &mapping).unwrap();
+        assert!(hsss.validate_weighted(parts.clone(), &mapping).is_clean());
+        let mut data = parts[0].data().to_vec();
+        data[2] = !data[2];
+        parts[0] = ChunkedPart::from(0, data).with_fingerprint(hsss.fingerprint());
+        parts.push(ChunkedPart::from(5, vec![1, 2]));
+        parts.push(ChunkedPart::from(99, vec![]));
+        assert_eq!(hsss.validate_weighted(parts, &mapping).suspicious(), [0, 5, 99]);
+    }
+}
@@ -0,0 +1,145 @@
+//! Компактный версионированный двоичный формат долей.
+//!
+//! Доля записывается тринадцатью байтами:
+//!
+//! | смещение | длина | содержимое                                      |
+//! |----------|-------|-------------------------------------------------|
+//! | 0        | 1     | версия формата, сейчас [WIRE_VERSION]           |
+//! | 1        | 4     | номер доли, u32 big-endian                      |
+//! | 5        | 4     | значение доли, u32 big-endian                   |
+//! | 9        | 4     | отпечаток схемы, u32 big-endian                 |
+//!
+//! Первый байт всегда задаёт версию, поэтому будущие версии крейта смогут читать доли
+//! текущей версии, а реализации на других языках -- разобрать формат по этой таблице.
+//! Доли версии 1 ([LEGACY_WIRE_LEN] байт, без отпечатка) по-прежнему читаются; у прочитанной
+//! доли нет отпечатка, и схема принимает её только после
+//! [crate::HadamardSSS::set_accept_legacy].
+//!
+//! Доли, сохранённые до появления формата как пара (номер, значение), читает
+//! [Part::from_unversioned]; [HadamardSSS::migrate_share] переводит любую такую долю
+//! в текущую версию, помечая её отпечатком схемы.
+use crate::scheme_impl::Part;
+use crate::HadamardSSS;
+
+/// Текущая версия формата.
+pub const WIRE_VERSION: u8 = 2;
+
+/// Длина доли в формате текущей версии.
+pub const WIRE_LEN: usize = 13;
+
+/// Версия формата без отпечатка схемы.
+pub const LEGACY_WIRE_VERSION: u8 = 1;
+
+/// Длина доли в формате версии 1.
+pub const LEGACY_WIRE_LEN: usize = 9;
+
+/// Длина доли в неявном представлении без байта версии.
+pub const UNVERSIONED_LEN: usize = 8;
+
+/// Двоичный формат доли.
+impl Part {
+    /// Запись доли в двоичном формате. Возвращается ошибка, если номер не помещается в u32
+    /// или у доли нет отпечатка схемы.
+    pub fn to_bytes(&self) -> Result<[u8; WIRE_LEN], &'static str> {
+        let number = u32::try_from(self.number()).map_err(|_| "part number does not fit the wire format")?;
+        let fingerprint = self.fingerprint().ok_or("part has no scheme fingerprint")?;
+        let mut out = [0u8; WIRE_LEN];
+        out[0] = WIRE_VERSION;
+        out[1..5].copy_from_slice(&number.to_be_bytes());
+        out[5..9].copy_from_slice(&self.data().to_be_bytes());
+        out[9..13].copy_from_slice(&fingerprint.to_be_bytes());
+        Ok(out)
+    }
+
+    /// Чтение доли из двоичного формата текущей версии или версии 1.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Part, &'static str> {
+        let word = |offset: usize| u32::from_be_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]]);
+        let len = match bytes.first() {
+            None => return Err("empty share encoding"),
+            Some(&WIRE_VERSION) => WIRE_LEN,
+            Some(&LEGACY_WIRE_VERSION) => LEGACY_WIRE_LEN,
+            Some(_) => return Err("unsupported wire format version"),
+        };
+        if bytes.len() != len {
+            return Err("wire encoding has wrong length");
+        }
+        let part = Part::from(word(1) as usize, word(5));
+        Ok(if len == WIRE_LEN { part.with_fingerprint(word(9)) } else { part })
+    }
+
+    /// Чтение доли в неявном представлении, в котором доли хранились до появления формата:
+    /// номер и значение, оба u32 big-endian, без байта версии. У прочитанной доли нет отпечатка.
+    pub fn from_unversioned(bytes: &[u8]) -> Result<Part, &'static str> {
+        let bytes: [u8; UNVERSIONED_LEN] = bytes.try_into().map_err(|_| "wire encoding has wrong length")?;
+        let number = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let data = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        Ok(Part::from(number as usize, data))
+    }
+}
+
+/// Перевод сохранённых долей в текущий формат.
+impl HadamardSSS {
+    /// Запись доли без отпечатка, например прочитанной [Part::from_unversioned] или из формата
+    /// версии 1, в формате текущей версии с отпечатком данной схемы. Доля с отпечатком
+    /// переводится, только если отпечаток совпадает с отпечатком схемы.
+    pub fn migrate_share(&self, legacy: Part) -> Result<[u8; WIRE_LEN], &'static str> {
+        if legacy.fingerprint().is_some_and(|fingerprint| fingerprint != self.fingerprint()) {
+            return Err("scheme mismatch: part fingerprint does not match the scheme");
+        }
+        let stamped = legacy.with_fingerprint(self.fingerprint());
+        self.hss.check_parts(&[stamped])?;
+        stamped.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SharingScheme;
+    #[test]
+    fn test_wire_format() {
+        let part = Part::from(6, 0xDEADBEEF).with_fingerprint(0x01020304);
+        let bytes = part.to_bytes().unwrap();
+        assert_eq!(bytes, [2, 0, 0, 0, 6, 0xDE, 0xAD, 0xBE, 0xEF, 1, 2, 3, 4]);
+        let decoded = Part::from_bytes(&bytes).unwrap();
+        assert_eq!((decoded.number(), decoded.data(), decoded.fingerprint()), (6, 0xDEADBEEF, Some(0x01020304)));
+        let legacy = Part::from_bytes(&[1, 0, 0, 0, 6, 0xDE, 0xAD, 0xBE, 0xEF]).unwrap();
+        assert_eq!((legacy.number(), legacy.data(), legacy.fingerprint()), (6, 0xDEADBEEF, None));
+
+        assert_eq!(Part::from_bytes(&[]).err(), Some("empty share encoding"));
+        assert_eq!(Part::from_bytes(&bytes[..9]).err(), Some("wire encoding has wrong length"));
+        assert_eq!(Part::from_bytes(&[3, 0, 0, 0, 6, 0, 0, 0, 0]).err(), Some("unsupported wire format version"));
+        assert_eq!(Part::from(6, 0).to_bytes().err(), Some("part has no scheme fingerprint"));
+        #[cfg(target_pointer_width = "64")]
+        assert!(Part::from(1 << 32, 0).with_fingerprint(0).to_bytes().is_err());
+    }
+
+    #[test]
+    fn test_migrate_share() {
+        let hsss = HadamardSSS::from(&crate::fixtures::matrix()).unwrap();
+        let parts = hsss.share(314159265).unwrap();
+        let stored: Vec<[u8; UNVERSIONED_LEN]> = parts.iter().map(|part| {
+            let mut bytes = [0u8; UNVERSIONED_LEN];
+            bytes[..4].copy_from_slice(&(part.number() as u32).to_be_bytes());
+            bytes[4..].copy_from_slice(&part.data().to_be_bytes());
+            bytes
+        }).collect();
+        let legacy = Part::from_unversioned(&stored[3]).unwrap();
+        assert_eq!((legacy.number(), legacy.data(), legacy.fingerprint()), (3, parts[3].data(), None));
+        assert_eq!(Part::from_unversioned(&stored[3][..7]).err(), Some("wire encoding has wrong length"));
+
+        let migrated: Vec<Part> = stored.iter()
+            .map(|bytes| Part::from_bytes(&hsss.migrate_share(Part::from_unversioned(bytes).unwrap()).unwrap()).unwrap())
+            .collect();
+        assert!(migrated.iter().zip(&parts).all(|(a, b)| a.to_bytes() == b.to_bytes()));
+        assert_eq!(hsss.reconstruct(migrated[0..5].to_vec()).unwrap(), 314159265);
+        let version1 = Part::from_bytes(&[1, 0, 0, 0, 2, 0, 0, 0, 7]).unwrap();
+        assert_eq!(hsss.migrate_share(version1).unwrap()[..9], [2, 0, 0, 0, 2, 0, 0, 0, 7]);
+        assert_eq!(hsss.migrate_share(parts[1]).unwrap(), parts[1].to_bytes().unwrap());
+
+        assert_eq!(hsss.migrate_share(Part::from(7, 0)).err(), Some("scheme mismatch: part number exceeds the order of the scheme"));
+        assert_eq!(hsss.migrate_share(Part::from(1, 0).with_fingerprint(!hsss.fingerprint())).err(),
+                   Some("scheme mismatch: part fingerprint does not match the scheme"));
+    }
+}